@@ -0,0 +1,83 @@
+//! Benchmarks for validating, staging, and advancing large setup graphs.
+//!
+//! Run with `cargo bench --features testing`.
+
+use bevy_ecs::prelude::*;
+use bird_barrier::testing::{GeneratedKey, RandomGraph};
+use bird_barrier::{SetupTracker, advance_setup};
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+const SIZES: [usize; 3] = [100, 1_000, 5_000];
+
+/// Creates a world with a `SetupTracker<GeneratedKey>` holding a random graph of `providers`
+/// providers.
+fn world_with_graph(providers: usize) -> World {
+	let mut world = World::new();
+	let on_finished = world.register_system(|| {});
+	world.insert_resource(SetupTracker::<GeneratedKey>::new(on_finished));
+	RandomGraph::new(providers).seed(42).register(&mut world);
+	world
+}
+
+fn validate(c: &mut Criterion) {
+	let mut group = c.benchmark_group("validate");
+	for size in SIZES {
+		let world = world_with_graph(size);
+		let tracker = world.resource::<SetupTracker<GeneratedKey>>();
+		group.bench_with_input(BenchmarkId::from_parameter(size), tracker, |b, tracker| {
+			b.iter(|| black_box(tracker.validation_errors()));
+		});
+	}
+	group.finish();
+}
+
+fn stages(c: &mut Criterion) {
+	let mut group = c.benchmark_group("stages");
+	for size in SIZES {
+		let world = world_with_graph(size);
+		let tracker = world.resource::<SetupTracker<GeneratedKey>>();
+		group.bench_with_input(BenchmarkId::from_parameter(size), tracker, |b, tracker| {
+			b.iter(|| black_box(tracker.stages()));
+		});
+	}
+	group.finish();
+}
+
+fn advance(c: &mut Criterion) {
+	let mut group = c.benchmark_group("advance_setup");
+	for size in SIZES {
+		// A single frame from a fresh graph, where only the roots are ready to run
+		group.bench_function(BenchmarkId::new("first_frame", size), |b| {
+			b.iter_batched(
+				|| world_with_graph(size),
+				|mut world| {
+					advance_setup::<GeneratedKey>(&mut world);
+					world
+				},
+				BatchSize::LargeInput,
+			);
+		});
+		// Every frame until setup finishes
+		group.bench_function(BenchmarkId::new("to_completion", size), |b| {
+			b.iter_batched(
+				|| world_with_graph(size),
+				|mut world| {
+					while !world
+						.resource::<SetupTracker<GeneratedKey>>()
+						.last_progress()
+						.finished()
+					{
+						advance_setup::<GeneratedKey>(&mut world);
+					}
+					world
+				},
+				BatchSize::LargeInput,
+			);
+		});
+	}
+	group.finish();
+}
+
+criterion_group!(benches, validate, stages, advance);
+criterion_main!(benches);
@@ -0,0 +1,98 @@
+//! Derive macros for [`bird_barrier`](https://docs.rs/bird_barrier).
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Index, parse_macro_input, spanned::Spanned};
+
+/// Derives `AssetCollection` for a struct, chaining the asset IDs of each of its fields.
+///
+/// Every field must implement `AssetIds`, which covers `Handle<T>`, `UntypedHandle`, and
+/// `Vec`s, arrays, `Option`s, and `HashMap` values of them. Fields that don't hold assets can be
+/// skipped with `#[asset_collection(skip)]`.
+///
+/// The generated impl refers to the crate as `::bird_barrier`. If it is renamed in `Cargo.toml`,
+/// or re-exported from another crate, give its path with `#[bird_barrier(crate = path)]` on the
+/// struct, e.g. `#[bird_barrier(crate = my_engine::bird_barrier)]`.
+#[proc_macro_derive(AssetCollection, attributes(asset_collection, bird_barrier))]
+pub fn derive_asset_collection(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	match asset_collection(input) {
+		Ok(tokens) => tokens.into(),
+		Err(e) => e.to_compile_error().into(),
+	}
+}
+
+fn asset_collection(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+	let Data::Struct(data) = &input.data else {
+		return Err(syn::Error::new(
+			input.span(),
+			"`AssetCollection` can only be derived for structs",
+		));
+	};
+
+	let krate = crate_path(&input.attrs)?;
+	let mut fields = Vec::new();
+	for (i, field) in data.fields.iter().enumerate() {
+		if is_skipped(field)? {
+			continue;
+		}
+		fields.push(match &field.ident {
+			Some(ident) => quote! { #ident },
+			None => {
+				let index = Index::from(i);
+				quote! { #index }
+			}
+		});
+	}
+	let name = &input.ident;
+	let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+	Ok(quote! {
+		impl #impl_generics #krate::AssetCollection for #name #ty_generics #where_clause {
+			fn iter_ids(&self) -> impl ::core::iter::Iterator<
+				Item = #krate::__private::UntypedAssetId,
+			> {
+				::core::iter::empty()
+					#(.chain(#krate::AssetIds::asset_ids(&self.#fields)))*
+			}
+		}
+	})
+}
+
+/// Returns the path given with `#[bird_barrier(crate = path)]`, or `::bird_barrier`.
+fn crate_path(attrs: &[syn::Attribute]) -> syn::Result<syn::Path> {
+	let mut path = syn::parse_quote!(::bird_barrier);
+	for attr in attrs
+		.iter()
+		.filter(|attr| attr.path().is_ident("bird_barrier"))
+	{
+		attr.parse_nested_meta(|meta| {
+			if meta.path.is_ident("crate") {
+				path = meta.value()?.parse()?;
+				Ok(())
+			} else {
+				Err(meta.error("expected `crate`"))
+			}
+		})?;
+	}
+	Ok(path)
+}
+
+/// Returns `true` if the field has an `#[asset_collection(skip)]` attribute.
+fn is_skipped(field: &syn::Field) -> syn::Result<bool> {
+	let mut skip = false;
+	for attr in field
+		.attrs
+		.iter()
+		.filter(|attr| attr.path().is_ident("asset_collection"))
+	{
+		attr.parse_nested_meta(|meta| {
+			if meta.path.is_ident("skip") {
+				skip = true;
+				Ok(())
+			} else {
+				Err(meta.error("expected `skip`"))
+			}
+		})?;
+	}
+	Ok(skip)
+}
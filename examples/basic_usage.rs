@@ -86,10 +86,12 @@ fn main() {
 	app.add_plugins(MinimalPlugins);
 
 	// Add the setup tracking plugin
-	app.add_plugins(SetupTrackingPlugin::<GameSetup, _, _, _, _>::new(
-		|| true, // Always run condition
-		setup_complete,
-	));
+	app.add_plugins(
+		SetupTrackingPlugin::<GameSetup>::builder()
+			.run_if(|| true) // Always run condition
+			.on_finished(setup_complete)
+			.build(),
+	);
 
 	// Register providers with dependencies
 	app.register_provider(load_assets.provides([GameSetup::LoadAssets]));
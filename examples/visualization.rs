@@ -240,43 +240,20 @@ fn main() {
 	app.add_systems(EguiPrimaryContextPass, custom_graph_panel);
 
 	// Register providers with complex dependencies
-	app.register_provider(load_config.provides([GameSetup::LoadConfig]));
-
-	app.register_provider(
-		load_assets
-			.requires([GameSetup::LoadConfig]) // Assets need config first
-			.provides([GameSetup::LoadAssets]),
-	);
-
-	app.register_provider(
-		initialize_audio
-			.requires([GameSetup::LoadConfig]) // Audio needs config first
-			.provides([GameSetup::InitializeAudio]),
-	);
-
-	app.register_provider(
-		build_main_menu
-			.requires([GameSetup::LoadAssets, GameSetup::InitializeAudio]) // Menu needs both assets and audio
-			.provides([GameSetup::BuildMainMenu]),
-	);
-
-	app.register_provider(
-		build_game_world
-			.requires([GameSetup::LoadAssets]) // World needs assets
-			.provides([GameSetup::BuildGameWorld]),
-	);
-
-	app.register_provider(
-		spawn_player
-			.requires([GameSetup::BuildGameWorld]) // Player needs world
-			.provides([GameSetup::SpawnPlayer]),
-	);
-
-	app.register_provider(
-		start_game
-			.requires([GameSetup::BuildMainMenu, GameSetup::SpawnPlayer]) // Game needs both menu and player
-			.provides([GameSetup::StartGame]),
-	);
+	register_providers!(app, {
+		load_config => [] -> [GameSetup::LoadConfig],
+		// Assets and audio need config first
+		load_assets => [GameSetup::LoadConfig] -> [GameSetup::LoadAssets],
+		initialize_audio => [GameSetup::LoadConfig] -> [GameSetup::InitializeAudio],
+		// Menu needs both assets and audio
+		build_main_menu => [GameSetup::LoadAssets, GameSetup::InitializeAudio] -> [GameSetup::BuildMainMenu],
+		// World needs assets
+		build_game_world => [GameSetup::LoadAssets] -> [GameSetup::BuildGameWorld],
+		// Player needs world
+		spawn_player => [GameSetup::BuildGameWorld] -> [GameSetup::SpawnPlayer],
+		// Game needs both menu and player
+		start_game => [GameSetup::BuildMainMenu, GameSetup::SpawnPlayer] -> [GameSetup::StartGame],
+	});
 
 	app.run();
 }
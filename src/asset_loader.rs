@@ -0,0 +1,303 @@
+//! Interop with [`bevy_asset_loader`], so asset collections and loading states defined for it
+//! can be part of the setup graph.
+//!
+//! An asset collection can be loaded by a provider and tracked by a key directly, without a
+//! `LoadingState`:
+//!
+//! ```rust,ignore
+//! impl SetupKey for MySetupKey {
+//!     fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+//!         match self {
+//!             MySetupKey::Textures => world.register_system(asset_loader_progress::<Textures>),
+//!             // ...
+//!         }
+//!     }
+//! }
+//!
+//! app.register_provider(
+//!     load_asset_loader_collection::<Textures>.provides([MySetupKey::Textures]),
+//! );
+//! ```
+//!
+//! Existing `LoadingState`s can join the barrier as well, by entering the loading state from a
+//! provider with [`enter_loading_state`], and tracking it with a [`LoadingStateProgress`] checker
+//! that lists the same collections:
+//!
+//! ```rust,ignore
+//! app.add_loading_state(
+//!     LoadingState::new(GameState::Loading)
+//!         .continue_to_state(GameState::Menu)
+//!         .on_failure_continue_to_state(GameState::Error)
+//!         .load_collection::<Textures>()
+//!         .load_collection::<Sounds>(),
+//! );
+//!
+//! // In `register_progress_checker`:
+//! world.register_system(
+//!     LoadingStateProgress::new(GameState::Loading, GameState::Menu)
+//!         .on_failure(GameState::Error)
+//!         .load_collection::<Textures>()
+//!         .load_collection::<Sounds>()
+//!         .into_checker(),
+//! )
+//! ```
+
+use crate::Progress;
+use bevy_asset::{AssetServer, UntypedHandle};
+use bevy_asset_loader::asset_collection::AssetCollection;
+use bevy_ecs::prelude::*;
+use bevy_state::state::{FreelyMutableState, NextState, State, States};
+use std::marker::PhantomData;
+
+/// The handles of a `bevy_asset_loader` [`AssetCollection`] that is being loaded by
+/// [`load_asset_loader_collection`].
+///
+/// Removed once the collection is created.
+#[derive(Resource, Debug)]
+pub struct AssetLoaderHandles<C: AssetCollection> {
+	/// The handles returned by [`AssetCollection::load`].
+	pub handles: Vec<UntypedHandle>,
+	_marker: PhantomData<C>,
+}
+
+/// Provider system that starts loading a `bevy_asset_loader` [`AssetCollection`].
+///
+/// Does nothing if the collection already exists or is already loading. The collection resource
+/// is inserted by [`asset_loader_progress`] once all of its assets have loaded.
+pub fn load_asset_loader_collection<C: AssetCollection>(world: &mut World) {
+	if world.contains_resource::<C>() || world.contains_resource::<AssetLoaderHandles<C>>() {
+		return;
+	}
+	let handles = C::load(world);
+	world.insert_resource(AssetLoaderHandles::<C> {
+		handles,
+		_marker: PhantomData,
+	});
+}
+
+/// Progress checker for a `bevy_asset_loader` [`AssetCollection`] loaded by
+/// [`load_asset_loader_collection`].
+///
+/// Each asset counts as loaded once its whole dependency tree has loaded. Once they all have, the
+/// collection is created with [`AssetCollection::create`] and inserted as a resource, and only
+/// then is the key finished, so systems requiring the key can use the collection right away.
+///
+/// Returns NaN if any of the assets failed to load, so the key is considered
+/// [failed](crate::SetupTracker::is_failed).
+pub fn asset_loader_progress<C: AssetCollection>(world: &mut World) -> Progress {
+	if world.contains_resource::<C>() {
+		return Progress::DONE;
+	}
+	let Some(loading) = world.get_resource::<AssetLoaderHandles<C>>() else {
+		return Progress::ZERO;
+	};
+	let server = world.resource::<AssetServer>();
+	let mut done = 0;
+	for handle in &loading.handles {
+		match server.get_recursive_dependency_load_state(handle) {
+			Some(state) if state.is_failed() => return Progress::new(f32::NAN),
+			Some(state) if state.is_loaded() => done += 1,
+			_ => {}
+		}
+	}
+	let total = loading.handles.len();
+	if done < total {
+		return Progress::new(done as f32 / total as f32);
+	}
+
+	let collection = C::create(world);
+	world.insert_resource(collection);
+	world.remove_resource::<AssetLoaderHandles<C>>();
+	Progress::DONE
+}
+
+/// Returns a provider system that enters a `bevy_asset_loader` `LoadingState`, so it starts
+/// loading once the provider's requirements are met.
+pub fn enter_loading_state<S: FreelyMutableState>(
+	loading_state: S,
+) -> impl System<In = (), Out = ()> {
+	IntoSystem::into_system(move |mut next: ResMut<NextState<S>>| {
+		next.set(loading_state.clone());
+	})
+}
+
+/// Builds a progress checker for a `bevy_asset_loader` `LoadingState`, from the states it was
+/// configured with and the collections it loads. See the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct LoadingStateProgress<S: States> {
+	loading_state: S,
+	continue_to: S,
+	failure: Option<S>,
+	collections: Vec<fn(&World) -> bool>,
+}
+
+impl<S: States> LoadingStateProgress<S> {
+	/// Tracks the loading state `loading_state`, which continues to `continue_to` once all of its
+	/// collections have loaded.
+	pub fn new(loading_state: S, continue_to: S) -> Self {
+		Self {
+			loading_state,
+			continue_to,
+			failure: None,
+			collections: Vec::new(),
+		}
+	}
+
+	/// The state the loading state continues to if any of its assets fail to load. Entering it
+	/// makes the checker return NaN, so the key is considered
+	/// [failed](crate::SetupTracker::is_failed).
+	pub fn on_failure(mut self, failure: S) -> Self {
+		self.failure = Some(failure);
+		self
+	}
+
+	/// Counts the collection `C` towards the progress of the loading state. It counts as loaded
+	/// once `bevy_asset_loader` has inserted it as a resource.
+	pub fn load_collection<C: AssetCollection>(mut self) -> Self {
+		self.collections
+			.push(|world| world.contains_resource::<C>());
+		self
+	}
+
+	/// Returns the progress checker.
+	///
+	/// Before the loading state is entered, the checker returns [`Progress::ZERO`]. While in it,
+	/// it returns the fraction of collections that have been inserted, and it only returns
+	/// [`Progress::DONE`] once the state has continued to `continue_to`, so systems requiring the
+	/// key run after everything `bevy_asset_loader` does in the loading state. It stays done when
+	/// the state changes again later.
+	pub fn into_checker(self) -> impl System<In = (), Out = Progress> {
+		let mut finished = false;
+		IntoSystem::into_system(move |world: &mut World| {
+			let Some(state) = world
+				.get_resource::<State<S>>()
+				.map(|state| state.get().clone())
+			else {
+				return Progress::ZERO;
+			};
+			finished |= state == self.continue_to;
+			if finished {
+				return Progress::DONE;
+			}
+			if self.failure.as_ref() == Some(&state) {
+				return Progress::new(f32::NAN);
+			}
+			if state != self.loading_state || self.collections.is_empty() {
+				return Progress::ZERO;
+			}
+			let loaded = self
+				.collections
+				.iter()
+				.filter(|loaded| loaded(world))
+				.count();
+			// Not done until the state continues
+			Progress::new((*Progress::from_steps(loaded, self.collections.len())).min(0.99))
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bevy_ecs::system::RunSystemOnce;
+
+	#[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+	enum TestState {
+		#[default]
+		Boot,
+		Loading,
+		Menu,
+		Error,
+	}
+
+	#[derive(Resource)]
+	struct Textures;
+
+	impl AssetCollection for Textures {
+		fn create(_world: &mut World) -> Self {
+			Self
+		}
+
+		fn load(_world: &mut World) -> Vec<UntypedHandle> {
+			Vec::new()
+		}
+	}
+
+	#[derive(Resource)]
+	struct Sounds;
+
+	impl AssetCollection for Sounds {
+		fn create(_world: &mut World) -> Self {
+			Self
+		}
+
+		fn load(_world: &mut World) -> Vec<UntypedHandle> {
+			Vec::new()
+		}
+	}
+
+	#[test]
+	fn test_empty_asset_loader_collection() {
+		let mut app = bevy_app::App::new();
+		app.add_plugins((
+			bevy_app::TaskPoolPlugin::default(),
+			bevy_asset::AssetPlugin::default(),
+		));
+		let world = app.world_mut();
+		assert_eq!(
+			world
+				.run_system_once(asset_loader_progress::<Textures>)
+				.unwrap(),
+			Progress::ZERO
+		);
+		world
+			.run_system_once(load_asset_loader_collection::<Textures>)
+			.unwrap();
+		assert!(world.contains_resource::<AssetLoaderHandles<Textures>>());
+		assert_eq!(
+			world
+				.run_system_once(asset_loader_progress::<Textures>)
+				.unwrap(),
+			Progress::DONE
+		);
+		assert!(world.contains_resource::<Textures>());
+		assert!(!world.contains_resource::<AssetLoaderHandles<Textures>>());
+	}
+
+	#[test]
+	fn test_loading_state_progress() {
+		let mut world = World::new();
+		let mut checker = LoadingStateProgress::new(TestState::Loading, TestState::Menu)
+			.on_failure(TestState::Error)
+			.load_collection::<Textures>()
+			.load_collection::<Sounds>()
+			.into_checker();
+		checker.initialize(&mut world);
+		assert_eq!(checker.run((), &mut world), Progress::ZERO);
+
+		world.insert_resource(State::new(TestState::Boot));
+		assert_eq!(checker.run((), &mut world), Progress::ZERO);
+		world.insert_resource(State::new(TestState::Loading));
+		assert_eq!(checker.run((), &mut world), Progress::ZERO);
+		world.insert_resource(Textures);
+		assert_eq!(checker.run((), &mut world), Progress::new(0.5));
+		world.insert_resource(Sounds);
+		assert_eq!(checker.run((), &mut world), Progress::new(0.99));
+
+		world.insert_resource(State::new(TestState::Menu));
+		assert_eq!(checker.run((), &mut world), Progress::DONE);
+		world.insert_resource(State::new(TestState::Boot));
+		assert_eq!(checker.run((), &mut world), Progress::DONE);
+	}
+
+	#[test]
+	fn test_loading_state_failure() {
+		let mut world = World::new();
+		let mut checker = LoadingStateProgress::new(TestState::Loading, TestState::Menu)
+			.on_failure(TestState::Error)
+			.into_checker();
+		checker.initialize(&mut world);
+		world.insert_resource(State::new(TestState::Error));
+		assert!(!checker.run((), &mut world).is_finite());
+	}
+}
@@ -0,0 +1,782 @@
+//! Helpers for tracking asset loading as setup keys.
+
+use crate::{Progress, SetupKey, SetupTracker};
+use bevy_app::{App, PreUpdate};
+use bevy_asset::{
+	Asset, AssetEvent, AssetPath, AssetServer, Assets, Handle, LoadedFolder, UntypedAssetId,
+	UntypedHandle,
+};
+use bevy_ecs::event::EventCursor;
+use bevy_ecs::prelude::*;
+use bevy_log::info;
+use bevy_platform::collections::{HashMap, HashSet};
+use std::borrow::Cow;
+
+/// Helper function to check asset loading progress for an asset collection.
+///
+/// Only the assets' own load states are checked. Use [`assets_recursive_progress`] for assets
+/// like GLTFs and scenes whose dependencies should finish loading too.
+///
+/// Assets that fail to load are handled according to the [`AssetFailurePolicy`] resource, and
+/// reported with an [`AssetSetupFailed`] trigger.
+///
+/// A collection without any assets has nothing to wait for, so its progress is [`Progress::DONE`]
+/// as soon as it is inserted.
+pub fn assets_progress<C: AssetCollection>(
+	collection: Option<Res<C>>,
+	server: Res<AssetServer>,
+	policy: Option<Res<AssetFailurePolicy>>,
+	reported: Local<HashSet<UntypedAssetId>>,
+	commands: Commands,
+) -> Progress {
+	let Some(collection) = collection else {
+		return Progress::ZERO;
+	};
+
+	collection_progress(
+		collection.iter_ids(),
+		|id| match server.get_load_state(id) {
+			Some(state) if state.is_failed() => AssetStatus::Failed,
+			Some(state) if state.is_loaded() => AssetStatus::Loaded,
+			_ => AssetStatus::Pending,
+		},
+		|| std::any::type_name::<C>().into(),
+		&server,
+		policy.as_deref().copied().unwrap_or_default(),
+		reported,
+		commands,
+	)
+}
+
+/// Helper function to check asset loading progress for an asset collection, counting each asset
+/// as loaded only once its whole dependency tree has loaded.
+///
+/// Failures are handled like in [`assets_progress`], including failures of dependencies.
+pub fn assets_recursive_progress<C: AssetCollection>(
+	collection: Option<Res<C>>,
+	server: Res<AssetServer>,
+	policy: Option<Res<AssetFailurePolicy>>,
+	reported: Local<HashSet<UntypedAssetId>>,
+	commands: Commands,
+) -> Progress {
+	let Some(collection) = collection else {
+		return Progress::ZERO;
+	};
+
+	collection_progress(
+		collection.iter_ids(),
+		|id| recursive_status(&server, id),
+		|| std::any::type_name::<C>().into(),
+		&server,
+		policy.as_deref().copied().unwrap_or_default(),
+		reported,
+		commands,
+	)
+}
+
+/// Handles of the folders loaded by [`load_folder`], by path.
+#[derive(Resource, Debug, Default)]
+pub struct AssetFolders(pub HashMap<AssetPath<'static>, Handle<LoadedFolder>>);
+
+/// Returns a provider system that loads every asset in the folder at `path` with
+/// [`AssetServer::load_folder`], keeping the folder's handle in [`AssetFolders`].
+///
+/// Use [`folder_progress`] to check its progress.
+pub fn load_folder(path: impl Into<AssetPath<'static>>) -> impl System<In = (), Out = ()> {
+	let path = path.into();
+	IntoSystem::into_system(move |world: &mut World| {
+		let handle = world.resource::<AssetServer>().load_folder(path.clone());
+		world
+			.get_resource_or_init::<AssetFolders>()
+			.0
+			.insert(path.clone(), handle);
+	})
+}
+
+/// Helper function to create a progress checker for a folder loaded with [`load_folder`].
+///
+/// The `AssetServer` only lists a folder's contents once every asset in it has loaded, so
+/// progress stays at zero until then. After that, each asset counts as loaded once its whole
+/// dependency tree has loaded, like in [`assets_recursive_progress`]. Failures are handled like in
+/// [`assets_progress`], including failing to read the folder.
+///
+/// An empty folder, or one where no assets match the filter of [`folder_progress_matching`], is
+/// [`Progress::DONE`] once it has been read.
+pub fn folder_progress(
+	path: impl Into<AssetPath<'static>>,
+) -> impl System<In = (), Out = Progress> {
+	folder_progress_matching(path, |_| true)
+}
+
+/// Like [`folder_progress`], but only counts assets whose paths match `filter`, e.g. to only wait
+/// for some file types:
+///
+/// ```rust,ignore
+/// folder_progress_matching("textures", |path| {
+///     path.get_full_extension().as_deref() == Some("png")
+/// })
+/// ```
+pub fn folder_progress_matching(
+	path: impl Into<AssetPath<'static>>,
+	filter: impl Fn(&AssetPath) -> bool + Send + Sync + 'static,
+) -> impl System<In = (), Out = Progress> {
+	let path = path.into();
+	IntoSystem::into_system(
+		move |folders: Option<Res<AssetFolders>>,
+		      loaded: Res<Assets<LoadedFolder>>,
+		      server: Res<AssetServer>,
+		      policy: Option<Res<AssetFailurePolicy>>,
+		      reported: Local<HashSet<UntypedAssetId>>,
+		      commands: Commands| {
+			let Some(handle) = folders.as_ref().and_then(|folders| folders.0.get(&path)) else {
+				return Progress::ZERO;
+			};
+			let ids = match loaded.get(handle) {
+				Some(folder) => folder
+					.handles
+					.iter()
+					.filter(|handle| handle.path().is_some_and(&filter))
+					.map(UntypedHandle::id)
+					.collect::<Vec<_>>(),
+				// Not listed yet, or reading the folder failed
+				None => vec![handle.id().untyped()],
+			};
+			collection_progress(
+				ids.into_iter(),
+				|id| recursive_status(&server, id),
+				|| path.to_string().into(),
+				&server,
+				policy.as_deref().copied().unwrap_or_default(),
+				reported,
+				commands,
+			)
+		},
+	)
+}
+
+/// What [`assets_progress`], [`assets_recursive_progress`], and [`folder_progress`] do when
+/// assets fail to load.
+///
+/// Insert this as a resource to change it. Applies to every asset collection.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AssetFailurePolicy {
+	/// Report the collection's progress as NaN, so the key is considered
+	/// [failed](crate::SetupTracker::is_failed) and its
+	/// [fallbacks](crate::IntoDependencyProvider::fallback_for) run.
+	#[default]
+	Fail,
+	/// Count failed assets as loaded, so setup continues without them.
+	Skip,
+}
+
+/// Observer trigger for when assets of a collection fail to load.
+///
+/// Triggered by [`assets_progress`], [`assets_recursive_progress`], and [`folder_progress`] once
+/// for each batch of newly failed assets, regardless of the [`AssetFailurePolicy`].
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct AssetSetupFailed {
+	/// The type name of the [`AssetCollection`], or the path of the folder for
+	/// [`folder_progress`].
+	pub collection: Cow<'static, str>,
+	/// The paths of the assets that failed. Assets without a path are listed as their ID.
+	pub paths: Vec<String>,
+}
+
+/// The load status of a single asset in a collection.
+enum AssetStatus {
+	Pending,
+	Loaded,
+	Failed,
+}
+
+/// Returns the status of `id` including its dependencies.
+fn recursive_status(server: &AssetServer, id: UntypedAssetId) -> AssetStatus {
+	match server.get_recursive_dependency_load_state(id) {
+		Some(state) if state.is_failed() => AssetStatus::Failed,
+		Some(state) if state.is_loaded() => AssetStatus::Loaded,
+		_ => AssetStatus::Pending,
+	}
+}
+
+/// Returns the progress of `ids` according to `status`, and reports newly failed assets.
+fn collection_progress(
+	ids: impl Iterator<Item = UntypedAssetId>,
+	status: impl Fn(UntypedAssetId) -> AssetStatus,
+	collection: impl FnOnce() -> Cow<'static, str>,
+	server: &AssetServer,
+	policy: AssetFailurePolicy,
+	mut reported: Local<HashSet<UntypedAssetId>>,
+	mut commands: Commands,
+) -> Progress {
+	let (progress, failed) = count_loaded(ids, status, policy);
+	let paths = failed
+		.into_iter()
+		.filter(|id| reported.insert(*id))
+		.map(|id| {
+			server
+				.get_path(id)
+				.map_or_else(|| id.to_string(), |path| path.to_string())
+		})
+		.collect::<Vec<_>>();
+	if !paths.is_empty() {
+		commands.trigger(AssetSetupFailed {
+			collection: collection(),
+			paths,
+		});
+	}
+	progress
+}
+
+/// Returns the fraction of `ids` that are loaded according to `status`, and the IDs of the
+/// assets that failed.
+fn count_loaded(
+	ids: impl Iterator<Item = UntypedAssetId>,
+	status: impl Fn(UntypedAssetId) -> AssetStatus,
+	policy: AssetFailurePolicy,
+) -> (Progress, Vec<UntypedAssetId>) {
+	let mut done = 0;
+	let mut total = 0;
+	let mut failed = Vec::new();
+	for id in ids {
+		total += 1;
+		match status(id) {
+			AssetStatus::Pending => {}
+			AssetStatus::Loaded => done += 1,
+			AssetStatus::Failed => failed.push(id),
+		}
+	}
+
+	let progress = match policy {
+		// Nothing to wait for
+		_ if total == 0 => Progress::DONE,
+		AssetFailurePolicy::Fail if !failed.is_empty() => Progress::new(f32::NAN),
+		AssetFailurePolicy::Fail => Progress::new(done as f32 / total as f32),
+		AssetFailurePolicy::Skip => Progress::new((done + failed.len()) as f32 / total as f32),
+	};
+	(progress, failed)
+}
+
+/// Trait for asset collections that can be tracked for loading progress.
+///
+/// With the `derive` feature, which isn't enabled by default, this can be derived for structs
+/// whose fields all implement [`AssetIds`]. Fields that don't hold assets can be skipped with
+/// `#[asset_collection(skip)]`:
+///
+/// ```rust,ignore
+/// #[derive(Resource, AssetCollection)]
+/// struct LevelAssets {
+///     terrain: Handle<Image>,
+///     props: Vec<Handle<Scene>>,
+///     sounds: HashMap<String, Handle<AudioSource>>,
+///     #[asset_collection(skip)]
+///     name: String,
+/// }
+/// ```
+///
+/// If `bird_barrier` is renamed or re-exported, give its path with
+/// `#[bird_barrier(crate = path)]` on the struct.
+pub trait AssetCollection: Resource {
+	/// Returns an iterator over all asset IDs in this collection.
+	fn iter_ids(&self) -> impl Iterator<Item = UntypedAssetId>;
+}
+
+#[cfg(feature = "derive")]
+pub use bird_barrier_macros::AssetCollection;
+
+/// Values holding asset handles, which can be fields of a derived [`AssetCollection`].
+pub trait AssetIds {
+	/// Returns an iterator over the IDs of every asset handle in this value.
+	fn asset_ids(&self) -> impl Iterator<Item = UntypedAssetId>;
+}
+
+impl<A: Asset> AssetIds for Handle<A> {
+	fn asset_ids(&self) -> impl Iterator<Item = UntypedAssetId> {
+		std::iter::once(self.id().untyped())
+	}
+}
+
+impl AssetIds for UntypedHandle {
+	fn asset_ids(&self) -> impl Iterator<Item = UntypedAssetId> {
+		std::iter::once(self.id())
+	}
+}
+
+impl<T: AssetIds> AssetIds for Option<T> {
+	fn asset_ids(&self) -> impl Iterator<Item = UntypedAssetId> {
+		self.iter().flat_map(T::asset_ids)
+	}
+}
+
+impl<T: AssetIds> AssetIds for Vec<T> {
+	fn asset_ids(&self) -> impl Iterator<Item = UntypedAssetId> {
+		self.iter().flat_map(T::asset_ids)
+	}
+}
+
+impl<T: AssetIds, const N: usize> AssetIds for [T; N] {
+	fn asset_ids(&self) -> impl Iterator<Item = UntypedAssetId> {
+		self.iter().flat_map(T::asset_ids)
+	}
+}
+
+impl<K, V: AssetIds, S> AssetIds for std::collections::HashMap<K, V, S> {
+	fn asset_ids(&self) -> impl Iterator<Item = UntypedAssetId> {
+		self.values().flat_map(V::asset_ids)
+	}
+}
+
+impl<K, V: AssetIds, S> AssetIds for bevy_platform::collections::HashMap<K, V, S> {
+	fn asset_ids(&self) -> impl Iterator<Item = UntypedAssetId> {
+		self.values().flat_map(V::asset_ids)
+	}
+}
+
+/// Helper system to load assets for an asset collection.
+pub fn load_assets<C: AssetCollection + FromWorld>(mut cmds: Commands, collection: Option<Res<C>>) {
+	if collection.is_some() {
+		return;
+	}
+
+	cmds.init_resource::<C>();
+}
+
+/// Asset collections whose keys are [invalidated](SetupTracker::invalidate_downstream) when one of
+/// their assets is hot-reloaded. See [`InvalidateOnReload`].
+#[derive(Resource)]
+pub struct ReloadInvalidations<K: SetupKey> {
+	collections: Vec<(CollectionContains, K)>,
+}
+
+/// Checks whether a collection resource contains an asset.
+type CollectionContains = fn(&World, UntypedAssetId) -> bool;
+
+impl<K: SetupKey> Default for ReloadInvalidations<K> {
+	fn default() -> Self {
+		Self {
+			collections: Vec::new(),
+		}
+	}
+}
+
+fn collection_contains<C: AssetCollection>(world: &World, id: UntypedAssetId) -> bool {
+	world
+		.get_resource::<C>()
+		.is_some_and(|collection| collection.iter_ids().any(|other| other == id))
+}
+
+/// Trait for re-running setup when assets are hot-reloaded during development.
+///
+/// Both methods are needed, since asset events are specific to each asset type:
+///
+/// ```rust,ignore
+/// app.invalidate_on_reload::<MySetupKey, LevelAssets>(MySetupKey::LoadLevel)
+///     .watch_reloads::<MySetupKey, Image>()
+///     .watch_reloads::<MySetupKey, Scene>();
+/// ```
+pub trait InvalidateOnReload {
+	/// Invalidates `key` and everything downstream of it whenever an asset in the collection `C`
+	/// is modified, so the providers that used it run again.
+	///
+	/// Only assets of types passed to [`watch_reloads`](Self::watch_reloads) are noticed.
+	fn invalidate_on_reload<K: SetupKey, C: AssetCollection>(&mut self, key: K) -> &mut Self;
+
+	/// Watches for modified assets of type `A`, invalidating the keys of the collections that
+	/// contain them. Call this once for each asset type.
+	fn watch_reloads<K: SetupKey, A: Asset>(&mut self) -> &mut Self;
+}
+
+impl InvalidateOnReload for App {
+	fn invalidate_on_reload<K: SetupKey, C: AssetCollection>(&mut self, key: K) -> &mut Self {
+		self.world_mut()
+			.get_resource_or_init::<ReloadInvalidations<K>>()
+			.collections
+			.push((collection_contains::<C>, key));
+		self
+	}
+
+	fn watch_reloads<K: SetupKey, A: Asset>(&mut self) -> &mut Self {
+		self.add_systems(PreUpdate, invalidate_reloaded::<K, A>)
+	}
+}
+
+fn invalidate_reloaded<K: SetupKey, A: Asset>(
+	world: &mut World,
+	mut cursor: Local<EventCursor<AssetEvent<A>>>,
+) {
+	let Some(events) = world.get_resource::<Events<AssetEvent<A>>>() else {
+		return;
+	};
+	let modified = cursor
+		.read(events)
+		.filter_map(|event| match event {
+			AssetEvent::Modified { id } => Some(id.untyped()),
+			_ => None,
+		})
+		.collect::<Vec<_>>();
+	if modified.is_empty() {
+		return;
+	}
+	let Some(invalidations) = world.get_resource::<ReloadInvalidations<K>>() else {
+		return;
+	};
+	let keys = invalidations
+		.collections
+		.iter()
+		.filter(|(contains, _)| modified.iter().any(|id| contains(world, *id)))
+		.map(|(_, key)| key.clone())
+		.collect::<Vec<_>>();
+	let Some(mut tracker) = world.get_resource_mut::<SetupTracker<K>>() else {
+		return;
+	};
+	for key in keys {
+		let invalidated = tracker.invalidate_downstream(&key);
+		if !invalidated.is_empty() {
+			info!(
+				"Assets were reloaded, re-running setup for {} keys",
+				invalidated.len()
+			);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// `TypePath` comes from `bevy_reflect`
+	#[cfg(all(feature = "derive", feature = "reflect"))]
+	#[test]
+	fn test_derive_asset_collection() {
+		use bevy_asset::{Asset, AssetId};
+		use bevy_platform::collections::HashMap;
+		use bevy_reflect::TypePath;
+
+		#[derive(Asset, TypePath)]
+		struct TestAsset;
+
+		#[derive(Resource, AssetCollection)]
+		struct TestAssets {
+			single: Handle<TestAsset>,
+			many: Vec<Handle<TestAsset>>,
+			named: HashMap<&'static str, Handle<TestAsset>>,
+			maybe: Option<Handle<TestAsset>>,
+			#[asset_collection(skip)]
+			_label: String,
+		}
+
+		#[derive(Resource, AssetCollection)]
+		#[bird_barrier(crate = crate)]
+		struct Tuple(Handle<TestAsset>, [Handle<TestAsset>; 2]);
+
+		let handle = |i| {
+			Handle::<TestAsset>::Weak(AssetId::Uuid {
+				uuid: bevy_asset::uuid::Uuid::from_u128(i),
+			})
+		};
+		let assets = TestAssets {
+			single: handle(1),
+			many: vec![handle(2), handle(3)],
+			named: HashMap::from_iter([("a", handle(4))]),
+			maybe: None,
+			_label: String::new(),
+		};
+		let mut ids = assets.iter_ids().collect::<Vec<_>>();
+		ids.sort();
+		let mut expected = (1..=4)
+			.map(|i| handle(i).id().untyped())
+			.collect::<Vec<_>>();
+		expected.sort();
+		assert_eq!(ids, expected);
+
+		let tuple = Tuple(handle(5), [handle(6), handle(7)]);
+		assert_eq!(tuple.iter_ids().count(), 3);
+	}
+
+	#[cfg(feature = "reflect")]
+	#[test]
+	fn test_invalidate_on_reload() {
+		use crate::ProviderInfo;
+		use bevy_asset::AssetId;
+		use bevy_ecs::system::{RunSystemOnce, SystemId};
+		use bevy_reflect::TypePath;
+		use std::borrow::Cow;
+
+		#[derive(Asset, TypePath)]
+		struct TestAsset;
+
+		#[derive(Resource)]
+		struct TestAssets(Handle<TestAsset>);
+
+		impl AssetCollection for TestAssets {
+			fn iter_ids(&self) -> impl Iterator<Item = UntypedAssetId> {
+				self.0.asset_ids()
+			}
+		}
+
+		#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+		struct LoadAssets;
+
+		impl SetupKey for LoadAssets {
+			fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+				world.register_system(|| Progress::DONE)
+			}
+		}
+
+		let id = AssetId::<TestAsset>::Uuid {
+			uuid: bevy_asset::uuid::Uuid::from_u128(1),
+		};
+		let mut app = App::new();
+		let mut tracker = SetupTracker::<LoadAssets>::new(app.world_mut().register_system(|| {}));
+		tracker.register_provider(
+			app.world_mut().register_system(|| {}),
+			ProviderInfo::new(vec![], vec![LoadAssets], Cow::Borrowed("load")),
+			app.world_mut(),
+		);
+		app.insert_resource(tracker)
+			.insert_resource(TestAssets(Handle::Weak(id)))
+			.add_event::<AssetEvent<TestAsset>>()
+			.invalidate_on_reload::<LoadAssets, TestAssets>(LoadAssets);
+
+		app.world_mut().send_event(AssetEvent::Modified {
+			id: AssetId::<TestAsset>::default(),
+		});
+		app.world_mut()
+			.run_system_once(invalidate_reloaded::<LoadAssets, TestAsset>)
+			.unwrap();
+		assert!(
+			!app.world()
+				.resource::<SetupTracker<LoadAssets>>()
+				.is_invalidated(&LoadAssets)
+		);
+
+		app.world_mut().send_event(AssetEvent::Modified { id });
+		app.world_mut()
+			.run_system_once(invalidate_reloaded::<LoadAssets, TestAsset>)
+			.unwrap();
+		assert!(
+			app.world()
+				.resource::<SetupTracker<LoadAssets>>()
+				.is_invalidated(&LoadAssets)
+		);
+	}
+
+	/// Creates an app with an `AssetServer` that reads from `dir`.
+	#[cfg(feature = "reflect")]
+	fn asset_app(dir: &std::path::Path) -> App {
+		let mut app = App::new();
+		app.add_plugins((
+			bevy_app::TaskPoolPlugin::default(),
+			bevy_asset::AssetPlugin {
+				file_path: dir.to_string_lossy().into_owned(),
+				..Default::default()
+			},
+		));
+		app
+	}
+
+	#[cfg(feature = "reflect")]
+	#[test]
+	fn test_empty_collection() {
+		use bevy_ecs::system::RunSystemOnce;
+		use bevy_reflect::TypePath;
+
+		#[derive(Asset, TypePath)]
+		struct TestAsset;
+
+		#[derive(Resource)]
+		struct TestAssets(Vec<Handle<TestAsset>>);
+
+		impl AssetCollection for TestAssets {
+			fn iter_ids(&self) -> impl Iterator<Item = UntypedAssetId> {
+				self.0.asset_ids()
+			}
+		}
+
+		let dir = std::env::temp_dir().join(format!("bird_barrier_assets_{}", std::process::id()));
+		std::fs::create_dir_all(dir.join("empty")).unwrap();
+		let mut app = asset_app(&dir);
+		let world = app.world_mut();
+
+		let progress = world
+			.run_system_once(assets_progress::<TestAssets>)
+			.unwrap();
+		assert_eq!(progress, Progress::ZERO);
+		world.insert_resource(TestAssets(Vec::new()));
+		let progress = world
+			.run_system_once(assets_progress::<TestAssets>)
+			.unwrap();
+		assert_eq!(progress, Progress::DONE);
+
+		world.run_system_once(load_folder("empty")).unwrap();
+		let mut checker = folder_progress("empty");
+		checker.initialize(app.world_mut());
+		assert_eq!(checker.run((), app.world_mut()), Progress::ZERO);
+		for _ in 0..100 {
+			app.update();
+			if checker.run((), app.world_mut()) == Progress::DONE {
+				break;
+			}
+			std::thread::sleep(std::time::Duration::from_millis(10));
+		}
+		std::fs::remove_dir_all(&dir).unwrap();
+		assert_eq!(checker.run((), app.world_mut()), Progress::DONE);
+	}
+
+	#[cfg(feature = "reflect")]
+	#[test]
+	fn test_recursive_progress() {
+		use bevy_asset::io::Reader;
+		use bevy_asset::{AssetApp, AssetLoader, LoadContext};
+		use bevy_ecs::system::RunSystemOnce;
+		use bevy_reflect::TypePath;
+		use std::sync::LazyLock;
+
+		/// Holds the child loader back until cancelled. Tasks may run on this thread, so the
+		/// loader has to wait for a wake-up instead of polling.
+		static RELEASE: LazyLock<crate::CancellationToken> = LazyLock::new(Default::default);
+
+		#[derive(Asset, TypePath)]
+		struct Parent(#[dependency] Handle<Child>);
+
+		#[derive(Asset, TypePath)]
+		struct Child;
+
+		#[derive(TypePath)]
+		struct ParentLoader;
+
+		impl AssetLoader for ParentLoader {
+			type Asset = Parent;
+			type Settings = ();
+			type Error = std::io::Error;
+
+			async fn load(
+				&self,
+				reader: &mut dyn Reader,
+				_settings: &(),
+				load_context: &mut LoadContext<'_>,
+			) -> Result<Parent, Self::Error> {
+				let mut path = Vec::new();
+				reader.read_to_end(&mut path).await?;
+				Ok(Parent(
+					load_context.load(String::from_utf8_lossy(&path).into_owned()),
+				))
+			}
+
+			fn extensions(&self) -> &[&str] {
+				&["parent"]
+			}
+		}
+
+		#[derive(TypePath)]
+		struct ChildLoader;
+
+		impl AssetLoader for ChildLoader {
+			type Asset = Child;
+			type Settings = ();
+			type Error = std::io::Error;
+
+			async fn load(
+				&self,
+				_reader: &mut dyn Reader,
+				_settings: &(),
+				_load_context: &mut LoadContext<'_>,
+			) -> Result<Child, Self::Error> {
+				RELEASE.cancelled().await;
+				Ok(Child)
+			}
+
+			fn extensions(&self) -> &[&str] {
+				&["child"]
+			}
+		}
+
+		#[derive(Resource)]
+		struct TestAssets(Handle<Parent>);
+
+		impl AssetCollection for TestAssets {
+			fn iter_ids(&self) -> impl Iterator<Item = UntypedAssetId> {
+				self.0.asset_ids()
+			}
+		}
+
+		let dir =
+			std::env::temp_dir().join(format!("bird_barrier_recursive_{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+		std::fs::write(dir.join("a.parent"), "a.child").unwrap();
+		std::fs::write(dir.join("a.child"), "").unwrap();
+		let mut app = asset_app(&dir);
+		app.init_asset::<Parent>()
+			.init_asset::<Child>()
+			.register_asset_loader(ParentLoader)
+			.register_asset_loader(ChildLoader);
+		let handle = app.world().resource::<AssetServer>().load("a.parent");
+		app.insert_resource(TestAssets(handle.clone()));
+
+		let progress = |app: &mut App| {
+			let world = app.world_mut();
+			let own = world
+				.run_system_once(assets_progress::<TestAssets>)
+				.unwrap();
+			let recursive = world
+				.run_system_once(assets_recursive_progress::<TestAssets>)
+				.unwrap();
+			(own, recursive)
+		};
+		let wait_for = |app: &mut App, expected| {
+			for _ in 0..100 {
+				app.update();
+				if progress(app) == expected {
+					return;
+				}
+				std::thread::sleep(std::time::Duration::from_millis(10));
+			}
+			panic!("expected {expected:?}, got {:?}", progress(app));
+		};
+
+		/// Releases the child loader even if the test fails, so the task pool can shut down.
+		struct Release;
+
+		impl Drop for Release {
+			fn drop(&mut self) {
+				RELEASE.cancel();
+			}
+		}
+		let _release = Release;
+
+		// The parent loads, but its child is still loading
+		wait_for(&mut app, (Progress::DONE, Progress::ZERO));
+		assert!(app.world().resource::<AssetServer>().is_loaded(&handle));
+
+		RELEASE.cancel();
+		wait_for(&mut app, (Progress::DONE, Progress::DONE));
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn test_count_loaded() {
+		let ids = (0..4)
+			.map(|i| UntypedAssetId::Uuid {
+				type_id: std::any::TypeId::of::<()>(),
+				uuid: bevy_asset::uuid::Uuid::from_u128(i),
+			})
+			.collect::<Vec<_>>();
+		let status = |id: UntypedAssetId| match ids.iter().position(|other| *other == id) {
+			Some(0) => AssetStatus::Loaded,
+			Some(1) => AssetStatus::Failed,
+			_ => AssetStatus::Pending,
+		};
+
+		let (progress, failed) =
+			count_loaded(ids.iter().copied(), status, AssetFailurePolicy::Fail);
+		assert!(!progress.is_finite());
+		assert_eq!(failed, [ids[1]]);
+
+		let (progress, failed) =
+			count_loaded(ids.iter().copied(), status, AssetFailurePolicy::Skip);
+		assert_eq!(progress, Progress::new(0.5));
+		assert_eq!(failed, [ids[1]]);
+
+		let (progress, _) = count_loaded(std::iter::empty(), status, AssetFailurePolicy::Fail);
+		assert_eq!(progress, Progress::DONE);
+	}
+}
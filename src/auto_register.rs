@@ -0,0 +1,117 @@
+//! Distributed provider registration, so providers can be declared next to the code they set up,
+//! in any crate, instead of in one central list.
+//!
+//! This relies on the [`inventory`] crate, which uses platform-specific linker support. In
+//! particular, WASM targets need extra setup; see its documentation.
+
+use crate::SetupKey;
+use bevy_ecs::world::World;
+use std::any::TypeId;
+
+#[doc(hidden)]
+pub use inventory as __inventory;
+
+/// A provider declared with [`auto_register_provider!`](crate::auto_register_provider).
+///
+/// [`SetupTrackingPlugin`](crate::SetupTrackingPlugin) registers every `AutoProvider` for its key
+/// type when it is built.
+pub struct AutoProvider {
+	key_type: fn() -> TypeId,
+	register: fn(&mut World),
+}
+
+impl AutoProvider {
+	/// Creates an `AutoProvider` for keys of type `K`, which registers providers with `register`.
+	///
+	/// Use [`auto_register_provider!`](crate::auto_register_provider) instead of calling this
+	/// directly.
+	pub const fn new<K: SetupKey>(register: fn(&mut World)) -> Self {
+		Self {
+			key_type: TypeId::of::<K>,
+			register,
+		}
+	}
+
+	/// Registers every `AutoProvider` declared for keys of type `K` in `world`.
+	///
+	/// The `SetupTracker<K>` resource must already exist.
+	pub fn register_all<K: SetupKey>(world: &mut World) {
+		for provider in inventory::iter::<AutoProvider> {
+			if (provider.key_type)() == TypeId::of::<K>() {
+				(provider.register)(world);
+			}
+		}
+	}
+}
+
+inventory::collect!(AutoProvider);
+
+/// Declares a provider that [`SetupTrackingPlugin`](crate::SetupTrackingPlugin) registers
+/// automatically, from any module or crate linked into the app.
+///
+/// The first argument is the setup key type, and the second is a provider expression as would be
+/// passed to [`RegisterProvider::register_provider`](crate::RegisterProvider::register_provider).
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use bevy::prelude::*;
+/// use bevy::ecs::system::SystemId;
+/// use bird_barrier::*;
+///
+/// #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// enum MySetupKey {
+///     LoadTerrain,
+/// }
+///
+/// impl SetupKey for MySetupKey {
+///     fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+///         world.register_system(|| Progress::DONE)
+///     }
+/// }
+///
+/// fn load_terrain() {}
+///
+/// auto_register_provider!(MySetupKey, load_terrain.provides([MySetupKey::LoadTerrain]));
+/// ```
+#[macro_export]
+macro_rules! auto_register_provider {
+	($key:ty, $provider:expr $(,)?) => {
+		$crate::__inventory::submit! {
+			$crate::AutoProvider::new::<$key>(|world| {
+				#[allow(unused_imports)]
+				use $crate::IntoDependencyProvider as _;
+				$crate::RegisterProvider::register_provider(world, $provider);
+			})
+		}
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{SetupTracker, SetupTrackingPlugin, test_utils::DoneSetupKey};
+	use bevy_app::App;
+
+	fn auto_registered() {}
+
+	crate::auto_register_provider!(
+		DoneSetupKey,
+		auto_registered
+			.provides([DoneSetupKey::A])
+			.named("auto_registered")
+	);
+
+	#[test]
+	fn test_auto_register() {
+		let mut app = App::new();
+		app.add_plugins(SetupTrackingPlugin::<DoneSetupKey>::builder().build());
+
+		let tracker = app.world().resource::<SetupTracker<DoneSetupKey>>();
+		assert_eq!(tracker.providers().len(), 1);
+		assert_eq!(
+			tracker.providers().values().next().unwrap().name(),
+			"auto_registered"
+		);
+	}
+}
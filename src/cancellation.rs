@@ -0,0 +1,170 @@
+//! Stopping work that providers started in the background when setup is cancelled.
+//!
+//! Each tracker has a [`CancellationToken`] that is cancelled by [`cancel_setup`] or when the
+//! tracker is [cleaned up](SetupTracker::cleanup). While [`advance_setup`] runs providers, it is
+//! available to them as the [`CurrentCancellationToken`] resource, so they can hand it to the
+//! tasks they spawn:
+//!
+//! ```rust,ignore
+//! fn bake_navmesh(token: Res<CurrentCancellationToken>, mut commands: Commands) {
+//!     let token = token.0.clone();
+//!     let task = AsyncComputeTaskPool::get().spawn(async move {
+//!         token.run_until_cancelled(bake(NAVMESH_SETTINGS)).await
+//!     });
+//!     commands.insert_resource(NavmeshTask(task));
+//! }
+//! ```
+//!
+//! Long computations that don't await anything can check
+//! [`is_cancelled`](CancellationToken::is_cancelled) between steps instead.
+//!
+//! [`advance_setup`]: crate::advance_setup
+//! [`cancel_setup`]: crate::cancel_setup
+
+use crate::{SetupKey, SetupTracker};
+use bevy_ecs::prelude::*;
+use std::future::{Future, poll_fn};
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+
+/// A flag shared between setup and the background work of its providers, set when setup is
+/// cancelled. See the [module docs](self).
+///
+/// Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<TokenInner>);
+
+#[derive(Debug, Default)]
+struct TokenInner {
+	cancelled: AtomicBool,
+	wakers: Mutex<Vec<Waker>>,
+}
+
+impl CancellationToken {
+	/// Creates a token that hasn't been cancelled.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Cancels the token, waking any tasks waiting on [`cancelled`](Self::cancelled).
+	pub fn cancel(&self) {
+		if self.0.cancelled.swap(true, Ordering::AcqRel) {
+			return;
+		}
+		for waker in self.0.wakers.lock().unwrap().drain(..) {
+			waker.wake();
+		}
+	}
+
+	/// Whether the token has been cancelled.
+	pub fn is_cancelled(&self) -> bool {
+		self.0.cancelled.load(Ordering::Acquire)
+	}
+
+	/// Waits until the token is cancelled.
+	pub fn cancelled(&self) -> impl Future<Output = ()> + '_ {
+		poll_fn(move |cx| {
+			if self.is_cancelled() {
+				return Poll::Ready(());
+			}
+			let mut wakers = self.0.wakers.lock().unwrap();
+			// `cancel` may have drained the wakers before we locked them
+			if self.is_cancelled() {
+				return Poll::Ready(());
+			}
+			if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+				wakers.push(cx.waker().clone());
+			}
+			Poll::Pending
+		})
+	}
+
+	/// Runs `future` until it finishes or the token is cancelled, whichever happens first.
+	/// Returns `None` if it was cancelled, in which case `future` is dropped without finishing.
+	pub async fn run_until_cancelled<F: Future>(&self, future: F) -> Option<F::Output> {
+		let mut future = pin!(future);
+		let mut cancelled = pin!(self.cancelled());
+		poll_fn(|cx| {
+			if cancelled.as_mut().poll(cx).is_ready() {
+				return Poll::Ready(None);
+			}
+			future.as_mut().poll(cx).map(Some)
+		})
+		.await
+	}
+}
+
+/// The [`CancellationToken`] of the tracker whose providers are being run by
+/// [`advance_setup`](crate::advance_setup). Only exists while they run.
+#[derive(Resource, Debug, Clone)]
+pub struct CurrentCancellationToken(pub CancellationToken);
+
+impl<K: SetupKey> SetupTracker<K> {
+	/// Returns the token that is cancelled when setup is [cancelled](crate::cancel_setup) or the
+	/// tracker is [cleaned up](Self::cleanup). See the [module docs](self).
+	pub fn cancellation_token(&self) -> &CancellationToken {
+		&self.cancellation
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		IntoDependencyProvider, RegisterProvider, advance_setup, cancel_setup,
+		test_utils::TestSetupKey,
+	};
+
+	#[derive(Resource)]
+	struct Token(CancellationToken);
+
+	#[test]
+	fn test_run_until_cancelled() {
+		let token = CancellationToken::new();
+		assert_eq!(
+			bevy_tasks::block_on(token.run_until_cancelled(async { 1 })),
+			Some(1)
+		);
+
+		let canceller = std::thread::spawn({
+			let token = token.clone();
+			move || {
+				std::thread::sleep(std::time::Duration::from_millis(10));
+				token.cancel();
+			}
+		});
+		let pending = token.run_until_cancelled(std::future::pending::<()>());
+		assert_eq!(bevy_tasks::block_on(pending), None);
+		assert!(token.is_cancelled());
+		canceller.join().unwrap();
+	}
+
+	#[test]
+	fn test_cancel_setup_cancels_token() {
+		let mut world = World::new();
+		let tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		world.insert_resource(tracker);
+		world.register_provider(
+			(|token: Res<CurrentCancellationToken>, mut commands: Commands| {
+				commands.insert_resource(Token(token.0.clone()));
+			})
+			.provides([TestSetupKey::A]),
+		);
+
+		advance_setup::<TestSetupKey>(&mut world);
+		assert!(!world.contains_resource::<CurrentCancellationToken>());
+		let token = world.resource::<Token>().0.clone();
+		assert!(!token.is_cancelled());
+
+		cancel_setup::<TestSetupKey>(&mut world);
+		assert!(token.is_cancelled());
+		assert!(
+			world
+				.resource::<SetupTracker<TestSetupKey>>()
+				.cancellation_token()
+				.is_cancelled()
+		);
+	}
+}
@@ -0,0 +1,155 @@
+//! Long jobs split across frames.
+
+use crate::Progress;
+use bevy_ecs::prelude::*;
+use bevy_platform::time::Instant;
+
+/// When the tracker whose providers are being run by [`advance_setup`](crate::advance_setup) runs
+/// out of its [frame budget](crate::SetupTracker::set_frame_budget), or `None` if it has no
+/// budget. Only exists while they run.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct CurrentFrameDeadline(pub Option<Instant>);
+
+/// A long job, split into items that are processed until the tracker's
+/// [frame budget](crate::SetupTracker::set_frame_budget) runs out, so it can be spread across
+/// frames without stalling them. Pair it with an
+/// [incremental provider](crate::IntoIncrementalProvider) to report its progress to its key:
+///
+/// ```rust,ignore
+/// fn generate_chunks(
+///     mut task: Local<Option<ChunkedTask<std::ops::Range<u32>>>>,
+///     deadline: Res<CurrentFrameDeadline>,
+///     mut terrain: ResMut<Terrain>,
+/// ) -> Progress {
+///     task.get_or_insert_with(|| ChunkedTask::new(0..1024))
+///         .run(deadline.0, |chunk| terrain.generate(chunk))
+/// }
+///
+/// app.register_provider(generate_chunks.provides_incrementally([MySetupKey::Terrain]));
+/// ```
+///
+/// The budget is shared with every other provider that runs in the same frame, and providers that
+/// ran earlier have already used up part of it.
+#[derive(Debug, Clone)]
+pub struct ChunkedTask<I: Iterator> {
+	items: I,
+	done: usize,
+	total: usize,
+	finished: bool,
+}
+
+impl<I: ExactSizeIterator> ChunkedTask<I> {
+	/// Creates a task that processes `items` each time it [runs](Self::run).
+	pub fn new(items: impl IntoIterator<IntoIter = I>) -> Self {
+		let items = items.into_iter();
+		let total = items.len();
+		Self::with_total(items, total)
+	}
+}
+
+impl<I: Iterator> ChunkedTask<I> {
+	/// Creates a task for an iterator whose length isn't known up front, with an estimate of how
+	/// many items it has. Progress stays below [`Progress::DONE`] until it actually runs out.
+	pub fn with_total(items: impl IntoIterator<IntoIter = I>, total: usize) -> Self {
+		Self {
+			items: items.into_iter(),
+			done: 0,
+			total,
+			finished: false,
+		}
+	}
+
+	/// Calls `f` with the next items until `deadline` has passed or there are none left, and
+	/// returns the task's progress. Pass the [`CurrentFrameDeadline`] to stay within the tracker's
+	/// frame budget. Without a deadline, every remaining item is processed.
+	///
+	/// At least one item is processed per call, so the task always moves forward, even if the
+	/// deadline has already passed.
+	pub fn run(&mut self, deadline: Option<Instant>, mut f: impl FnMut(I::Item)) -> Progress {
+		while !self.finished {
+			let Some(item) = self.items.next() else {
+				self.finished = true;
+				break;
+			};
+			f(item);
+			self.done += 1;
+			// Finish now rather than on the next run if the iterator knows it's empty
+			if self.items.size_hint().1 == Some(0) {
+				self.finished = true;
+			} else if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+				break;
+			}
+		}
+		self.progress()
+	}
+
+	/// Returns how many items have been processed so far.
+	pub fn done(&self) -> usize {
+		self.done
+	}
+
+	/// Returns the fraction of items processed so far.
+	pub fn progress(&self) -> Progress {
+		if self.finished {
+			Progress::DONE
+		} else {
+			// The total may be an underestimate
+			Progress::new((*Progress::from_steps(self.done, self.total)).min(0.99))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		IntoIncrementalProvider, RegisterProvider, SetupTracker, advance_setup,
+		test_utils::TestSetupKey,
+	};
+	use std::time::Duration;
+
+	#[test]
+	fn test_chunked_task() {
+		let mut processed = Vec::new();
+		let mut task = ChunkedTask::new(0..4);
+		let passed = Some(Instant::now());
+		assert_eq!(task.run(passed, |i| processed.push(i)), Progress::new(0.25));
+		assert_eq!(task.run(passed, |i| processed.push(i)), Progress::new(0.5));
+		assert_eq!(task.done(), 2);
+
+		assert_eq!(task.run(None, |i| processed.push(i)), Progress::DONE);
+		assert_eq!(processed, [0, 1, 2, 3]);
+		assert_eq!(task.run(None, |_| unreachable!()), Progress::DONE);
+
+		// Underestimated totals don't finish early
+		let mut task = ChunkedTask::with_total(0..4, 2);
+		task.run(passed, |_| {});
+		task.run(passed, |_| {});
+		assert!(!task.run(passed, |_| {}).finished());
+	}
+
+	#[test]
+	fn test_chunked_task_frame_budget() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		tracker.set_frame_budget(Some(Duration::ZERO));
+		world.insert_resource(tracker);
+		world.register_provider(
+			(|mut task: Local<Option<ChunkedTask<std::ops::Range<u32>>>>,
+			  deadline: Res<CurrentFrameDeadline>| {
+				assert!(deadline.0.is_some());
+				task.get_or_insert_with(|| ChunkedTask::new(0..4))
+					.run(deadline.0, |_| {})
+			})
+			.provides_incrementally([TestSetupKey::A]),
+		);
+
+		// One item per frame, since the budget is always used up
+		for expected in [0.25, 0.5, 0.75] {
+			advance_setup::<TestSetupKey>(&mut world);
+			let tracker = world.resource::<SetupTracker<TestSetupKey>>();
+			assert_eq!(tracker.last_progress(), Progress::new(expected));
+		}
+		assert!(!world.contains_resource::<CurrentFrameDeadline>());
+	}
+}
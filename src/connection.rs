@@ -0,0 +1,115 @@
+//! Progress tracking driven by external state machines, such as network connections.
+//!
+//! Networking crates usually expose their connection as a resource with a state that only moves
+//! forward during setup, e.g. connecting → authenticating → joined. [`connection_progress`] polls
+//! that resource every time progress is checked, and maps its state to [`Progress`] with a closure
+//! you supply:
+//!
+//! ```rust,ignore
+//! world.register_system(connection_progress(|client: &RenetClient| {
+//!     if client.is_connected() {
+//!         Progress::DONE
+//!     } else if client.is_disconnected() {
+//!         Progress::new(f32::NAN)
+//!     } else {
+//!         Progress::from_steps(1, 2)
+//!     }
+//! }))
+//! ```
+//!
+//! Returning non-finite progress marks the key as failed, e.g. when the connection is refused, so
+//! a [fallback](crate::IntoDependencyProvider::fallback_for) can offer to play offline instead.
+
+use crate::Progress;
+use bevy_ecs::prelude::*;
+
+/// Creates a progress checker that maps the state of the connection resource `C` to progress.
+///
+/// Returns [`Progress::ZERO`] while `C` doesn't exist, since most networking crates only insert
+/// their client resource once a connection is started.
+pub fn connection_progress<C: Resource>(
+	map: impl Fn(&C) -> Progress + Send + Sync + 'static,
+) -> impl System<In = (), Out = Progress> {
+	IntoSystem::into_system(move |connection: Option<Res<C>>| {
+		connection.map_or(Progress::ZERO, |connection| map(&connection))
+	})
+}
+
+/// Implement this trait for a connection state enum to use it with [`phase_progress`].
+pub trait ConnectionPhase {
+	/// The number of phases before the connection is complete.
+	const PHASES: usize;
+
+	/// The index of the current phase, where [`PHASES`](Self::PHASES) means the connection is
+	/// complete, or `None` if the connection has failed.
+	fn phase(&self) -> Option<usize>;
+}
+
+/// Creates a progress checker for a connection resource whose state is a [`ConnectionPhase`].
+///
+/// Each phase counts as an equal share of progress, and a failed connection reports non-finite
+/// progress.
+pub fn phase_progress<C: Resource + ConnectionPhase>() -> impl System<In = (), Out = Progress> {
+	connection_progress(|connection: &C| match connection.phase() {
+		Some(phase) => Progress::from_steps(phase, C::PHASES),
+		None => Progress::new(f32::NAN),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bevy_ecs::system::RunSystemOnce;
+
+	#[derive(Resource)]
+	enum TestConnection {
+		Connecting,
+		Authenticating,
+		Joined,
+		Refused,
+	}
+
+	impl ConnectionPhase for TestConnection {
+		const PHASES: usize = 2;
+
+		fn phase(&self) -> Option<usize> {
+			match self {
+				TestConnection::Connecting => Some(0),
+				TestConnection::Authenticating => Some(1),
+				TestConnection::Joined => Some(2),
+				TestConnection::Refused => None,
+			}
+		}
+	}
+
+	#[test]
+	fn test_phase_progress() {
+		let mut world = World::new();
+		let checker = world.register_system(phase_progress::<TestConnection>());
+		assert_eq!(world.run_system(checker).unwrap(), Progress::ZERO);
+
+		world.insert_resource(TestConnection::Connecting);
+		assert_eq!(world.run_system(checker).unwrap(), Progress::ZERO);
+
+		world.insert_resource(TestConnection::Authenticating);
+		assert_eq!(world.run_system(checker).unwrap(), Progress::new(0.5));
+
+		world.insert_resource(TestConnection::Joined);
+		assert_eq!(world.run_system(checker).unwrap(), Progress::DONE);
+
+		world.insert_resource(TestConnection::Refused);
+		assert!(!world.run_system(checker).unwrap().is_finite());
+	}
+
+	#[test]
+	fn test_connection_progress() {
+		let mut world = World::new();
+		world.insert_resource(TestConnection::Joined);
+		let progress = world
+			.run_system_once(connection_progress(|connection: &TestConnection| {
+				matches!(connection, TestConnection::Joined).into()
+			}))
+			.unwrap();
+		assert_eq!(progress, Progress::DONE);
+	}
+}
@@ -0,0 +1,678 @@
+//! Data-driven setup graph definitions, loaded from RON.
+//!
+//! Systems are registered by name in a [`SetupSystemRegistry`], and a [`SetupGraphDefinition`]
+//! wires them together, so setup ordering can be changed without recompiling.
+//!
+//! With the `assets` feature, definitions can be loaded as assets from `.setup.ron` files, once
+//! [`SetupGraphAssetPlugin`] is added, by a provider created with [`load_setup_graph`]:
+//!
+//! ```rust,ignore
+//! app.add_plugins(SetupGraphAssetPlugin).register_provider(
+//!     load_setup_graph::<MySetupKey>("setup/level.setup.ron")
+//!         .provides_incrementally([MySetupKey::SetupGraph]),
+//! );
+//! ```
+//!
+//! Only RON is supported. Definitions in other serde formats, such as TOML, can be deserialized
+//! with that format's own crate and applied with [`SetupGraphDefinition::apply`].
+
+#[cfg(feature = "assets")]
+use crate::Progress;
+use crate::{ProviderInfo, ProviderKeys, SetupGraphError, SetupKey, SetupTracker};
+#[cfg(feature = "assets")]
+use bevy_app::{App, Plugin};
+#[cfg(feature = "assets")]
+use bevy_asset::{
+	Asset, AssetApp, AssetLoader, AssetPath, AssetServer, Assets, AsyncReadExt, Handle,
+	LoadContext, io::Reader,
+};
+use bevy_ecs::{prelude::*, system::SystemId};
+#[cfg(feature = "assets")]
+use bevy_log::{error, warn};
+use bevy_platform::collections::HashMap;
+#[cfg(feature = "assets")]
+use bevy_reflect::TypePath;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::borrow::Cow;
+use std::fmt::{Debug, Display, Formatter};
+use std::time::Duration;
+
+/// Named systems that [`SetupGraphDefinition`]s can refer to.
+#[derive(Resource, Debug, Default)]
+pub struct SetupSystemRegistry {
+	systems: HashMap<String, SystemId>,
+}
+
+impl SetupSystemRegistry {
+	/// Registers `system` in `world` under `name`, replacing any system previously registered
+	/// under the same name.
+	pub fn register<S: IntoSystem<(), (), M> + 'static, M>(
+		&mut self,
+		name: impl Into<String>,
+		system: S,
+		world: &mut World,
+	) -> SystemId {
+		let id = world.register_system(system);
+		self.systems.insert(name.into(), id);
+		id
+	}
+
+	/// Returns the system registered under `name`.
+	pub fn get(&self, name: &str) -> Option<SystemId> {
+		self.systems.get(name).copied()
+	}
+}
+
+/// A setup graph described as data, e.g. in a RON file:
+///
+/// ```ron
+/// (
+///     providers: [
+///         (system: "load_config", provides: [LoadConfig]),
+///         (
+///             system: "load_assets",
+///             name: Some("Load assets"),
+///             requires: [LoadConfig],
+///             provides: [LoadAssets],
+///         ),
+///     ],
+/// )
+/// ```
+///
+/// Keys are deserialized with `K`'s `Deserialize` implementation. Other serde formats, such as
+/// TOML, can be used by deserializing this type directly and calling [`apply`](Self::apply), but
+/// errors then can't point at their location in the source.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(bound = "K: DeserializeOwned")]
+pub struct SetupGraphDefinition<K: SetupKey> {
+	/// The providers in the graph.
+	pub providers: Vec<ProviderDefinition<K>>,
+	/// The source text this definition was parsed from, used to locate errors.
+	#[serde(skip)]
+	source: Option<String>,
+}
+
+/// A single provider in a [`SetupGraphDefinition`].
+#[derive(Deserialize, Debug, Clone)]
+#[serde(bound = "K: DeserializeOwned")]
+pub struct ProviderDefinition<K: SetupKey> {
+	/// The name the provider's system was registered under in the [`SetupSystemRegistry`].
+	pub system: String,
+	/// See [`Provider::named`](crate::Provider::named). Defaults to `system`.
+	#[serde(default)]
+	pub name: Option<String>,
+	/// See [`Provider::description`](crate::Provider::description).
+	#[serde(default)]
+	pub description: Option<String>,
+	/// See [`Provider::tags`](crate::Provider::tags).
+	#[serde(default)]
+	pub tags: Vec<String>,
+	/// See [`IntoDependencyProvider::requires`](crate::IntoDependencyProvider::requires).
+	#[serde(default)]
+	pub requires: Vec<K>,
+	/// See [`IntoDependencyProvider::requires_optional`](crate::IntoDependencyProvider::requires_optional).
+	#[serde(default)]
+	pub requires_optional: Vec<K>,
+	/// See [`IntoDependencyProvider::provides`](crate::IntoDependencyProvider::provides).
+	#[serde(default)]
+	pub provides: Vec<K>,
+	/// See [`IntoDependencyProvider::provides_any`](crate::IntoDependencyProvider::provides_any).
+	#[serde(default)]
+	pub provides_any: Vec<K>,
+	/// See [`IntoDependencyProvider::fallback_for`](crate::IntoDependencyProvider::fallback_for).
+	#[serde(default)]
+	pub fallback_for: Vec<K>,
+	/// See [`Provider::timeout`](crate::Provider::timeout), in seconds.
+	#[serde(default)]
+	pub timeout_secs: Option<f32>,
+	/// See [`Provider::weight`](crate::Provider::weight).
+	#[serde(default)]
+	pub weight: Option<f32>,
+	/// See [`Provider::repeat_until_provided`](crate::Provider::repeat_until_provided).
+	#[serde(default)]
+	pub repeat_until_provided: bool,
+}
+
+impl<K: SetupKey + DeserializeOwned> SetupGraphDefinition<K> {
+	/// Parses a definition from RON source text.
+	pub fn from_ron(source: &str) -> Result<Self, SetupDataError> {
+		let mut definition = ron::from_str::<Self>(source).map_err(|e| SetupDataError::Parse {
+			message: e.code.to_string(),
+			location: Some(Location {
+				line: e.position.line,
+				column: e.position.col,
+			}),
+		})?;
+		definition.source = Some(source.to_owned());
+		Ok(definition)
+	}
+}
+
+impl<K: SetupKey> SetupGraphDefinition<K> {
+	/// Registers every provider in this definition with the `SetupTracker<K>` in `world`, looking
+	/// up their systems in the world's [`SetupSystemRegistry`].
+	///
+	/// All system names and values are checked before anything is registered, so on error the
+	/// tracker is left unchanged. Once every other provider has been registered, check the
+	/// resulting graph with [`validate`](Self::validate).
+	pub fn apply(&self, world: &mut World) -> Result<Vec<SystemId>, SetupDataError> {
+		let registry = world.get_resource_or_init::<SetupSystemRegistry>();
+		let mut systems = Vec::with_capacity(self.providers.len());
+		let mut timeouts = Vec::with_capacity(self.providers.len());
+		let mut errors = Vec::new();
+		for (i, provider) in self.providers.iter().enumerate() {
+			let location = self.locate(&provider.system, i);
+			match provider
+				.timeout_secs
+				.map(Duration::try_from_secs_f32)
+				.transpose()
+			{
+				Ok(timeout) => timeouts.push(timeout),
+				Err(e) => errors.push(SetupDataError::InvalidTimeout {
+					system: provider.system.clone(),
+					message: e.to_string(),
+					location,
+				}),
+			}
+			match registry.get(&provider.system) {
+				Some(id) if systems.contains(&id) => errors.push(SetupDataError::DuplicateSystem {
+					system: provider.system.clone(),
+					location,
+				}),
+				Some(id) => systems.push(id),
+				None => errors.push(SetupDataError::UnknownSystem {
+					system: provider.system.clone(),
+					location,
+				}),
+			}
+		}
+		if !errors.is_empty() {
+			return Err(SetupDataError::Multiple(errors));
+		}
+
+		world.resource_scope::<SetupTracker<K>, _>(|world, mut tracker| {
+			for ((id, timeout), provider) in systems.iter().zip(timeouts).zip(&self.providers) {
+				let mut provides = provider
+					.provides
+					.iter()
+					.cloned()
+					.collect::<ProviderKeys<K>>();
+				provides.extend(provider.provides_any.iter().cloned());
+				provides.extend(provider.fallback_for.iter().cloned());
+				let info = ProviderInfo {
+					requires: provider.requires.iter().cloned().collect(),
+					requires_optional: provider.requires_optional.clone(),
+					provides,
+					provides_any: provider.provides_any.clone(),
+					fallback_for: provider.fallback_for.clone(),
+					timeout,
+					weight: provider.weight,
+					name: Cow::Owned(provider.name().to_owned()),
+					description: provider.description.clone().map(Cow::Owned),
+					tags: provider.tags.iter().cloned().map(Cow::Owned).collect(),
+					on_cancel: None,
+					repeat_until_provided: provider.repeat_until_provided,
+					reports_progress: false,
+					scheduled: false,
+				};
+				tracker.register_provider(*id, info, world);
+			}
+		});
+		Ok(systems)
+	}
+
+	/// Finds the location of the provider registered with `name` in the source text, if it is
+	/// part of this definition.
+	fn locate_name(&self, name: &str) -> Option<Location> {
+		let (i, provider) = self
+			.providers
+			.iter()
+			.enumerate()
+			.find(|(_, provider)| provider.name() == name)?;
+		self.locate(&provider.system, i)
+	}
+
+	/// Finds the location of the `index`th provider's system name in the source text, if known.
+	fn locate(&self, system: &str, index: usize) -> Option<Location> {
+		let source = self.source.as_deref()?;
+		let quoted = format!("\"{system}\"");
+		// Skip earlier providers that use the same system name
+		let skip = self.providers[..index]
+			.iter()
+			.filter(|provider| provider.system == system)
+			.count();
+		let (offset, _) = source.match_indices(&quoted).nth(skip)?;
+		let before = &source[..offset];
+		let line = before.matches('\n').count() + 1;
+		let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+		Some(Location { line, column })
+	}
+}
+
+impl<K: SetupKey + Debug> SetupGraphDefinition<K> {
+	/// Validates the `SetupTracker<K>` in `world` with [`SetupTracker::validate`], pointing each
+	/// error at a provider from this definition where possible.
+	///
+	/// Missing keys point at the first provider from this definition that requires them,
+	/// duplicate providers at the last one that provides the key, and cycles at the first
+	/// provider along the cycle.
+	pub fn validate(&self, world: &mut World) -> Result<(), SetupDataError> {
+		let Err(invalid) = SetupTracker::<K>::validate(world) else {
+			return Ok(());
+		};
+		let errors = invalid
+			.errors
+			.iter()
+			.map(|error| {
+				let location = match error {
+					SetupGraphError::UnprovidedKey { required_by, .. } => {
+						required_by.iter().find_map(|name| self.locate_name(name))
+					}
+					SetupGraphError::DuplicateProviders { providers, .. } => providers
+						.iter()
+						.rev()
+						.find_map(|name| self.locate_name(name)),
+					SetupGraphError::Cycle(cycle) => {
+						cycle.via.iter().find_map(|name| self.locate_name(name))
+					}
+					SetupGraphError::TooLarge(_) => None,
+				};
+				SetupDataError::Invalid {
+					message: error.to_string(),
+					location,
+				}
+			})
+			.collect();
+		Err(SetupDataError::Multiple(errors))
+	}
+}
+
+impl<K: SetupKey> ProviderDefinition<K> {
+	/// The name the provider is registered with: `name` if given, or `system` otherwise.
+	pub fn name(&self) -> &str {
+		self.name.as_deref().unwrap_or(&self.system)
+	}
+}
+
+/// A line and column in a setup graph definition's source text, both starting at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+	/// The line number.
+	pub line: usize,
+	/// The column number, in bytes.
+	pub column: usize,
+}
+
+impl Display for Location {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}:{}", self.line, self.column)
+	}
+}
+
+/// An error loading or applying a [`SetupGraphDefinition`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SetupDataError {
+	/// The source text could not be parsed.
+	Parse {
+		/// What went wrong.
+		message: String,
+		/// Where it went wrong.
+		location: Option<Location>,
+	},
+	/// A provider refers to a system that isn't in the [`SetupSystemRegistry`].
+	UnknownSystem {
+		/// The name of the system.
+		system: String,
+		/// Where the system is referred to.
+		location: Option<Location>,
+	},
+	/// More than one provider refers to the same system.
+	DuplicateSystem {
+		/// The name of the system.
+		system: String,
+		/// Where the system is referred to again.
+		location: Option<Location>,
+	},
+	/// A provider's `timeout_secs` is negative, not a number, or too large.
+	InvalidTimeout {
+		/// The name of the provider's system.
+		system: String,
+		/// Why the timeout is invalid.
+		message: String,
+		/// Where the provider's system is referred to.
+		location: Option<Location>,
+	},
+	/// The setup graph failed [validation](SetupGraphDefinition::validate).
+	Invalid {
+		/// The validation error.
+		message: String,
+		/// The provider from the definition the error concerns.
+		location: Option<Location>,
+	},
+	/// Several errors.
+	Multiple(Vec<SetupDataError>),
+}
+
+impl Display for SetupDataError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let (message, location) = match self {
+			SetupDataError::Parse { message, location } => (message.clone(), location),
+			SetupDataError::UnknownSystem { system, location } => (
+				format!("system `{system}` is not in the `SetupSystemRegistry`"),
+				location,
+			),
+			SetupDataError::DuplicateSystem { system, location } => (
+				format!("system `{system}` is used by more than one provider"),
+				location,
+			),
+			SetupDataError::InvalidTimeout {
+				system,
+				message,
+				location,
+			} => (
+				format!("invalid timeout for system `{system}`: {message}"),
+				location,
+			),
+			SetupDataError::Invalid { message, location } => (message.clone(), location),
+			SetupDataError::Multiple(errors) => {
+				for (i, error) in errors.iter().enumerate() {
+					if i > 0 {
+						writeln!(f)?;
+					}
+					write!(f, "{error}")?;
+				}
+				return Ok(());
+			}
+		};
+		match location {
+			Some(location) => write!(f, "{location}: {message}"),
+			None => f.write_str(&message),
+		}
+	}
+}
+
+impl std::error::Error for SetupDataError {}
+
+/// The source text of a setup graph definition, loaded from `.setup.ron` files by
+/// [`SetupGraphLoader`].
+///
+/// Definitions are only parsed once their key type is known, by [`load_setup_graph`] or
+/// [`SetupGraphDefinition::from_ron`].
+#[cfg(feature = "assets")]
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct SetupGraphSource(pub String);
+
+/// Loads [`SetupGraphSource`] assets from `.setup.ron` files.
+#[cfg(feature = "assets")]
+#[derive(Debug, Default)]
+pub struct SetupGraphLoader;
+
+#[cfg(feature = "assets")]
+impl AssetLoader for SetupGraphLoader {
+	type Asset = SetupGraphSource;
+	type Settings = ();
+	type Error = std::io::Error;
+
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &(),
+		_load_context: &mut LoadContext<'_>,
+	) -> Result<SetupGraphSource, Self::Error> {
+		let mut source = String::new();
+		reader.read_to_string(&mut source).await?;
+		Ok(SetupGraphSource(source))
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["setup.ron"]
+	}
+}
+
+/// Registers the [`SetupGraphSource`] asset and its [`SetupGraphLoader`].
+#[cfg(feature = "assets")]
+#[derive(Debug, Default)]
+pub struct SetupGraphAssetPlugin;
+
+#[cfg(feature = "assets")]
+impl Plugin for SetupGraphAssetPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_asset::<SetupGraphSource>()
+			.init_asset_loader::<SetupGraphLoader>();
+	}
+}
+
+/// Creates a provider system that loads the setup graph definition at `path` as a
+/// [`SetupGraphSource`] asset, then parses it and [applies](SetupGraphDefinition::apply) it to the
+/// `SetupTracker<K>`. Needs [`SetupGraphAssetPlugin`]. See the [module docs](self).
+///
+/// The system returns [`Progress::ZERO`] until the definition is applied, and
+/// [`Progress::DONE`] afterwards, so it is meant to be registered as an
+/// [incremental provider](crate::IntoIncrementalProvider). If the file can't be loaded or the
+/// definition is invalid, the error is logged with its location in the file, and the system
+/// returns non-finite progress. The definition is only applied once.
+///
+/// The graph is [validated](SetupGraphDefinition::validate) after applying it, and validation
+/// errors are logged as well, but don't fail the provider, since other providers may still be
+/// registered later.
+#[cfg(feature = "assets")]
+pub fn load_setup_graph<K: SetupKey + Debug + DeserializeOwned>(
+	path: impl Into<AssetPath<'static>>,
+) -> impl FnMut(&mut World) -> Progress {
+	let path = path.into();
+	let mut handle = None::<Handle<SetupGraphSource>>;
+	let mut finished = None::<Progress>;
+	move |world| {
+		if let Some(finished) = finished {
+			return finished;
+		}
+		let handle = handle
+			.get_or_insert_with(|| world.resource::<AssetServer>().load(path.clone()))
+			.clone();
+		let server = world.resource::<AssetServer>();
+		if server.load_state(&handle).is_failed() {
+			error!("Failed to load setup graph `{path}`");
+			return *finished.insert(Progress::new(f32::NAN));
+		}
+		let Some(source) = world.resource::<Assets<SetupGraphSource>>().get(&handle) else {
+			return Progress::ZERO;
+		};
+		let result = SetupGraphDefinition::<K>::from_ron(&source.0)
+			.and_then(|definition| definition.apply(world).map(|_| definition));
+		*finished.insert(match result {
+			Ok(definition) => {
+				if let Err(e) = definition.validate(world) {
+					warn!(
+						"Setup graph `{path}` is not valid yet:\n{}",
+						prefix_lines(&path, &e)
+					);
+				}
+				Progress::DONE
+			}
+			Err(e) => {
+				error!(
+					"Failed to apply setup graph `{path}`:\n{}",
+					prefix_lines(&path, &e)
+				);
+				Progress::new(f32::NAN)
+			}
+		})
+	}
+}
+
+/// Prefixes each line of `error` with `path`, so locations read like `path:line:column`.
+#[cfg(feature = "assets")]
+fn prefix_lines(path: &AssetPath, error: &SetupDataError) -> String {
+	error
+		.to_string()
+		.lines()
+		.map(|line| format!("{path}:{line}"))
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Progress, test_utils::TestSetupKey};
+
+	fn world() -> World {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<TestSetupKey>::new(on_finished));
+		let mut registry = SetupSystemRegistry::default();
+		registry.register("load_a", || {}, &mut world);
+		registry.register("load_b", || {}, &mut world);
+		world.insert_resource(registry);
+		world
+	}
+
+	#[test]
+	fn test_apply_definition() {
+		let mut world = world();
+		let definition = SetupGraphDefinition::<TestSetupKey>::from_ron(
+			r#"(
+				providers: [
+					(system: "load_a", provides: [A]),
+					(system: "load_b", name: Some("Load B"), requires: [A], provides: [B]),
+				],
+			)"#,
+		)
+		.unwrap();
+		let systems = definition.apply(&mut world).unwrap();
+
+		let tracker = world.resource::<SetupTracker<TestSetupKey>>();
+		assert_eq!(tracker.providers()[&systems[0]].name(), "load_a");
+		assert_eq!(tracker.providers()[&systems[1]].name(), "Load B");
+		assert_eq!(
+			tracker.providers()[&systems[1]].requires(),
+			[TestSetupKey::A]
+		);
+	}
+
+	#[test]
+	fn test_definition_errors() {
+		let err = SetupGraphDefinition::<TestSetupKey>::from_ron("(providers: [(system: 1)])")
+			.unwrap_err();
+		assert!(matches!(
+			err,
+			SetupDataError::Parse {
+				location: Some(_),
+				..
+			}
+		));
+
+		let mut world = world();
+		let err = SetupGraphDefinition::<TestSetupKey>::from_ron(
+			"(providers: [\n\t(system: \"load_a\"),\n\t(system: \"load_c\"),\n])",
+		)
+		.unwrap()
+		.apply(&mut world)
+		.unwrap_err();
+		assert_eq!(
+			err.to_string(),
+			"3:11: system `load_c` is not in the `SetupSystemRegistry`"
+		);
+		assert!(
+			world
+				.resource::<SetupTracker<TestSetupKey>>()
+				.providers()
+				.is_empty()
+		);
+
+		let err = SetupGraphDefinition::<TestSetupKey>::from_ron(
+			"(providers: [\n\t(system: \"load_a\", timeout_secs: Some(-1.0)),\n])",
+		)
+		.unwrap()
+		.apply(&mut world)
+		.unwrap_err();
+		assert!(
+			err.to_string()
+				.starts_with("2:11: invalid timeout for system `load_a`:")
+		);
+	}
+
+	#[test]
+	fn test_validation_error_locations() {
+		let mut world = world();
+		let definition = SetupGraphDefinition::<TestSetupKey>::from_ron(
+			"(providers: [\n\t(system: \"load_a\", provides: [A]),\n\t\
+			(system: \"load_b\", requires: [A, C], provides: [B]),\n])",
+		)
+		.unwrap();
+		definition.apply(&mut world).unwrap();
+		let SetupDataError::Multiple(errors) = definition.validate(&mut world).unwrap_err() else {
+			panic!("expected multiple errors");
+		};
+		assert_eq!(errors.len(), 1);
+		assert!(matches!(
+			errors[0],
+			SetupDataError::Invalid {
+				location: Some(Location {
+					line: 3,
+					column: 11
+				}),
+				..
+			}
+		));
+	}
+
+	#[cfg(feature = "assets")]
+	#[test]
+	fn test_load_setup_graph() {
+		let dir = std::env::temp_dir().join(format!("bird_barrier_data_{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+		std::fs::write(
+			dir.join("graph.setup.ron"),
+			"(providers: [(system: \"load_a\", provides: [A])])",
+		)
+		.unwrap();
+		std::fs::write(
+			dir.join("broken.setup.ron"),
+			"(providers: [\n\t(system: \"load_c\", provides: [C]),\n])",
+		)
+		.unwrap();
+
+		let mut app = App::new();
+		app.add_plugins((
+			bevy_app::TaskPoolPlugin::default(),
+			bevy_asset::AssetPlugin {
+				file_path: dir.to_string_lossy().into_owned(),
+				..Default::default()
+			},
+			SetupGraphAssetPlugin,
+		));
+		let world = app.world_mut();
+		let on_finished = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<TestSetupKey>::new(on_finished));
+		let mut registry = SetupSystemRegistry::default();
+		registry.register("load_a", || {}, world);
+		world.insert_resource(registry);
+
+		let mut graph =
+			IntoSystem::into_system(load_setup_graph::<TestSetupKey>("graph.setup.ron"));
+		let mut broken =
+			IntoSystem::into_system(load_setup_graph::<TestSetupKey>("broken.setup.ron"));
+		graph.initialize(app.world_mut());
+		broken.initialize(app.world_mut());
+		let mut progress = (Progress::ZERO, Progress::ZERO);
+		for _ in 0..100 {
+			app.update();
+			progress = (
+				graph.run((), app.world_mut()),
+				broken.run((), app.world_mut()),
+			);
+			if progress.0 != Progress::ZERO && progress.1 != Progress::ZERO {
+				break;
+			}
+			std::thread::sleep(std::time::Duration::from_millis(10));
+		}
+		std::fs::remove_dir_all(&dir).unwrap();
+		assert_eq!(progress.0, Progress::DONE);
+		assert!(!progress.1.is_finite());
+		let tracker = app.world().resource::<SetupTracker<TestSetupKey>>();
+		assert_eq!(tracker.providers().len(), 1);
+		assert_eq!(tracker.providers()[0].name(), "load_a");
+	}
+}
@@ -0,0 +1,359 @@
+//! Tools for diagnosing problems with the order providers run in.
+//!
+//! Insert a [`SetupStepper`] to pause [`advance_setup`](crate::advance_setup), and then run one
+//! ready provider at a time with [`SetupStepper::step`]. Progress is still checked while paused, so
+//! the effect of each step can be seen in the tracker, and each step logs which of the provider's
+//! keys finished. To step with a key, add [`step_setup`] to your schedule with an input condition:
+//!
+//! ```rust,ignore
+//! app.insert_resource(SetupStepper::<MySetupKey>::default())
+//!     .add_systems(Update, step_setup::<MySetupKey>.run_if(input_just_pressed(KeyCode::F10)));
+//! ```
+//!
+//! With the `visualization` feature, [`SetupGraphVisualizationPlugin`] also shows a window with
+//! buttons for stepping.
+//!
+//! To pause when a particular key finishes instead, add it to the [`SetupBreakpoints`]. Hitting a
+//! breakpoint pauses the stepper, inserting one if needed, logs the key's providers and the keys
+//! that are still pending, and triggers [`SetupBreakpointHit`]. Observe it to pause the rest of the
+//! app as well:
+//!
+//! ```rust,ignore
+//! app.init_resource::<SetupBreakpoints<MySetupKey>>()
+//!     .add_observer(|_: Trigger<SetupBreakpointHit<MySetupKey>>, mut time: ResMut<Time<Virtual>>| {
+//!         time.pause();
+//!     });
+//! app.world_mut().resource_mut::<SetupBreakpoints<MySetupKey>>().add(MySetupKey::Terrain);
+//! ```
+//!
+//! [`SetupGraphVisualizationPlugin`]: crate::SetupGraphVisualizationPlugin
+
+use crate::{SetupKey, SetupTracker};
+use bevy_ecs::{prelude::*, system::SystemId};
+use bevy_log::info;
+use bevy_platform::collections::HashSet;
+use std::time::Duration;
+
+/// Pauses automatic dispatch of providers, so they can be run one at a time. See the
+/// [module docs](self).
+///
+/// Starts out paused.
+#[derive(Resource, Debug)]
+pub struct SetupStepper<K: SetupKey> {
+	paused: bool,
+	steps: usize,
+	last_step: Option<SetupStep<K>>,
+}
+
+/// A provider run by a [`SetupStepper`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetupStep<K: SetupKey> {
+	/// The provider's system.
+	pub system: SystemId,
+	/// The provider's [name](crate::ProviderInfo::name).
+	pub name: String,
+	/// The keys the provider provides that had finished right after it ran.
+	pub finished: Vec<K>,
+}
+
+impl<K: SetupKey> Default for SetupStepper<K> {
+	fn default() -> Self {
+		Self {
+			paused: true,
+			steps: 0,
+			last_step: None,
+		}
+	}
+}
+
+impl<K: SetupKey> SetupStepper<K> {
+	/// Whether automatic dispatch is paused.
+	pub fn is_paused(&self) -> bool {
+		self.paused
+	}
+
+	/// Pauses automatic dispatch, so providers only run when [stepping](Self::step).
+	pub fn pause(&mut self) {
+		self.paused = true;
+	}
+
+	/// Resumes automatic dispatch, forgetting any steps that haven't been taken yet.
+	pub fn resume(&mut self) {
+		self.paused = false;
+		self.steps = 0;
+	}
+
+	/// Runs one more ready provider the next time [`advance_setup`](crate::advance_setup) runs
+	/// while paused. If no provider is ready, the step waits until one is.
+	pub fn step(&mut self) {
+		self.steps += 1;
+	}
+
+	/// Returns the provider run by the most recent step.
+	pub fn last_step(&self) -> Option<&SetupStep<K>> {
+		self.last_step.as_ref()
+	}
+
+	/// Limits `to_run` to what the stepper allows, and returns whether a step is being taken.
+	pub(crate) fn limit(&mut self, to_run: &mut Vec<SystemId>) -> bool {
+		if !self.paused {
+			return false;
+		}
+		if self.steps == 0 || to_run.is_empty() {
+			to_run.clear();
+			return false;
+		}
+		self.steps -= 1;
+		to_run.truncate(1);
+		true
+	}
+}
+
+/// Records and logs the step that just ran `system`.
+pub(crate) fn record_step<K: SetupKey>(
+	tracker: &SetupTracker<K>,
+	system: SystemId,
+	world: &mut World,
+) {
+	let info = &tracker.providers()[&system];
+	let finished = info
+		.provides()
+		.iter()
+		.filter(|key| tracker.entries()[*key].run(key, world).finished())
+		.cloned()
+		.collect::<Vec<_>>();
+	info!(
+		"Stepped `{}`, finishing [{}]",
+		info.name(),
+		finished
+			.iter()
+			.map(|key| tracker.key_span_name(key))
+			.collect::<Vec<_>>()
+			.join(", ")
+	);
+	if let Some(mut stepper) = world.get_resource_mut::<SetupStepper<K>>() {
+		stepper.last_step = Some(SetupStep {
+			system,
+			name: info.name().to_owned(),
+			finished,
+		});
+	}
+}
+
+/// Keys to pause setup at when they finish. See the [module docs](self).
+#[derive(Resource, Debug)]
+pub struct SetupBreakpoints<K: SetupKey> {
+	keys: HashSet<K>,
+}
+
+impl<K: SetupKey> Default for SetupBreakpoints<K> {
+	fn default() -> Self {
+		Self {
+			keys: Default::default(),
+		}
+	}
+}
+
+impl<K: SetupKey> SetupBreakpoints<K> {
+	/// Pauses setup when `key` finishes.
+	pub fn add(&mut self, key: K) {
+		self.keys.insert(key);
+	}
+
+	/// Removes the breakpoint on `key`. Returns `false` if there wasn't one.
+	pub fn remove(&mut self, key: &K) -> bool {
+		self.keys.remove(key)
+	}
+
+	/// Whether setup pauses when `key` finishes.
+	pub fn contains(&self, key: &K) -> bool {
+		self.keys.contains(key)
+	}
+}
+
+/// Observer trigger for when a key in the [`SetupBreakpoints`] finishes.
+#[derive(Event, Debug, Clone)]
+pub struct SetupBreakpointHit<K: SetupKey> {
+	/// The key that finished.
+	pub key: K,
+	/// The names of the key's providers that had been dispatched.
+	pub providers: Vec<String>,
+	/// The keys that haven't finished yet.
+	pub pending: Vec<K>,
+	/// How long the key took to finish after the first of its providers was dispatched.
+	pub duration: Option<Duration>,
+	/// How long the key took to finish after the first provider of any key was dispatched.
+	pub elapsed: Duration,
+}
+
+/// Pauses setup if `key`, which just finished, has a breakpoint.
+pub(crate) fn check_breakpoint<K: SetupKey>(key: &K, tracker: &SetupTracker<K>, world: &mut World) {
+	if !world
+		.get_resource::<SetupBreakpoints<K>>()
+		.is_some_and(|breakpoints| breakpoints.contains(key))
+	{
+		return;
+	}
+	let Some(&finished) = tracker.completed.get(key) else {
+		return;
+	};
+	let started = tracker
+		.providers_of(key)
+		.filter_map(|(system, _)| Some((system, *tracker.started.get(&system)?)))
+		.collect::<Vec<_>>();
+	let since = |start| finished.saturating_duration_since(start);
+	let hit = SetupBreakpointHit {
+		key: key.clone(),
+		providers: started
+			.iter()
+			.map(|(system, _)| tracker.providers()[system].name().to_owned())
+			.collect(),
+		pending: tracker
+			.entries()
+			.keys()
+			.filter(|key| !tracker.completed.contains_key(*key))
+			.cloned()
+			.collect(),
+		duration: started.iter().map(|(_, start)| *start).min().map(since),
+		elapsed: tracker
+			.started
+			.values()
+			.min()
+			.copied()
+			.map(since)
+			.unwrap_or_default(),
+	};
+	info!(
+		"Breakpoint: {} finished after {:.2?} ({:.2?} into setup), provided by [{}]. Pending: [{}]",
+		tracker.key_span_name(key),
+		hit.duration.unwrap_or_default(),
+		hit.elapsed,
+		hit.providers.join(", "),
+		hit.pending
+			.iter()
+			.map(|key| tracker.key_span_name(key))
+			.collect::<Vec<_>>()
+			.join(", ")
+	);
+	world.get_resource_or_init::<SetupStepper<K>>().pause();
+	world.trigger(hit);
+}
+
+/// System that takes one [step](SetupStepper::step), e.g. when a key is pressed. Does nothing if
+/// there is no [`SetupStepper`].
+pub fn step_setup<K: SetupKey>(stepper: Option<ResMut<SetupStepper<K>>>) {
+	if let Some(mut stepper) = stepper {
+		stepper.step();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{IntoDependencyProvider, Progress, RegisterProvider, advance_setup};
+	use bevy_ecs::system::RunSystemOnce;
+
+	#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+	enum TestSetupKey {
+		A,
+		B,
+	}
+
+	impl SetupKey for TestSetupKey {
+		fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+			match self {
+				TestSetupKey::A => {
+					world.register_system(|ran: Option<Res<RanA>>| ran.is_some().into())
+				}
+				TestSetupKey::B => {
+					world.register_system(|ran: Option<Res<RanB>>| ran.is_some().into())
+				}
+			}
+		}
+	}
+
+	#[derive(Resource)]
+	struct RanA;
+
+	#[derive(Resource)]
+	struct RanB;
+
+	#[test]
+	fn test_setup_stepper() {
+		let mut world = World::new();
+		let tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		world.insert_resource(tracker);
+		world.init_resource::<SetupStepper<TestSetupKey>>();
+		let a = (|mut commands: Commands| commands.insert_resource(RanA))
+			.provides([TestSetupKey::A])
+			.register(&mut world);
+		world.register_provider(
+			(|mut commands: Commands| commands.insert_resource(RanB)).provides([TestSetupKey::B]),
+		);
+
+		advance_setup::<TestSetupKey>(&mut world);
+		assert!(!world.contains_resource::<RanA>());
+
+		world.run_system_once(step_setup::<TestSetupKey>).unwrap();
+		advance_setup::<TestSetupKey>(&mut world);
+		assert!(world.contains_resource::<RanA>());
+		assert!(!world.contains_resource::<RanB>());
+		let stepper = world.resource::<SetupStepper<TestSetupKey>>();
+		let step = stepper.last_step().unwrap();
+		assert_eq!(step.system, a);
+		assert_eq!(step.finished, [TestSetupKey::A]);
+
+		world.resource_mut::<SetupStepper<TestSetupKey>>().resume();
+		advance_setup::<TestSetupKey>(&mut world);
+		assert!(world.contains_resource::<RanB>());
+	}
+
+	#[test]
+	fn test_setup_breakpoints() {
+		#[derive(Resource, Default)]
+		struct Hits(Vec<SetupBreakpointHit<TestSetupKey>>);
+
+		let mut world = World::new();
+		let tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		world.insert_resource(tracker);
+		world.init_resource::<Hits>();
+		world.add_observer(
+			|trigger: Trigger<SetupBreakpointHit<TestSetupKey>>, mut hits: ResMut<Hits>| {
+				hits.0.push(trigger.event().clone());
+			},
+		);
+		let mut breakpoints = SetupBreakpoints::default();
+		breakpoints.add(TestSetupKey::A);
+		world.insert_resource(breakpoints);
+		world
+			.register_provider(
+				(|mut commands: Commands| commands.insert_resource(RanA))
+					.provides([TestSetupKey::A])
+					.named("a"),
+			)
+			.register_provider(
+				(|mut commands: Commands| commands.insert_resource(RanB))
+					.requires([TestSetupKey::A])
+					.provides([TestSetupKey::B]),
+			);
+
+		advance_setup::<TestSetupKey>(&mut world);
+		assert!(world.contains_resource::<RanA>());
+		assert!(!world.contains_resource::<SetupStepper<TestSetupKey>>());
+
+		advance_setup::<TestSetupKey>(&mut world);
+		assert!(!world.contains_resource::<RanB>());
+		assert!(world.resource::<SetupStepper<TestSetupKey>>().is_paused());
+		let hits = &world.resource::<Hits>().0;
+		assert_eq!(hits.len(), 1);
+		assert_eq!(hits[0].key, TestSetupKey::A);
+		assert_eq!(hits[0].providers, ["a"]);
+		assert_eq!(hits[0].pending, [TestSetupKey::B]);
+		assert!(hits[0].duration.is_some());
+
+		world.resource_mut::<SetupStepper<TestSetupKey>>().resume();
+		advance_setup::<TestSetupKey>(&mut world);
+		assert!(world.contains_resource::<RanB>());
+		assert_eq!(world.resource::<Hits>().0.len(), 1);
+	}
+}
@@ -0,0 +1,293 @@
+//! Progress tracking for byte transfers, such as patch downloads or fetching remote configuration.
+//!
+//! Anything that can report how many bytes it has transferred can implement [`ByteStreamProgress`]
+//! and be tracked with [`byte_stream_progress`]. For fetches that don't already have their own
+//! progress reporting, [`fetch_bytes`] runs an async function on the `IoTaskPool`, passing it a
+//! [`ByteCounter`] to update as data arrives:
+//!
+//! ```rust,ignore
+//! struct RemoteConfig(Vec<u8>);
+//!
+//! app.register_provider(
+//!     fetch_bytes(|counter: ByteCounter| async move {
+//!         let mut response = my_http_client::get(CONFIG_URL).await?;
+//!         counter.set_total(response.content_length());
+//!         let mut body = Vec::new();
+//!         while let Some(chunk) = response.chunk().await? {
+//!             counter.add(chunk.len() as u64);
+//!             body.extend_from_slice(&chunk);
+//!         }
+//!         Ok(RemoteConfig(body))
+//!     })
+//!     .provides([MySetupKey::FetchConfig]),
+//! );
+//!
+//! // In `MySetupKey::register_progress_checker`:
+//! world.register_system(byte_stream_progress::<ByteTransfer<RemoteConfig>>)
+//! ```
+//!
+//! If setup is [cancelled](crate::cancel_setup) or the tracker is cleaned up while `fetch` is
+//! running, its future is dropped and the transfer is marked failed. Its [`ByteCounter`] also has
+//! the tracker's [cancellation token](ByteCounter::cancellation), for any work `fetch` hands off
+//! elsewhere.
+//!
+//! On wasm32, the `IoTaskPool` runs `fetch` on the browser's event loop, so its future doesn't
+//! need to be `Send`, e.g. when it awaits a `JsFuture`. Its output does, since it ends up in a
+//! resource.
+
+use crate::{CancellationToken, CurrentCancellationToken, Progress};
+use bevy_ecs::error::BevyError;
+use bevy_ecs::prelude::*;
+use bevy_log::{debug, error};
+use bevy_tasks::{ConditionalSendFuture, IoTaskPool};
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Implement this trait for anything that transfers a known or unknown number of bytes.
+pub trait ByteStreamProgress {
+	/// The number of bytes transferred so far.
+	fn bytes_done(&self) -> u64;
+
+	/// The total number of bytes to transfer, if known.
+	fn bytes_total(&self) -> Option<u64>;
+
+	/// Whether the transfer has finished, including any processing of the data afterwards.
+	///
+	/// Defaults to whether every byte has been transferred.
+	fn is_complete(&self) -> bool {
+		self.bytes_total()
+			.is_some_and(|total| self.bytes_done() >= total)
+	}
+
+	/// Whether the transfer has failed. Defaults to `false`.
+	fn is_failed(&self) -> bool {
+		false
+	}
+
+	/// The progress of the transfer.
+	///
+	/// Progress is the ratio of bytes transferred to the total, but only reaches
+	/// [`Progress::DONE`] once the transfer [is complete](Self::is_complete). While the total is
+	/// unknown, returns [`Progress::ZERO`]. A failed transfer returns non-finite progress.
+	fn progress(&self) -> Progress {
+		if self.is_failed() {
+			return Progress::new(f32::NAN);
+		}
+		if self.is_complete() {
+			return Progress::DONE;
+		}
+		match self.bytes_total() {
+			Some(total) if total > 0 => {
+				Progress::new((self.bytes_done() as f64 / total as f64).min(0.99) as f32)
+			}
+			_ => Progress::ZERO,
+		}
+	}
+}
+
+/// Helper function to check the progress of a byte stream stored as a resource.
+pub fn byte_stream_progress<S: ByteStreamProgress + Resource>(stream: Option<Res<S>>) -> Progress {
+	stream.map_or(Progress::ZERO, |stream| stream.progress())
+}
+
+const RUNNING: u8 = 0;
+const COMPLETE: u8 = 1;
+const FAILED: u8 = 2;
+
+/// A thread-safe counter of transferred bytes that can be shared between a task and the world.
+///
+/// Clones share the same counts.
+#[derive(Debug, Clone)]
+pub struct ByteCounter(Arc<ByteCounterInner>);
+
+#[derive(Debug)]
+struct ByteCounterInner {
+	done: AtomicU64,
+	/// `u64::MAX` while unknown.
+	total: AtomicU64,
+	state: AtomicU8,
+	cancellation: CancellationToken,
+}
+
+impl Default for ByteCounter {
+	fn default() -> Self {
+		Self::with_cancellation(CancellationToken::new())
+	}
+}
+
+impl ByteCounter {
+	/// Creates a counter for a transfer that should stop when `cancellation` is cancelled.
+	pub fn with_cancellation(cancellation: CancellationToken) -> Self {
+		Self(Arc::new(ByteCounterInner {
+			done: AtomicU64::new(0),
+			total: AtomicU64::new(u64::MAX),
+			state: AtomicU8::new(RUNNING),
+			cancellation,
+		}))
+	}
+
+	/// The token that is cancelled when the transfer should stop.
+	pub fn cancellation(&self) -> &CancellationToken {
+		&self.0.cancellation
+	}
+
+	/// Adds `bytes` to the number of bytes transferred.
+	pub fn add(&self, bytes: u64) {
+		self.0.done.fetch_add(bytes, Ordering::Relaxed);
+	}
+
+	/// Sets the total number of bytes to transfer, e.g. from a `Content-Length` header.
+	pub fn set_total(&self, total: Option<u64>) {
+		self.0
+			.total
+			.store(total.unwrap_or(u64::MAX), Ordering::Relaxed);
+	}
+
+	/// Marks the transfer as complete, regardless of the byte counts.
+	pub fn complete(&self) {
+		self.0.state.store(COMPLETE, Ordering::Release);
+	}
+
+	/// Marks the transfer as failed.
+	pub fn fail(&self) {
+		self.0.state.store(FAILED, Ordering::Release);
+	}
+}
+
+impl ByteStreamProgress for ByteCounter {
+	fn bytes_done(&self) -> u64 {
+		self.0.done.load(Ordering::Relaxed)
+	}
+
+	fn bytes_total(&self) -> Option<u64> {
+		let total = self.0.total.load(Ordering::Relaxed);
+		(total != u64::MAX).then_some(total)
+	}
+
+	/// Only `true` once [`complete`](Self::complete) has been called, since reading the last byte
+	/// doesn't mean the data has been processed.
+	fn is_complete(&self) -> bool {
+		self.0.state.load(Ordering::Acquire) == COMPLETE
+	}
+
+	fn is_failed(&self) -> bool {
+		self.0.state.load(Ordering::Acquire) == FAILED
+	}
+}
+
+/// Resource tracking a transfer started by [`fetch_bytes`], holding its output once it's done.
+#[derive(Resource)]
+pub struct ByteTransfer<T: Send + Sync + 'static> {
+	counter: ByteCounter,
+	output: Arc<Mutex<Option<T>>>,
+}
+
+impl<T: Send + Sync + 'static> ByteTransfer<T> {
+	/// The counter the transfer reports its progress to.
+	pub fn counter(&self) -> &ByteCounter {
+		&self.counter
+	}
+
+	/// Takes the output of the transfer, if it has completed and hasn't been taken already.
+	pub fn take_output(&self) -> Option<T> {
+		self.output.lock().unwrap().take()
+	}
+}
+
+impl<T: Send + Sync + 'static> ByteStreamProgress for ByteTransfer<T> {
+	fn bytes_done(&self) -> u64 {
+		self.counter.bytes_done()
+	}
+
+	fn bytes_total(&self) -> Option<u64> {
+		self.counter.bytes_total()
+	}
+
+	fn is_complete(&self) -> bool {
+		self.counter.is_complete()
+	}
+
+	fn is_failed(&self) -> bool {
+		self.counter.is_failed()
+	}
+}
+
+/// Creates a provider system that runs `fetch` on the `IoTaskPool` and inserts a
+/// [`ByteTransfer<T>`] resource to track it.
+///
+/// `fetch` should report the bytes it transfers to the [`ByteCounter`] it is passed. The counter is
+/// marked complete when `fetch` returns `Ok`, and failed when it returns an error, which is also
+/// logged. If a `ByteTransfer<T>` already exists, the provider does nothing, so each `T` should
+/// only be fetched by one provider at a time.
+///
+/// `fetch` is stopped and the counter marked failed when the tracker's
+/// [cancellation token](crate::SetupTracker::cancellation_token) is cancelled.
+#[allow(clippy::type_complexity)]
+pub fn fetch_bytes<T, F, Fut>(
+	fetch: F,
+) -> impl FnMut(Commands, Option<Res<ByteTransfer<T>>>, Option<Res<CurrentCancellationToken>>)
+where
+	T: Send + Sync + 'static,
+	F: Fn(ByteCounter) -> Fut + Send + Sync + 'static,
+	Fut: ConditionalSendFuture<Output = Result<T, BevyError>> + 'static,
+{
+	move |mut commands, existing, cancellation| {
+		if existing.is_some() {
+			return;
+		}
+		let counter = ByteCounter::with_cancellation(
+			cancellation.map_or_else(CancellationToken::new, |token| token.0.clone()),
+		);
+		let output = Arc::new(Mutex::new(None));
+		let task = fetch(counter.clone());
+		IoTaskPool::get()
+			.spawn({
+				let counter = counter.clone();
+				let output = output.clone();
+				async move {
+					let Some(result) = counter.cancellation().run_until_cancelled(task).await
+					else {
+						debug!("Cancelled fetching {}", std::any::type_name::<T>());
+						counter.fail();
+						return;
+					};
+					match result {
+						Ok(value) => {
+							*output.lock().unwrap() = Some(value);
+							counter.complete();
+						}
+						Err(e) => {
+							error!("Failed to fetch {}: {e}", std::any::type_name::<T>());
+							counter.fail();
+						}
+					}
+				}
+			})
+			.detach();
+		commands.insert_resource(ByteTransfer { counter, output });
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_byte_counter_progress() {
+		let counter = ByteCounter::default();
+		counter.add(100);
+		assert_eq!(counter.progress(), Progress::ZERO);
+
+		counter.set_total(Some(400));
+		assert_eq!(counter.progress(), Progress::new(0.25));
+
+		counter.add(300);
+		assert!(!counter.progress().finished());
+
+		counter.clone().complete();
+		assert_eq!(counter.progress(), Progress::DONE);
+
+		counter.fail();
+		assert!(!counter.progress().is_finite());
+	}
+}
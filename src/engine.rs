@@ -0,0 +1,202 @@
+//! Setup keys for engine-level milestones, so setup can wait for the engine without each project
+//! re-implementing the checks.
+//!
+//! The engine itself does the work, so [`EngineReady`] keys are provided by a provider that does
+//! nothing. To mix them with your own keys, wrap them in a variant of your key type and forward to
+//! their progress checker:
+//!
+//! ```rust,ignore
+//! #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+//! enum MySetupKey {
+//!     Engine(EngineReady),
+//!     CompileShaders,
+//! }
+//!
+//! impl SetupKey for MySetupKey {
+//!     fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+//!         match self {
+//!             MySetupKey::Engine(milestone) => milestone.register_progress_checker(world),
+//!             MySetupKey::CompileShaders => world.register_system(pipelines_ready_progress()),
+//!         }
+//!     }
+//! }
+//!
+//! app.register_provider(
+//!     (|| {})
+//!         .provides([
+//!             MySetupKey::Engine(EngineReady::PrimaryWindow),
+//!             MySetupKey::Engine(EngineReady::RenderDevice),
+//!         ])
+//!         .named("engine"),
+//! );
+//! ```
+
+use crate::{Progress, SetupKey};
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemId;
+
+/// Engine-level facts that setup can depend on.
+///
+/// Each variant is only available with the feature that enables its checker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EngineReady {
+	/// The primary window's OS window has been created. See [`primary_window_progress`].
+	#[cfg(feature = "window")]
+	PrimaryWindow,
+	/// The GPU device has been initialized. See [`render_device_progress`].
+	#[cfg(feature = "render")]
+	RenderDevice,
+	/// An audio output device is available. See [`audio_output_progress`].
+	#[cfg(feature = "audio")]
+	AudioOutput,
+}
+
+impl SetupKey for EngineReady {
+	fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+		match *self {
+			#[cfg(feature = "window")]
+			EngineReady::PrimaryWindow => world.register_system(primary_window_progress),
+			#[cfg(feature = "render")]
+			EngineReady::RenderDevice => world.register_system(render_device_progress),
+			#[cfg(feature = "audio")]
+			EngineReady::AudioOutput => world.register_system(audio_output_progress),
+		}
+	}
+}
+
+/// Helper function to check whether the primary window's OS window has been created.
+///
+/// The `PrimaryWindow` entity is spawned as soon as `WindowPlugin` is built, but the windowing
+/// backend only adds its `RawHandleWrapper` once the OS window actually exists.
+#[cfg(feature = "window")]
+pub fn primary_window_progress(
+	q: Query<
+		(),
+		(
+			With<bevy_window::PrimaryWindow>,
+			With<bevy_window::RawHandleWrapper>,
+		),
+	>,
+) -> Progress {
+	(!q.is_empty()).into()
+}
+
+/// Helper function to check whether the GPU device has been initialized.
+///
+/// `RenderPlugin` inserts `RenderDevice` into the main world once the renderer has finished
+/// initializing, which may take several frames on some platforms.
+#[cfg(feature = "render")]
+pub fn render_device_progress(
+	device: Option<Res<bevy_render::renderer::RenderDevice>>,
+) -> Progress {
+	device.is_some().into()
+}
+
+/// Marks the muted sound that [`audio_output_progress`] plays to find out whether `bevy_audio`
+/// has an output stream, and the frame it was spawned on.
+#[cfg(feature = "audio")]
+#[derive(Component, Debug)]
+pub struct AudioOutputProbe(u32);
+
+/// Helper function to check whether `bevy_audio` managed to open an audio output stream.
+///
+/// `bevy_audio` doesn't expose its output stream, only whether it plays sounds, so this spawns a
+/// muted [`AudioOutputProbe`] sound. `AudioPlugin` gives it an `AudioSink` at the end of the frame
+/// if it has an output stream, in which case progress is [`Progress::DONE`]. If the sound still
+/// isn't playing two frames later, according to the `FrameCount` resource, or `AudioPlugin`
+/// wasn't added, progress is non-finite, so a
+/// [fallback](crate::IntoDependencyProvider::fallback_for) can disable sound instead. The probe
+/// is despawned either way.
+///
+/// Without `FrameCount`, which is added by `MinimalPlugins` and `DefaultPlugins`, a missing output
+/// stream can't be told apart from a frame that hasn't ended yet, so progress stays at zero.
+#[cfg(feature = "audio")]
+pub fn audio_output_progress(
+	mut commands: Commands,
+	pitches: Option<ResMut<bevy_asset::Assets<bevy_audio::Pitch>>>,
+	frame: Option<Res<bevy_diagnostic::FrameCount>>,
+	probes: Query<(Entity, &AudioOutputProbe, Has<bevy_audio::AudioSink>)>,
+	mut result: Local<Option<Progress>>,
+) -> Progress {
+	if let Some(result) = *result {
+		return result;
+	}
+	let frame = frame.map(|frame| frame.0);
+	let Some((probe, spawned_at, playing)) = probes
+		.iter()
+		.next()
+		.map(|(probe, AudioOutputProbe(spawned_at), playing)| (probe, *spawned_at, playing))
+	else {
+		let Some(mut pitches) = pitches else {
+			// `AudioPlugin` wasn't added
+			return *result.insert(Progress::new(f32::NAN));
+		};
+		commands.spawn((
+			bevy_audio::AudioPlayer(pitches.add(bevy_audio::Pitch::new(440.0, Default::default()))),
+			bevy_audio::PlaybackSettings::ONCE.muted(),
+			AudioOutputProbe(frame.unwrap_or_default()),
+		));
+		return Progress::ZERO;
+	};
+	let progress = if playing {
+		Progress::DONE
+	} else if frame.is_some_and(|frame| frame.wrapping_sub(spawned_at) >= 2) {
+		Progress::new(f32::NAN)
+	} else {
+		return Progress::ZERO;
+	};
+	commands.entity(probe).despawn();
+	*result.insert(progress)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[cfg(feature = "window")]
+	#[test]
+	fn test_primary_window_progress() {
+		use bevy_ecs::system::RunSystemOnce;
+
+		let mut world = World::new();
+		world.spawn((bevy_window::Window::default(), bevy_window::PrimaryWindow));
+		let progress = world.run_system_once(primary_window_progress).unwrap();
+		assert_eq!(progress, Progress::ZERO);
+	}
+
+	#[cfg(feature = "audio")]
+	#[test]
+	fn test_audio_output_progress() {
+		use bevy_diagnostic::FrameCount;
+
+		let mut world = World::new();
+		let checker = EngineReady::AudioOutput.register_progress_checker(&mut world);
+		// Without `AudioPlugin`, there is no audio output
+		assert!(!world.run_system(checker).unwrap().is_finite());
+
+		let mut world = World::new();
+		world.init_resource::<bevy_asset::Assets<bevy_audio::Pitch>>();
+		world.init_resource::<FrameCount>();
+		let checker = EngineReady::AudioOutput.register_progress_checker(&mut world);
+		assert_eq!(world.run_system(checker).unwrap(), Progress::ZERO);
+		let mut probes = world.query::<&AudioOutputProbe>();
+		assert_eq!(probes.iter(&world).count(), 1);
+
+		// Nothing plays the probe, as if `AudioPlugin` had no output stream
+		world.resource_mut::<FrameCount>().0 += 1;
+		assert_eq!(world.run_system(checker).unwrap(), Progress::ZERO);
+		world.resource_mut::<FrameCount>().0 += 1;
+		assert!(!world.run_system(checker).unwrap().is_finite());
+		assert_eq!(probes.iter(&world).count(), 0);
+		// The result is kept
+		assert!(!world.run_system(checker).unwrap().is_finite());
+	}
+
+	#[cfg(feature = "render")]
+	#[test]
+	fn test_render_device_progress() {
+		let mut world = World::new();
+		let checker = EngineReady::RenderDevice.register_progress_checker(&mut world);
+		assert_eq!(world.run_system(checker).unwrap(), Progress::ZERO);
+	}
+}
@@ -0,0 +1,308 @@
+//! ECS-native representation of the setup graph.
+//!
+//! Every setup key and provider tracked by a [`SetupTracker`] is mirrored as an entity, so setup
+//! state can be inspected with ordinary queries, change detection, and entity inspectors. The
+//! [`SetupTracker`] resource remains the source of truth; these entities are kept in sync with it
+//! as providers are registered or removed, and as [`advance_setup`](crate::advance_setup) runs.
+//!
+//! With the `reflect` feature, these components implement `Reflect`, so tools like
+//! bevy-inspector-egui and `bevy_remote` can introspect setup state. [`SetupTrackingPlugin`]
+//! registers the non-generic types. The generic ones can only be reflected if the key type
+//! implements `Reflect`, so they must be registered manually, e.g.
+//! `app.register_type::<SetupKeyState<MyKey>>()`.
+//!
+//! [`SetupTrackingPlugin`]: crate::SetupTrackingPlugin
+
+use crate::{Progress, SetupKey, SetupTracker};
+use bevy_ecs::{name::Name, prelude::*, system::SystemId};
+#[cfg(feature = "reflect")]
+use bevy_reflect::{Reflect, std_traits::ReflectDefault};
+use std::borrow::Cow;
+use std::time::Duration;
+
+/// The state of a setup key, attached to the entity representing that key.
+///
+/// `progress` and `failed` are updated by [`advance_setup`](crate::advance_setup) each time it
+/// runs, and are only written when they change, so `Changed<SetupKeyState<K>>` can be used to
+/// react to progress.
+#[derive(Component, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(Reflect), reflect(Component, PartialEq))]
+pub struct SetupKeyState<K: SetupKey> {
+	/// The key this entity represents.
+	pub key: K,
+	/// The key's progress as of the last time its progress checker ran.
+	pub progress: Progress,
+	/// Whether the key has [failed](SetupTracker::is_failed).
+	pub failed: bool,
+}
+
+/// Identifies the entity representing a setup provider.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct SetupProvider {
+	/// The provider's system.
+	pub system: SystemId,
+}
+
+/// The setup keys a provider provides, attached to the entity representing that provider.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "reflect", derive(Reflect), reflect(Component, PartialEq))]
+pub struct ProviderOf<K: SetupKey>(pub Vec<K>);
+
+/// The setup keys a provider requires, attached to the entity representing that provider.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "reflect", derive(Reflect), reflect(Component, PartialEq))]
+pub struct Requires<K: SetupKey>(pub Vec<K>);
+
+/// The metadata of a provider, attached to the entity representing that provider alongside its
+/// [`Name`].
+#[derive(Component, Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(
+	feature = "reflect",
+	derive(Reflect),
+	reflect(Component, Debug, PartialEq, Default)
+)]
+pub struct ProviderMetadata {
+	/// See [`ProviderInfo::description`](crate::ProviderInfo::description).
+	pub description: Option<Cow<'static, str>>,
+	/// See [`ProviderInfo::tags`](crate::ProviderInfo::tags).
+	pub tags: Vec<Cow<'static, str>>,
+	/// See [`ProviderInfo::timeout`](crate::ProviderInfo::timeout).
+	pub timeout: Option<Duration>,
+}
+
+/// A summary of a whole [`SetupTracker`], attached to an entity representing the tracker.
+///
+/// Updated by [`advance_setup`](crate::advance_setup) each time it runs, and only written when it
+/// changes. See [`SetupTracker::status_entity`].
+#[derive(Component, Debug, Clone, PartialEq, Default)]
+#[cfg_attr(
+	feature = "reflect",
+	derive(Reflect),
+	reflect(Component, Debug, PartialEq, Default)
+)]
+pub struct SetupStatus {
+	/// See [`SetupTracker::last_progress`].
+	pub progress: Progress,
+	/// Whether setup has finished.
+	pub finished: bool,
+	/// See [`SetupTracker::current_stage`].
+	pub current_stage: usize,
+	/// The number of tracked setup keys.
+	pub keys: usize,
+	/// The number of registered providers.
+	pub providers: usize,
+	/// The number of keys that have [failed](SetupTracker::is_failed).
+	pub failed: usize,
+}
+
+impl<K: SetupKey> SetupTracker<K> {
+	/// Returns the entity representing `key`, if it is tracked.
+	pub fn key_entity(&self, key: &K) -> Option<Entity> {
+		self.key_entities.get(key).copied()
+	}
+
+	/// Returns the entity representing the provider registered with `system`, if any.
+	pub fn provider_entity(&self, system: SystemId) -> Option<Entity> {
+		self.provider_entities.get(&system).copied()
+	}
+
+	/// Returns the entity holding this tracker's [`SetupStatus`], if
+	/// [`advance_setup`](crate::advance_setup) has run yet.
+	pub fn status_entity(&self) -> Option<Entity> {
+		self.status_entity
+	}
+
+	/// Spawns or updates the entity holding this tracker's [`SetupStatus`], only touching it if
+	/// something changed.
+	pub(crate) fn sync_status_entity(&mut self, world: &mut World) {
+		let status = SetupStatus {
+			progress: self.last_progress,
+			finished: self.last_progress.finished(),
+			current_stage: self.current_stage,
+			keys: self.entries.len(),
+			providers: self.providers.len(),
+			failed: self.failed.len(),
+		};
+		let existing = self
+			.status_entity
+			.and_then(|entity| world.get_mut::<SetupStatus>(entity));
+		match existing {
+			Some(mut existing) => {
+				if *existing != status {
+					*existing = status;
+				}
+			}
+			None => {
+				let name = format!("SetupTracker<{}>", disqualified::ShortName::of::<K>());
+				self.status_entity = Some(world.spawn((Name::new(name), status)).id());
+			}
+		}
+	}
+
+	/// Spawns an entity for `key` if it doesn't have one yet.
+	pub(crate) fn spawn_key_entity(&mut self, key: &K, world: &mut World) {
+		if self.key_entities.contains_key(key) {
+			return;
+		}
+		let entity = world
+			.spawn(SetupKeyState {
+				key: key.clone(),
+				progress: Progress::ZERO,
+				failed: false,
+			})
+			.id();
+		self.key_entities.insert(key.clone(), entity);
+	}
+
+	/// Spawns or updates the entity for the provider registered with `system`.
+	pub(crate) fn sync_provider_entity(&mut self, system: SystemId, world: &mut World) {
+		let info = &self.providers[&system];
+		let components = (
+			Name::new(info.name().to_owned()),
+			SetupProvider { system },
+			ProviderMetadata {
+				description: info.description.clone(),
+				tags: info.tags.clone(),
+				timeout: info.timeout,
+			},
+			ProviderOf(info.provides().to_vec()),
+			Requires(info.requires().to_vec()),
+		);
+		match self
+			.provider_entities
+			.get(&system)
+			.and_then(|entity| world.get_entity_mut(*entity).ok())
+		{
+			Some(mut entity) => {
+				entity.insert(components);
+			}
+			None => {
+				let entity = world.spawn(components).id();
+				self.provider_entities.insert(system, entity);
+			}
+		}
+	}
+
+	/// Despawns the entity for `key`, if any.
+	pub(crate) fn despawn_key_entity(&mut self, key: &K, world: &mut World) {
+		if let Some(entity) = self.key_entities.remove(key) {
+			world.try_despawn(entity).ok();
+		}
+	}
+
+	/// Despawns the entity for the provider registered with `system`, if any.
+	pub(crate) fn despawn_provider_entity(&mut self, system: SystemId, world: &mut World) {
+		if let Some(entity) = self.provider_entities.remove(&system) {
+			world.try_despawn(entity).ok();
+		}
+	}
+
+	/// Updates the [`SetupKeyState`] of `key`'s entity, only touching it if something changed.
+	pub(crate) fn update_key_state(&self, key: &K, progress: Progress, world: &mut World) {
+		let failed = self.failed.contains(key);
+		let Some(mut state) = self
+			.key_entities
+			.get(key)
+			.and_then(|entity| world.get_mut::<SetupKeyState<K>>(*entity))
+		else {
+			return;
+		};
+		if state.progress != progress || state.failed != failed {
+			state.progress = progress;
+			state.failed = failed;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ProviderInfo, test_utils::TestSetupKey};
+	use std::borrow::Cow;
+
+	#[test]
+	fn test_entities_mirror_tracker() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+
+		let system = world.register_system(|| {});
+		tracker.register_provider(
+			system,
+			ProviderInfo::new(
+				vec![TestSetupKey::A],
+				vec![TestSetupKey::B],
+				Cow::Borrowed("provider_b"),
+			),
+			&mut world,
+		);
+
+		let provider = tracker.provider_entity(system).unwrap();
+		assert_eq!(
+			world.get::<ProviderOf<TestSetupKey>>(provider).unwrap().0,
+			vec![TestSetupKey::B]
+		);
+		assert_eq!(
+			world.get::<Requires<TestSetupKey>>(provider).unwrap().0,
+			vec![TestSetupKey::A]
+		);
+		assert_eq!(world.get::<Name>(provider).unwrap().as_str(), "provider_b");
+
+		let mut keys = world.query::<&SetupKeyState<TestSetupKey>>();
+		assert_eq!(keys.iter(&world).count(), 2);
+
+		let key_a = tracker.key_entity(&TestSetupKey::A).unwrap();
+		tracker.mark_failed(TestSetupKey::A);
+		tracker.update_key_state(&TestSetupKey::A, Progress::ZERO, &mut world);
+		assert!(
+			world
+				.get::<SetupKeyState<TestSetupKey>>(key_a)
+				.unwrap()
+				.failed
+		);
+
+		tracker.remove_provider(system, &mut world);
+		assert!(world.get_entity(provider).is_err());
+		assert!(world.get_entity(key_a).is_err());
+		assert_eq!(keys.iter(&world).count(), 0);
+	}
+
+	#[test]
+	fn test_status_entity() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		let system = world.register_system(|| {});
+		tracker.register_provider(
+			system,
+			ProviderInfo {
+				description: Some(Cow::Borrowed("Provides A")),
+				timeout: Some(Duration::from_secs(1)),
+				..ProviderInfo::new(vec![], vec![TestSetupKey::A], Cow::Borrowed("provider_a"))
+			},
+			&mut world,
+		);
+		let provider = tracker.provider_entity(system).unwrap();
+		let metadata = world.get::<ProviderMetadata>(provider).unwrap();
+		assert_eq!(metadata.description.as_deref(), Some("Provides A"));
+		assert_eq!(metadata.timeout, Some(Duration::from_secs(1)));
+		world.insert_resource(tracker);
+
+		crate::advance_setup::<TestSetupKey>(&mut world);
+		let tracker = world.resource::<SetupTracker<TestSetupKey>>();
+		let entity = tracker.status_entity().unwrap();
+		let status = world.get::<SetupStatus>(entity).unwrap();
+		assert_eq!(status.keys, 1);
+		assert_eq!(status.providers, 1);
+		assert!(!status.finished);
+
+		#[cfg(feature = "reflect")]
+		{
+			use bevy_reflect::{GetField, Struct};
+			let reflected: &dyn Struct = status;
+			assert_eq!(reflected.get_field::<usize>("keys"), Some(&1));
+			assert_eq!(
+				reflected.get_field::<Progress>("progress"),
+				Some(&Progress::ZERO)
+			);
+		}
+	}
+}
@@ -0,0 +1,237 @@
+use crate::{GraphLimits, Progress, SetupKey};
+use std::fmt::{Debug, Formatter};
+
+/// Error type for invalid setup graph configurations.
+///
+/// Contains every problem found while validating the graph. The `Display` implementation prints
+/// a multi-line, human-readable report of all of them.
+#[derive(Debug, Clone)]
+pub struct InvalidSetupGraph<K: SetupKey> {
+	pub errors: Vec<SetupGraphError<K>>,
+}
+
+impl<K: SetupKey + Debug> std::fmt::Display for InvalidSetupGraph<K> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let n = self.errors.len();
+		write!(
+			f,
+			"invalid setup graph ({n} error{}):",
+			if n == 1 { "" } else { "s" }
+		)?;
+		for error in &self.errors {
+			write!(f, "\n  - ")?;
+			// Indent continuation lines to line up under the bullet
+			let msg = error.to_string().replace('\n', "\n    ");
+			f.write_str(&msg)?;
+		}
+		Ok(())
+	}
+}
+
+impl<K: SetupKey + Debug> std::error::Error for InvalidSetupGraph<K> {}
+
+/// A single problem found while validating a setup graph.
+#[derive(Debug, Clone)]
+pub enum SetupGraphError<K: SetupKey> {
+	/// A key is required by some providers, but no provider provides it.
+	UnprovidedKey {
+		key: K,
+		/// Names of the providers that require `key`.
+		required_by: Vec<String>,
+	},
+	/// A key is provided by more than one provider.
+	DuplicateProviders {
+		key: K,
+		/// Names of the providers that provide `key`.
+		providers: Vec<String>,
+	},
+	/// A chain of keys that (transitively) depend on themselves.
+	Cycle(DependencyCycle<K>),
+	/// The graph exceeds its configured [`GraphLimits`], so no other checks were performed.
+	TooLarge(GraphTooLarge),
+}
+
+impl<K: SetupKey + Debug> std::fmt::Display for SetupGraphError<K> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::UnprovidedKey { key, required_by } => {
+				write!(f, "{key:?} is required but never provided")?;
+				if !required_by.is_empty() {
+					write!(f, "\nrequired by: {}", required_by.join(", "))?;
+				}
+				Ok(())
+			}
+			Self::DuplicateProviders { key, providers } => {
+				write!(
+					f,
+					"{key:?} is provided by {} providers\nprovided by: {}",
+					providers.len(),
+					providers.join(", ")
+				)
+			}
+			Self::Cycle(cycle) => write!(f, "dependency cycle: {cycle}"),
+			Self::TooLarge(too_large) => std::fmt::Display::fmt(too_large, f),
+		}
+	}
+}
+
+impl<K: SetupKey + Debug> std::error::Error for SetupGraphError<K> {}
+
+/// A suspicious, but not necessarily invalid, setup graph configuration.
+///
+/// Returned by [`SetupTracker::lint`](crate::SetupTracker::lint).
+#[derive(Debug, Clone)]
+pub enum SetupGraphWarning<K: SetupKey> {
+	/// A key is provided but never required, and is not marked as a terminal output.
+	UnusedProvision {
+		key: K,
+		/// Names of the providers that provide `key`.
+		provided_by: Vec<String>,
+	},
+	/// A provider requires keys that it also provides, so it can never run.
+	SelfDependency {
+		/// Name of the provider.
+		provider: String,
+		/// Keys that the provider both requires and provides.
+		keys: Vec<K>,
+	},
+}
+
+impl<K: SetupKey + Debug> std::fmt::Display for SetupGraphWarning<K> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::UnusedProvision { key, provided_by } => {
+				write!(
+					f,
+					"{key:?} is provided but never required, and is not marked terminal\n\
+					provided by: {}",
+					provided_by.join(", ")
+				)
+			}
+			Self::SelfDependency { provider, keys } => {
+				write!(f, "{provider} requires keys it also provides: {keys:?}")
+			}
+		}
+	}
+}
+
+/// A cycle of setup keys that (transitively) depend on themselves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyCycle<K: SetupKey> {
+	/// The keys in the cycle, in dependency order. Each key depends on the next one, and the last
+	/// key depends on the first.
+	pub path: Vec<K>,
+	/// The names of the providers along each edge of the cycle. `via[i]` is the provider that
+	/// provides `path[i]` and requires the next key in `path`.
+	pub via: Vec<String>,
+}
+
+impl<K: SetupKey + Debug> std::fmt::Display for DependencyCycle<K> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		for (key, via) in self.path.iter().zip(&self.via) {
+			write!(f, "{key:?} -[{via}]-> ")?;
+		}
+		if let Some(first) = self.path.first() {
+			write!(f, "{first:?}")?;
+		}
+		Ok(())
+	}
+}
+
+/// Error returned when a setup graph exceeds its [`GraphLimits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphTooLarge {
+	pub keys: usize,
+	pub edges: usize,
+	pub limits: GraphLimits,
+}
+
+impl std::fmt::Display for GraphTooLarge {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"setup graph is too large: {} keys (limit {}), {} edges (limit {}). \
+			Consider replacing fine-grained keys (e.g. one per asset) with coarser, hierarchical \
+			keys whose progress checkers aggregate the work, or splitting setup into sub-barriers \
+			tracked by separate key types. If the graph really is this large, raise the limits with \
+			`SetupTracker::set_limits`.",
+			self.keys, self.limits.max_keys, self.edges, self.limits.max_edges,
+		)
+	}
+}
+
+impl std::error::Error for GraphTooLarge {}
+
+/// Error returned by [`SetupTracker::run_to_completion`](crate::SetupTracker::run_to_completion)
+/// when setup doesn't finish.
+#[derive(Debug, Clone)]
+pub enum SetupIncomplete<K: SetupKey> {
+	/// Setup didn't finish before the timeout.
+	TimedOut {
+		/// Overall progress when the timeout elapsed.
+		progress: Progress,
+		/// Keys that weren't finished, in registration order.
+		pending: Vec<K>,
+	},
+	/// Setup can't finish until the app updates, because it is waiting for
+	/// [scheduled providers](crate::Provider::in_schedule) or
+	/// [reported](crate::ProgressChecker::Reported) keys.
+	NeedsSchedule {
+		/// Overall progress when setup stopped making progress.
+		progress: Progress,
+		/// Keys that weren't finished, in registration order.
+		pending: Vec<K>,
+	},
+	/// Setup was [cancelled](crate::cancel_setup).
+	Cancelled,
+}
+
+impl<K: SetupKey + Debug> std::fmt::Display for SetupIncomplete<K> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::TimedOut { progress, pending } => write!(
+				f,
+				"setup timed out at {progress} with {} pending keys: {pending:?}",
+				pending.len()
+			),
+			Self::NeedsSchedule { progress, pending } => write!(
+				f,
+				"setup needs the app to update at {progress} with {} pending keys: {pending:?}",
+				pending.len()
+			),
+			Self::Cancelled => f.write_str("setup was cancelled"),
+		}
+	}
+}
+
+impl<K: SetupKey + Debug> std::error::Error for SetupIncomplete<K> {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::DoneSetupKey;
+
+	#[test]
+	fn test_invalid_setup_graph_display() {
+		let error = InvalidSetupGraph {
+			errors: vec![
+				SetupGraphError::UnprovidedKey {
+					key: DoneSetupKey::A,
+					required_by: vec!["provider_b".into()],
+				},
+				SetupGraphError::Cycle(DependencyCycle {
+					path: vec![DoneSetupKey::A, DoneSetupKey::B],
+					via: vec!["provider_a".into(), "provider_b".into()],
+				}),
+			],
+		};
+
+		assert_eq!(
+			error.to_string(),
+			"invalid setup graph (2 errors):\n  \
+			- A is required but never provided\n    \
+			required by: provider_b\n  \
+			- dependency cycle: A -[provider_a]-> B -[provider_b]-> A"
+		);
+	}
+}
@@ -0,0 +1,408 @@
+//! Text exports of the setup dependency graph and its execution, for pasting into documents and
+//! issues or opening in other tools.
+
+use crate::{SetupKey, SetupTracker};
+use bevy_ecs::system::{Res, SystemId};
+use bevy_log::info;
+use bevy_platform::collections::HashSet;
+use bevy_platform::time::Instant;
+use disqualified::ShortName;
+use std::borrow::Cow;
+use std::fmt::{Debug, Write};
+
+impl<K: SetupKey + Debug> SetupTracker<K> {
+	/// Renders the dependency graph as a [Mermaid](https://mermaid.js.org) flowchart, which can be
+	/// pasted into a ```` ```mermaid ```` block in Markdown.
+	///
+	/// Each provider is a node, labeled with its name. Edges point from each provider to the
+	/// providers that depend on it, labeled with the [key label](Self::key_label). Keys that are
+	/// required but never provided are shown as separate, rounded nodes.
+	pub fn to_mermaid(&self) -> String {
+		let mut out = String::from("flowchart LR\n");
+		for (i, info) in self.providers.values().enumerate() {
+			writeln!(out, "    p{i}[\"{}\"]", escape_mermaid(info.name())).unwrap();
+		}
+		for (i, label) in self.unprovided_keys() {
+			writeln!(out, "    k{i}([\"{}\"])", escape_mermaid(&label)).unwrap();
+		}
+		for (from, label, to) in self.export_edges() {
+			let label = escape_mermaid(&label);
+			writeln!(out, "    {from} -->|\"{label}\"| p{to}").unwrap();
+		}
+		out
+	}
+
+	/// Renders the dependency graph in the [DOT](https://graphviz.org/doc/info/lang.html) language,
+	/// which can be rendered with Graphviz.
+	///
+	/// The graph has the same nodes and edges as [`to_mermaid`](Self::to_mermaid): providers are
+	/// boxes, and keys that are required but never provided are ellipses.
+	pub fn to_dot(&self) -> String {
+		let mut out = String::from("digraph setup {\n    rankdir=LR;\n");
+		for (i, info) in self.providers.values().enumerate() {
+			writeln!(
+				out,
+				"    p{i} [label={}, shape=box];",
+				dot_string(info.name())
+			)
+			.unwrap();
+		}
+		for (i, label) in self.unprovided_keys() {
+			writeln!(
+				out,
+				"    k{i} [label={}, shape=ellipse];",
+				dot_string(&label)
+			)
+			.unwrap();
+		}
+		for (from, label, to) in self.export_edges() {
+			writeln!(out, "    {from} -> p{to} [label={}];", dot_string(&label)).unwrap();
+		}
+		out.push_str("}\n");
+		out
+	}
+
+	/// The index and label of each key that is required but never provided, which exports show
+	/// as separate nodes named `k{index}`.
+	fn unprovided_keys(&self) -> impl Iterator<Item = (usize, Cow<'static, str>)> + '_ {
+		self.entries
+			.keys()
+			.enumerate()
+			.filter(|(_, key)| self.providers_of(key).next().is_none())
+			.map(|(i, key)| (i, self.key_label(key)))
+	}
+
+	/// The edges of the exported graph, as the node each edge starts from, the label of the key
+	/// it stands for, and the index of the dependant provider it points to.
+	///
+	/// Edges point from each provider of a key to the providers that depend on it, or from the
+	/// key's own node if nothing provides it.
+	fn export_edges(&self) -> Vec<(String, Cow<'static, str>, usize)> {
+		let index = |system: SystemId| self.providers.get_index_of(&system).unwrap();
+		let mut edges = Vec::new();
+		for (i, key) in self.entries.keys().enumerate() {
+			let label = self.key_label(key);
+			let from = self
+				.providers_of(key)
+				.map(|(system, _)| format!("p{}", index(system)))
+				.collect::<Vec<_>>();
+			let from = if from.is_empty() {
+				vec![format!("k{i}")]
+			} else {
+				from
+			};
+			for (dependant, _) in self.dependants_of(key) {
+				for from in &from {
+					edges.push((from.clone(), label.clone(), index(dependant)));
+				}
+			}
+		}
+		edges
+	}
+
+	/// Renders setup execution as Chrome trace event JSON, which can be opened in
+	/// `chrome://tracing` or [Perfetto](https://ui.perfetto.dev).
+	///
+	/// Each stage is shown as a thread. Each provider is a span from when it first ran until all of
+	/// its keys finished (or now, if they haven't), and keys finishing are shown as instant events.
+	/// If [`set_record_trace`](Self::set_record_trace) was enabled, every individual provider run
+	/// is nested inside its provider's span as well.
+	pub fn to_chrome_trace(&self) -> String {
+		let mut events = Vec::new();
+		let Some(origin) = self.started.values().copied().min() else {
+			return "[]".into();
+		};
+		let now = Instant::now();
+		let micros = |instant: Instant| instant.duration_since(origin).as_micros();
+		let stages = self.stage_indices();
+		// Thread 0 holds providers that can never run
+		let tid = |system: &SystemId| stages.get(system).map_or(0, |stage| stage + 1);
+
+		let stage_count = stages.values().max().map_or(0, |max| max + 1);
+		for stage in 0..stage_count {
+			events.push(format!(
+				r#"{{"name":"thread_name","ph":"M","pid":1,"tid":{},"args":{{"name":"Stage {stage}"}}}}"#,
+				stage + 1,
+			));
+		}
+
+		for (system, info) in self.providers.iter() {
+			let Some(start) = self.started.get(system).copied() else {
+				continue;
+			};
+			let end = info
+				.provides()
+				.iter()
+				.map(|key| self.completed.get(key).copied())
+				.collect::<Option<Vec<_>>>()
+				.and_then(|ends| ends.into_iter().max())
+				.unwrap_or(now)
+				.max(start);
+			events.push(format!(
+				r#"{{"name":{},"cat":"provider","ph":"X","ts":{},"dur":{},"pid":1,"tid":{}}}"#,
+				json_string(info.name()),
+				micros(start),
+				end.duration_since(start).as_micros(),
+				tid(system),
+			));
+			for key in info.provides() {
+				if let Some(completed) = self.completed.get(key) {
+					events.push(format!(
+						r#"{{"name":{},"cat":"key","ph":"i","s":"t","ts":{},"pid":1,"tid":{}}}"#,
+						json_string(&self.key_label(key)),
+						micros(*completed),
+						tid(system),
+					));
+				}
+			}
+		}
+
+		for run in self.trace.iter().flatten() {
+			let Some(info) = self.providers.get(&run.system) else {
+				continue;
+			};
+			events.push(format!(
+				r#"{{"name":{},"cat":"run","ph":"X","ts":{},"dur":{},"pid":1,"tid":{}}}"#,
+				json_string(info.name()),
+				micros(run.start),
+				run.duration.as_micros(),
+				tid(&run.system),
+			));
+		}
+
+		format!("[\n{}\n]\n", events.join(",\n"))
+	}
+
+	/// Renders the dependency graph as an indented tree, like `cargo tree`.
+	///
+	/// Each root is a key nothing depends on, and each key's children are the keys its providers
+	/// require. Lines show the [key label](Self::key_label), its progress as of the last time
+	/// [`advance_setup`](crate::advance_setup) ran, and the names of its providers. Keys that were
+	/// already expanded are marked with `(*)` instead of repeating their dependencies.
+	pub fn format_tree(&self) -> String {
+		let mut out = String::new();
+		let mut expanded = HashSet::new();
+		for key in self.entries.keys() {
+			if self.dependants_of(key).next().is_none() {
+				self.format_subtree(key, "", None, &mut expanded, &mut out);
+			}
+		}
+		out
+	}
+
+	fn format_subtree(
+		&self,
+		key: &K,
+		prefix: &str,
+		last: Option<bool>,
+		expanded: &mut HashSet<K>,
+		out: &mut String,
+	) {
+		let branch = match last {
+			None => "",
+			Some(false) => "├── ",
+			Some(true) => "└── ",
+		};
+		let progress = match self.last_key_progress(key) {
+			None => "?".to_owned(),
+			Some(progress) if !progress.is_finite() || self.is_failed(key) => "failed".to_owned(),
+			Some(progress) => format!("{progress:.0}"),
+		};
+		write!(out, "{prefix}{branch}{} ({progress})", self.key_label(key)).unwrap();
+		let providers = self
+			.providers_of(key)
+			.map(|(system, _)| self.providers[&system].name())
+			.collect::<Vec<_>>();
+		if providers.is_empty() {
+			out.push_str(" [unprovided]");
+		} else {
+			write!(out, " [{}]", providers.join(", ")).unwrap();
+		}
+
+		let requirements = self
+			.providers_of(key)
+			.flat_map(|(system, _)| self.providers[&system].requires())
+			.fold(Vec::new(), |mut requirements, requirement| {
+				if !requirements.contains(&requirement) {
+					requirements.push(requirement);
+				}
+				requirements
+			});
+		if !requirements.is_empty() && !expanded.insert(key.clone()) {
+			out.push_str(" (*)\n");
+			return;
+		}
+		out.push('\n');
+
+		let prefix = match last {
+			None => prefix.to_owned(),
+			Some(false) => format!("{prefix}│   "),
+			Some(true) => format!("{prefix}    "),
+		};
+		for (i, requirement) in requirements.iter().enumerate() {
+			let last = i + 1 == requirements.len();
+			self.format_subtree(requirement, &prefix, Some(last), expanded, out);
+		}
+	}
+}
+
+/// System that logs the [dependency tree](SetupTracker::format_tree) of the `SetupTracker<K>`.
+pub fn print_setup_tree<K: SetupKey + Debug>(tracker: Res<SetupTracker<K>>) {
+	info!("{}\n{}", ShortName::of::<K>(), tracker.format_tree());
+}
+
+/// Quotes and escapes `s` as a JSON string.
+fn json_string(s: &str) -> String {
+	let mut out = String::with_capacity(s.len() + 2);
+	out.push('"');
+	for c in s.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+			c => out.push(c),
+		}
+	}
+	out.push('"');
+	out
+}
+
+/// Quotes `s` as a DOT string.
+fn dot_string(s: &str) -> String {
+	format!(
+		"\"{}\"",
+		s.replace('\\', "\\\\")
+			.replace('"', "\\\"")
+			.replace('\n', "\\n")
+	)
+}
+
+/// Replaces characters that would end a quoted Mermaid label.
+fn escape_mermaid(label: &str) -> String {
+	label.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{Progress, ProviderInfo, SetupKey, SetupTracker, advance_setup};
+	use bevy_ecs::prelude::*;
+	use bevy_ecs::system::SystemId;
+	use std::borrow::Cow;
+
+	#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+	enum TestSetupKey {
+		A,
+		B,
+		C,
+	}
+
+	#[derive(Resource)]
+	struct CDone;
+
+	impl SetupKey for TestSetupKey {
+		fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+			match self {
+				TestSetupKey::C => {
+					world.register_system(|done: Option<Res<CDone>>| done.is_some().into())
+				}
+				_ => world.register_system(|| Progress::DONE),
+			}
+		}
+	}
+
+	#[test]
+	fn test_to_mermaid() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(vec![], vec![TestSetupKey::A], Cow::Borrowed("load \"a\"")),
+			&mut world,
+		);
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(
+				vec![TestSetupKey::A, TestSetupKey::C],
+				vec![TestSetupKey::B],
+				Cow::Borrowed("load_b"),
+			),
+			&mut world,
+		);
+
+		assert_eq!(
+			tracker.to_mermaid(),
+			"flowchart LR\n\
+			\x20   p0[\"load #quot;a#quot;\"]\n\
+			\x20   p1[\"load_b\"]\n\
+			\x20   k1([\"C\"])\n\
+			\x20   p0 -->|\"A\"| p1\n\
+			\x20   k1 -->|\"C\"| p1\n"
+		);
+		assert_eq!(
+			tracker.to_dot(),
+			"digraph setup {\n\
+			\x20   rankdir=LR;\n\
+			\x20   p0 [label=\"load \\\"a\\\"\", shape=box];\n\
+			\x20   p1 [label=\"load_b\", shape=box];\n\
+			\x20   k1 [label=\"C\", shape=ellipse];\n\
+			\x20   p0 -> p1 [label=\"A\"];\n\
+			\x20   k1 -> p1 [label=\"C\"];\n\
+			}\n"
+		);
+	}
+
+	#[test]
+	fn test_format_tree() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(vec![], vec![TestSetupKey::A], Cow::Borrowed("load_a")),
+			&mut world,
+		);
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(
+				vec![TestSetupKey::A, TestSetupKey::C],
+				vec![TestSetupKey::B],
+				Cow::Borrowed("load_b"),
+			),
+			&mut world,
+		);
+		tracker
+			.last_key_progress
+			.insert(TestSetupKey::A, Progress::DONE);
+
+		assert_eq!(
+			tracker.format_tree(),
+			"B (?) [load_b]\n\
+			├── A (100%) [load_a]\n\
+			└── C (?) [unprovided]\n"
+		);
+	}
+
+	#[test]
+	fn test_to_chrome_trace() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		assert_eq!(tracker.to_chrome_trace(), "[]");
+
+		tracker.set_record_trace(true);
+		tracker.register_provider(
+			world.register_system(|mut commands: Commands| commands.insert_resource(CDone)),
+			ProviderInfo::new(vec![], vec![TestSetupKey::C], Cow::Borrowed("load_c")),
+			&mut world,
+		);
+		world.insert_resource(tracker);
+		advance_setup::<TestSetupKey>(&mut world);
+
+		let trace = world
+			.resource::<SetupTracker<TestSetupKey>>()
+			.to_chrome_trace();
+		assert!(trace.contains(r#"{"name":"thread_name","ph":"M","pid":1,"tid":1,"#));
+		assert!(trace.contains(r#"{"name":"load_c","cat":"provider","ph":"X","#));
+		assert!(trace.contains(r#"{"name":"load_c","cat":"run","ph":"X","#));
+		assert!(trace.contains(r#"{"name":"C","cat":"key","ph":"i","#));
+	}
+}
@@ -0,0 +1,173 @@
+//! Reading files from disk on the `IoTaskPool`, such as configs and save games, which are the first
+//! step of setup for most games.
+//!
+//! [`read_files`] creates a provider that reads every file in the background, parses their
+//! contents into a resource, and inserts it. Its progress is the fraction of files read, so it is
+//! registered as an [incremental provider](crate::IntoIncrementalProvider):
+//!
+//! ```rust,ignore
+//! #[derive(Resource)]
+//! struct Settings(MySettings);
+//!
+//! app.register_provider(
+//!     read_files(["settings.ron"], |mut files| {
+//!         Ok(Settings(ron::de::from_bytes(&files.remove(0))?))
+//!     })
+//!     .provides_incrementally([MySetupKey::LoadSettings]),
+//! );
+//! ```
+//!
+//! Files are read with `std::fs`, so this doesn't work on wasm32.
+
+use crate::{CancellationToken, CurrentCancellationToken, Progress};
+use bevy_ecs::error::BevyError;
+use bevy_ecs::prelude::*;
+use bevy_log::{debug, error};
+use bevy_tasks::IoTaskPool;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The state of a [`read_files`] provider once it has started reading.
+struct ReadingFiles<T> {
+	total: usize,
+	read: Arc<AtomicUsize>,
+	output: Arc<Mutex<Option<Result<T, BevyError>>>>,
+	/// The final progress, once the output has been taken.
+	finished: Option<Progress>,
+}
+
+/// Creates a provider system that reads the files at `paths` on the `IoTaskPool`, then passes their
+/// contents to `parse`, in the same order, and inserts the resource it returns. See the
+/// [module docs](self).
+///
+/// The system returns the fraction of files read so far, reaching [`Progress::DONE`] once the
+/// resource is inserted. If a file can't be read or `parse` returns an error, the error is logged
+/// and the system returns non-finite progress. The files are only read once, even if the system
+/// runs again after finishing.
+///
+/// Reading stops before the next file when the tracker's
+/// [cancellation token](crate::SetupTracker::cancellation_token) is cancelled.
+pub fn read_files<T, F>(
+	paths: impl IntoIterator<Item = impl Into<PathBuf>>,
+	parse: F,
+) -> impl FnMut(Commands, Option<Res<CurrentCancellationToken>>) -> Progress
+where
+	T: Resource,
+	F: Fn(Vec<Vec<u8>>) -> Result<T, BevyError> + Send + Sync + 'static,
+{
+	let paths = paths.into_iter().map(Into::into).collect::<Vec<PathBuf>>();
+	let parse = Arc::new(parse);
+	let mut reading = None::<ReadingFiles<T>>;
+	move |mut commands, cancellation| {
+		let reading = reading.get_or_insert_with(|| {
+			let cancellation =
+				cancellation.map_or_else(CancellationToken::new, |token| token.0.clone());
+			let read = Arc::new(AtomicUsize::new(0));
+			let output = Arc::new(Mutex::new(None));
+			IoTaskPool::get()
+				.spawn({
+					let paths = paths.clone();
+					let parse = parse.clone();
+					let read = read.clone();
+					let output = output.clone();
+					async move {
+						let mut contents = Vec::with_capacity(paths.len());
+						for path in &paths {
+							if cancellation.is_cancelled() {
+								debug!(
+									"Cancelled reading files for {}",
+									std::any::type_name::<T>()
+								);
+								return;
+							}
+							match std::fs::read(path) {
+								Ok(bytes) => contents.push(bytes),
+								Err(e) => {
+									*output.lock().unwrap() =
+										Some(Err(format!("{}: {e}", path.display()).into()));
+									return;
+								}
+							}
+							read.fetch_add(1, Ordering::Relaxed);
+						}
+						*output.lock().unwrap() = Some(parse(contents));
+					}
+				})
+				.detach();
+			ReadingFiles {
+				total: paths.len(),
+				read,
+				output,
+				finished: None,
+			}
+		});
+		if let Some(result) = reading.output.lock().unwrap().take() {
+			reading.finished = Some(match result {
+				Ok(value) => {
+					commands.insert_resource(value);
+					Progress::DONE
+				}
+				Err(e) => {
+					error!("Failed to read {}: {e}", std::any::type_name::<T>());
+					Progress::new(f32::NAN)
+				}
+			});
+		}
+		reading.finished.unwrap_or_else(|| {
+			// Not done until the resource is inserted
+			let read = reading.read.load(Ordering::Relaxed);
+			Progress::new((*Progress::from_steps(read, reading.total)).min(0.99))
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		IntoIncrementalProvider, RegisterProvider, SetupTracker, advance_setup,
+		test_utils::TestSetupKey,
+	};
+	use bevy_tasks::TaskPool;
+
+	#[derive(Resource, Debug, PartialEq)]
+	struct Config(Vec<String>);
+
+	#[test]
+	fn test_read_files() {
+		IoTaskPool::get_or_init(TaskPool::new);
+		let dir = std::env::temp_dir().join(format!("bird_barrier_files_{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+		std::fs::write(dir.join("a.txt"), "a").unwrap();
+		std::fs::write(dir.join("b.txt"), "b").unwrap();
+
+		let mut world = World::new();
+		let tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		world.insert_resource(tracker);
+		world.register_provider(
+			read_files([dir.join("a.txt"), dir.join("b.txt")], |files| {
+				let files = files.into_iter().map(String::from_utf8);
+				Ok(Config(files.collect::<Result<_, _>>()?))
+			})
+			.provides_incrementally([TestSetupKey::A]),
+		);
+
+		for _ in 0..100 {
+			advance_setup::<TestSetupKey>(&mut world);
+			if world.contains_resource::<Config>() {
+				break;
+			}
+			bevy_tasks::tick_global_task_pools_on_main_thread();
+			std::thread::sleep(std::time::Duration::from_millis(10));
+		}
+		std::fs::remove_dir_all(&dir).unwrap();
+		assert_eq!(
+			world.resource::<Config>(),
+			&Config(vec!["a".into(), "b".into()])
+		);
+		advance_setup::<TestSetupKey>(&mut world);
+		let tracker = world.resource::<SetupTracker<TestSetupKey>>();
+		assert_eq!(tracker.last_progress(), Progress::DONE);
+	}
+}
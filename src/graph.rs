@@ -0,0 +1,74 @@
+//! Export of the setup dependency graph to [`petgraph`], for custom analyses such as dominators,
+//! strongly connected components, or transitive reduction.
+
+use crate::{SetupKey, SetupTracker};
+use bevy_ecs::system::SystemId;
+use petgraph::graph::{DiGraph, NodeIndex};
+
+impl<K: SetupKey> SetupTracker<K> {
+	/// Returns the dependency graph between setup keys as a petgraph [`DiGraph`].
+	///
+	/// Each node is a setup key. For each provider, there is an edge from every key it requires to
+	/// every key it provides, weighted by the provider's `SystemId`. Edges therefore point in the
+	/// direction setup flows, from dependencies to dependants.
+	///
+	/// Nodes are added in the same order as [`entries`](Self::entries), so `NodeIndex::new(i)`
+	/// corresponds to the `i`th entry.
+	pub fn as_graph(&self) -> DiGraph<K, SystemId> {
+		let mut graph = DiGraph::with_capacity(self.entries.len(), self.edge_count());
+		for key in self.entries.keys() {
+			graph.add_node(key.clone());
+		}
+		let node = |key: &K| NodeIndex::new(self.entries.get_index_of(key).unwrap());
+		for (system, info) in self.providers.iter() {
+			for provided in info.provides() {
+				for required in info.requires() {
+					graph.add_edge(node(required), node(provided), *system);
+				}
+			}
+		}
+		graph
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{ProviderInfo, SetupTracker, test_utils::DoneSetupKey};
+	use bevy_ecs::world::World;
+	use petgraph::algo::toposort;
+	use std::borrow::Cow;
+
+	#[test]
+	fn test_as_graph() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<DoneSetupKey>::new(world.register_system(|| {}));
+
+		let system_b = world.register_system(|| {});
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(vec![], vec![DoneSetupKey::A], Cow::Borrowed("provider_a")),
+			&mut world,
+		);
+		tracker.register_provider(
+			system_b,
+			ProviderInfo::new(
+				vec![DoneSetupKey::A],
+				vec![DoneSetupKey::B, DoneSetupKey::C],
+				Cow::Borrowed("provider_b"),
+			),
+			&mut world,
+		);
+
+		let graph = tracker.as_graph();
+		assert_eq!(graph.node_count(), 3);
+		assert_eq!(graph.edge_count(), 2);
+		assert!(graph.edge_weights().all(|id| *id == system_b));
+
+		let order = toposort(&graph, None)
+			.unwrap()
+			.into_iter()
+			.map(|node| graph[node].clone())
+			.collect::<Vec<_>>();
+		assert_eq!(order[0], DoneSetupKey::A);
+	}
+}
@@ -0,0 +1,344 @@
+//! Several independent trackers for the same key type, held by entities.
+//!
+//! The [`SetupTracker`] resource can only drive one setup process per key type. To run the same
+//! setup graph several times concurrently, e.g. on a server that pre-loads the next map while the
+//! current one is still being played, spawn an entity with a [`SetupInstance`] for each, and add
+//! [`advance_setup_instances`] to your schedule:
+//!
+//! ```rust,ignore
+//! let tracker = SetupTracker::<LevelSetup>::new(world.register_system(|| {}));
+//! let next_level = world.spawn((SetupInstance::new(tracker), Level(2))).id();
+//! with_setup_instance::<LevelSetup, _>(world, next_level, |world| {
+//!     world.register_provider(spawn_level_geometry.provides([LevelSetup::Geometry]));
+//! });
+//!
+//! app.add_systems(Update, advance_setup_instances::<LevelSetup>);
+//! ```
+//!
+//! While an instance is being advanced, its tracker is temporarily moved into the
+//! `SetupTracker<K>` resource, so providers, progress checkers, and observers of setup events work
+//! exactly as they do with a single tracker. They can read [`CurrentSetupInstance`] to find out
+//! which instance they're running for. Moving the tracker doesn't count as a change to the
+//! `SetupInstance`, or to a tracker already in the resource, so `Changed<SetupInstance<K>>` only
+//! matches instances whose tracker changed while it was moved.
+//!
+//! An instance's lifetime follows its entity, e.g. the root entity of a level in a streaming open
+//! world: despawning the entity, or removing its `SetupInstance`, [cancels](crate::cancel_setup)
+//! setup and [cleans up](SetupTracker::cleanup) the tracker.
+//!
+//! The [`SetupProgressStore`](crate::SetupProgressStore) is still shared between all instances,
+//! so keys that use [reported progress](crate::ProgressChecker::Reported) can't tell them apart.
+
+use crate::{Provider, SetupKey, SetupTracker, advance_setup, cancel_setup};
+use bevy_ecs::{component::HookContext, prelude::*, system::SystemId, world::DeferredWorld};
+
+/// A [`SetupTracker`] held by an entity instead of a resource. See the [module docs](self).
+///
+/// The component is removed once its tracker is [cleaned up](SetupTracker::cleanup). Removing it
+/// any other way, including by despawning its entity, cancels setup and cleans up the tracker.
+#[derive(Component, Debug)]
+#[component(on_remove = cancel_removed_instance::<K>)]
+pub struct SetupInstance<K: SetupKey> {
+	/// `None` while the tracker is moved into the resource by [`with_setup_instance`]
+	tracker: Option<SetupTracker<K>>,
+}
+
+impl<K: SetupKey> SetupInstance<K> {
+	/// Creates an instance driven by `tracker`.
+	pub fn new(tracker: SetupTracker<K>) -> Self {
+		Self {
+			tracker: Some(tracker),
+		}
+	}
+
+	/// Returns the instance's tracker.
+	///
+	/// # Panics
+	///
+	/// Panics if called from inside [`with_setup_instance`] for this instance. Use the
+	/// `SetupTracker<K>` resource there instead.
+	pub fn tracker(&self) -> &SetupTracker<K> {
+		self.tracker
+			.as_ref()
+			.expect("setup instance is currently being advanced")
+	}
+
+	/// Returns the instance's tracker mutably.
+	///
+	/// # Panics
+	///
+	/// Panics if called from inside [`with_setup_instance`] for this instance. Use the
+	/// `SetupTracker<K>` resource there instead.
+	pub fn tracker_mut(&mut self) -> &mut SetupTracker<K> {
+		self.tracker
+			.as_mut()
+			.expect("setup instance is currently being advanced")
+	}
+}
+
+/// The entity whose [`SetupInstance`] is currently in the `SetupTracker` resource.
+///
+/// Only exists inside [`with_setup_instance`].
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CurrentSetupInstance(pub Entity);
+
+/// Runs `f` with the tracker of `entity`'s [`SetupInstance`] in the `SetupTracker<K>` resource,
+/// e.g. to register providers with it. Any tracker already in the resource is set aside and put
+/// back afterwards.
+///
+/// Returns `None` without running `f` if `entity` has no `SetupInstance<K>`.
+pub fn with_setup_instance<K: SetupKey, R>(
+	world: &mut World,
+	entity: Entity,
+	f: impl FnOnce(&mut World) -> R,
+) -> Option<R> {
+	let tracker = world
+		.get_mut::<SetupInstance<K>>(entity)?
+		.bypass_change_detection()
+		.tracker
+		.take()?;
+	let (result, tracker) = with_tracker(world, entity, tracker, f);
+	match tracker {
+		Some((tracker, changed)) => match world.get_mut::<SetupInstance<K>>(entity) {
+			Some(mut instance) => {
+				instance.bypass_change_detection().tracker = Some(tracker);
+				if changed {
+					instance.set_changed();
+				}
+			}
+			// Removed by `f`, while the hook couldn't see the tracker
+			None => cancel_and_clean_up(world, entity, tracker),
+		},
+		None => {
+			// Cleaned up
+			if let Ok(mut entity) = world.get_entity_mut(entity) {
+				entity.remove::<SetupInstance<K>>();
+			}
+		}
+	}
+	Some(result)
+}
+
+/// Registers `provider` with the tracker of `entity`'s [`SetupInstance`].
+///
+/// Returns `None` if `entity` has no `SetupInstance<K>`.
+pub fn register_instance_provider<K: SetupKey, S: IntoSystem<(), (), M> + 'static, M>(
+	world: &mut World,
+	entity: Entity,
+	provider: Provider<K, S, M>,
+) -> Option<SystemId> {
+	with_setup_instance::<K, _>(world, entity, |world| provider.register(world))
+}
+
+/// Moves `tracker` into the resource while running `f`, and returns it unless it was cleaned up,
+/// along with whether it was changed.
+///
+/// A tracker already in the resource is swapped out rather than removed, and its change ticks are
+/// restored afterwards.
+fn with_tracker<K: SetupKey, R>(
+	world: &mut World,
+	entity: Entity,
+	tracker: SetupTracker<K>,
+	f: impl FnOnce(&mut World) -> R,
+) -> (R, Option<(SetupTracker<K>, bool)>) {
+	let outer_tracker = match world.get_resource_mut::<SetupTracker<K>>() {
+		Some(mut resource) => {
+			let last_changed = resource.last_changed();
+			let outer = std::mem::replace(resource.bypass_change_detection(), tracker);
+			Some((outer, last_changed))
+		}
+		None => {
+			world.insert_resource(tracker);
+			None
+		}
+	};
+	let outer_instance = world.remove_resource::<CurrentSetupInstance>();
+	world.insert_resource(CurrentSetupInstance(entity));
+	// Changes made by `f` get a later tick than the swap
+	world.increment_change_tick();
+	let swapped = world.resource_ref::<SetupTracker<K>>().last_changed();
+
+	let result = f(world);
+
+	world.remove_resource::<CurrentSetupInstance>();
+	let tracker = match (world.get_resource_mut::<SetupTracker<K>>(), outer_tracker) {
+		(Some(mut resource), Some((outer, last_changed))) => {
+			let changed = resource.last_changed() != swapped;
+			let tracker = std::mem::replace(resource.bypass_change_detection(), outer);
+			resource.set_last_changed(last_changed);
+			Some((tracker, changed))
+		}
+		(Some(resource), None) => {
+			let changed = resource.last_changed() != swapped;
+			world
+				.remove_resource::<SetupTracker<K>>()
+				.map(|tracker| (tracker, changed))
+		}
+		// Cleaned up
+		(None, outer) => {
+			if let Some((outer, _)) = outer {
+				world.insert_resource(outer);
+			}
+			None
+		}
+	};
+	if let Some(instance) = outer_instance {
+		world.insert_resource(instance);
+	}
+	(result, tracker)
+}
+
+fn cancel_and_clean_up<K: SetupKey>(world: &mut World, entity: Entity, tracker: SetupTracker<K>) {
+	with_tracker(world, entity, tracker, |world| {
+		cancel_setup::<K>(world);
+		SetupTracker::<K>::cleanup(world);
+	});
+}
+
+fn cancel_removed_instance<K: SetupKey>(mut world: DeferredWorld, context: HookContext) {
+	let entity = context.entity;
+	let Some(tracker) = world
+		.get_mut::<SetupInstance<K>>(entity)
+		.and_then(|mut instance| instance.tracker.take())
+	else {
+		// Cleaned up, or currently in the resource
+		return;
+	};
+	world
+		.commands()
+		.queue(move |world: &mut World| cancel_and_clean_up(world, entity, tracker));
+}
+
+/// Runs [`advance_setup`] for every [`SetupInstance<K>`], one after another.
+pub fn advance_setup_instances<K: SetupKey>(world: &mut World) {
+	let instances = world
+		.query_filtered::<Entity, With<SetupInstance<K>>>()
+		.iter(world)
+		.collect::<Vec<_>>();
+	for entity in instances {
+		with_setup_instance::<K, _>(world, entity, advance_setup::<K>);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{IntoDependencyProvider, Progress, RegisterProvider};
+	use bevy_ecs::system::SystemId;
+
+	#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+	enum LevelSetup {
+		Geometry,
+	}
+
+	impl SetupKey for LevelSetup {
+		fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+			world.register_system(
+				|current: Res<CurrentSetupInstance>, loaded: Query<(), With<Loaded>>| {
+					loaded.contains(current.0).into()
+				},
+			)
+		}
+	}
+
+	#[derive(Component)]
+	struct Loaded;
+
+	#[test]
+	fn test_setup_instances() {
+		let mut world = World::new();
+		let levels = [(); 2].map(|_| {
+			let tracker = SetupTracker::<LevelSetup>::new(world.register_system(|| {}));
+			let level = world.spawn(SetupInstance::new(tracker)).id();
+			with_setup_instance::<LevelSetup, _>(&mut world, level, |world| {
+				world.register_provider(
+					(|current: Res<CurrentSetupInstance>, mut commands: Commands| {
+						commands.entity(current.0).insert(Loaded);
+					})
+					.provides([LevelSetup::Geometry]),
+				);
+			});
+			level
+		});
+		assert!(!world.contains_resource::<SetupTracker<LevelSetup>>());
+
+		advance_setup_instances::<LevelSetup>(&mut world);
+		advance_setup_instances::<LevelSetup>(&mut world);
+		for level in levels {
+			assert!(world.entity(level).contains::<Loaded>());
+			let instance = world.get::<SetupInstance<LevelSetup>>(level).unwrap();
+			assert_eq!(instance.tracker().last_progress(), Progress::DONE);
+		}
+		assert!(!world.contains_resource::<CurrentSetupInstance>());
+
+		// Passes that change nothing don't touch the instances' change ticks
+		let last_changed = |world: &World| {
+			levels.map(|level| {
+				world
+					.entity(level)
+					.get_ref::<SetupInstance<LevelSetup>>()
+					.unwrap()
+					.last_changed()
+			})
+		};
+		let before = last_changed(&world);
+		world.increment_change_tick();
+		advance_setup_instances::<LevelSetup>(&mut world);
+		assert_eq!(last_changed(&world), before);
+	}
+
+	#[test]
+	fn test_outer_tracker_unchanged() {
+		let mut world = World::new();
+		let outer = SetupTracker::<LevelSetup>::new(world.register_system(|| {}));
+		world.insert_resource(outer);
+		let tracker = SetupTracker::<LevelSetup>::new(world.register_system(|| {}));
+		let level = world.spawn(SetupInstance::new(tracker)).id();
+		register_instance_provider(
+			&mut world,
+			level,
+			(|current: Res<CurrentSetupInstance>, mut commands: Commands| {
+				commands.entity(current.0).insert(Loaded);
+			})
+			.provides([LevelSetup::Geometry]),
+		);
+
+		let before = world
+			.resource_ref::<SetupTracker<LevelSetup>>()
+			.last_changed();
+		world.increment_change_tick();
+		advance_setup_instances::<LevelSetup>(&mut world);
+		assert!(world.entity(level).contains::<Loaded>());
+		let outer = world.resource_ref::<SetupTracker<LevelSetup>>();
+		assert_eq!(outer.last_changed(), before);
+		assert!(outer.providers().is_empty());
+	}
+
+	#[test]
+	fn test_despawn_setup_instance() {
+		#[derive(Resource, Default)]
+		struct Cancelled(u32);
+
+		let mut world = World::new();
+		world.init_resource::<Cancelled>();
+		let tracker = SetupTracker::<LevelSetup>::new(world.register_system(|| {}));
+		let level = world.spawn(SetupInstance::new(tracker)).id();
+		let provider = register_instance_provider(
+			&mut world,
+			level,
+			(|current: Res<CurrentSetupInstance>, mut commands: Commands| {
+				commands.entity(current.0).insert(Loaded);
+			})
+			.provides([LevelSetup::Geometry])
+			.on_cancel(|mut cancelled: ResMut<Cancelled>| cancelled.0 += 1),
+		)
+		.unwrap();
+
+		advance_setup_instances::<LevelSetup>(&mut world);
+		advance_setup_instances::<LevelSetup>(&mut world);
+		world.despawn(level);
+		world.flush();
+		assert_eq!(world.resource::<Cancelled>().0, 1);
+		assert!(world.run_system(provider).is_err());
+	}
+}
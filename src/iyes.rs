@@ -0,0 +1,95 @@
+//! Interop with [`iyes_progress`], for migrating between the two crates incrementally.
+//!
+//! Setup progress can be reported to an iyes_progress `ProgressTracker` as one of its entries:
+//!
+//! ```rust,ignore
+//! app.add_systems(
+//!     Update,
+//!     setup_progress_for_iyes::<MySetupKey>
+//!         .track_progress::<MyStates>()
+//!         .after(advance_setup::<MySetupKey>),
+//! );
+//! ```
+//!
+//! And the other way around, iyes_progress' global progress can be used as a key's progress
+//! checker with [`iyes_progress_checker`]. Don't do both with the same state type, or each will
+//! wait for the other forever.
+
+use crate::{Progress, SetupKey, SetupTracker};
+use bevy_ecs::prelude::*;
+use bevy_state::state::FreelyMutableState;
+use iyes_progress::prelude::ProgressTracker;
+
+/// The number of units setup progress is divided into for [`setup_progress_for_iyes`], since
+/// iyes_progress counts whole units of work.
+pub const IYES_PROGRESS_UNITS: u32 = 1000;
+
+/// System that returns the overall progress of the `SetupTracker<K>` as an iyes_progress
+/// `Progress`, to be added with `.track_progress::<S>()`.
+///
+/// Progress is as of the last time [`advance_setup`](crate::advance_setup) ran, divided into
+/// [`IYES_PROGRESS_UNITS`] units. Non-finite progress counts as no progress. Once the tracker is
+/// [cleaned up](SetupTracker::cleanup), setup is reported as complete.
+pub fn setup_progress_for_iyes<K: SetupKey>(
+	tracker: Option<Res<SetupTracker<K>>>,
+) -> iyes_progress::Progress {
+	let progress = tracker.map_or(Progress::DONE, |tracker| tracker.last_progress());
+	let done = if progress.finished() {
+		IYES_PROGRESS_UNITS
+	} else if progress.is_finite() {
+		(*progress * IYES_PROGRESS_UNITS as f32) as u32
+	} else {
+		0
+	};
+	iyes_progress::Progress {
+		done,
+		total: IYES_PROGRESS_UNITS,
+	}
+}
+
+/// Progress checker that returns the global progress of the iyes_progress `ProgressTracker<S>`.
+///
+/// Returns [`Progress::ZERO`] while the tracker doesn't exist, and [`Progress::DONE`] if it has
+/// no work to track.
+pub fn iyes_progress_checker<S: FreelyMutableState>(
+	tracker: Option<Res<ProgressTracker<S>>>,
+) -> Progress {
+	let Some(tracker) = tracker else {
+		return Progress::ZERO;
+	};
+	let progress = tracker.get_global_progress();
+	if progress.total == 0 {
+		Progress::DONE
+	} else {
+		Progress::new(progress.done as f32 / progress.total as f32)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::DoneSetupKey;
+	use bevy_ecs::system::RunSystemOnce;
+
+	#[test]
+	fn test_setup_progress_for_iyes() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<DoneSetupKey>::new(world.register_system(|| {}));
+		tracker.last_progress = Progress::new(0.25);
+		world.insert_resource(tracker);
+
+		let progress = world
+			.run_system_once(setup_progress_for_iyes::<DoneSetupKey>)
+			.unwrap();
+		assert_eq!(progress.done, IYES_PROGRESS_UNITS / 4);
+		assert_eq!(progress.total, IYES_PROGRESS_UNITS);
+
+		world
+			.resource_mut::<SetupTracker<DoneSetupKey>>()
+			.last_progress = Progress::new(f32::NAN);
+		let progress = world
+			.run_system_once(setup_progress_for_iyes::<DoneSetupKey>)
+			.unwrap();
+		assert_eq!(progress.done, 0);
+	}
+}
@@ -27,7 +27,28 @@
 //! ## Features
 //!
 //! - `assets`: Enable asset loading progress tracking helpers
-//! - `reflect`: Enable reflection support for setup keys
+//! - `derive`: Enable `#[derive(AssetCollection)]`
+//! - `debug`: Enable pausing setup and running one provider at a time with `SetupStepper`, and
+//!   pausing when keys finish with `SetupBreakpoints`
+//! - `asset-loader`: Enable loading and tracking `bevy_asset_loader` collections as setup keys
+//! - `download`: Enable tracking downloads and other byte streams via `ByteStreamProgress`
+//! - `files`: Enable reading config and save files on the IO task pool with `read_files`
+//! - `window`: Enable the `EngineReady::PrimaryWindow` engine milestone key
+//! - `audio`: Enable the `EngineReady::AudioOutput` engine milestone key
+//! - `reflect`: Enable reflection support for `Progress` and the setup entity components
+//! - `auto-register`: Enable declaring providers anywhere with `auto_register_provider!`
+//! - `iyes-progress`: Enable reporting setup progress to, and tracking progress from, `iyes_progress`
+//! - `loading-screen`: Enable a ready-made `bevy_ui` loading screen
+//! - `ron`: Enable loading data-driven setup graph definitions from RON, as assets with `assets`
+//! - `scene`: Enable tracking scene spawning via `scene_spawned_progress`
+//! - `serialize`: Enable JSON reports of the tracker state via `SetupTracker::report`, and
+//!   snapshots of progress and completion for sending over the network or restoring on relaunch
+//! - `render`: Enable tracking render pipeline compilation via `pipelines_ready_progress`, and the
+//!   `EngineReady::RenderDevice` key
+//! - `remote`: Enable serving the tracker state over the Bevy Remote Protocol via `setup_status`
+//! - `testing`: Enable test utilities in the `testing` module, such as
+//!   `SetupTestExt::run_until_setup_complete` and random graph generation
+//! - `graph`: Enable exporting the dependency graph to `petgraph` via `SetupTracker::as_graph`
 //! - `visualization`: Enable interactive graph visualization with egui
 //!
 //! ## Quick Start
@@ -83,26 +104,117 @@
 //! }
 //! ```
 
+// Lets derive macros refer to `::bird_barrier` within this crate's own tests
+extern crate self as bird_barrier;
+
 use bevy_ecs::{prelude::*, query::QueryFilter, system::SystemId};
 use bevy_state::{prelude::State, state::States};
+use std::any::TypeId;
+use std::borrow::Cow;
+use std::fmt::Debug;
 use std::hash::Hash;
 
-#[cfg(feature = "assets")]
-use bevy_asset::{AssetServer, UntypedAssetId};
-
+mod cancellation;
+mod chunked;
+mod connection;
+mod entity;
+mod error;
+mod export;
+mod instance;
 mod plugin;
 mod progress;
+mod progress_log;
 mod provider;
+mod reads;
+mod store;
+mod timeline;
 mod tracker;
 
+#[cfg(feature = "asset-loader")]
+mod asset_loader;
+#[cfg(feature = "assets")]
+mod assets;
+#[cfg(feature = "auto-register")]
+mod auto_register;
+#[cfg(feature = "ron")]
+mod data;
+#[cfg(feature = "debug")]
+mod debug;
+#[cfg(feature = "download")]
+mod download;
+#[cfg(any(feature = "audio", feature = "render", feature = "window"))]
+mod engine;
+#[cfg(feature = "files")]
+mod files;
+#[cfg(feature = "graph")]
+mod graph;
+#[cfg(feature = "iyes-progress")]
+mod iyes;
+#[cfg(feature = "loading-screen")]
+mod loading_screen;
+#[cfg(feature = "remote")]
+mod remote;
+#[cfg(feature = "render")]
+mod render;
+#[cfg(feature = "serialize")]
+mod report;
+#[cfg(feature = "scene")]
+mod scene;
+#[cfg(feature = "serialize")]
+mod snapshot;
+#[cfg(test)]
+mod test_utils;
+#[cfg(feature = "testing")]
+pub mod testing;
 #[cfg(feature = "visualization")]
 mod visualization;
 
+pub use cancellation::*;
+pub use chunked::*;
+pub use connection::*;
+pub use entity::*;
+pub use error::*;
+pub use export::*;
+pub use instance::*;
 pub use plugin::*;
 pub use progress::*;
+pub use progress_log::*;
 pub use provider::*;
+pub use reads::*;
+pub use store::*;
+pub use timeline::*;
 pub use tracker::*;
 
+#[cfg(feature = "asset-loader")]
+pub use asset_loader::*;
+#[cfg(feature = "assets")]
+pub use assets::*;
+#[cfg(feature = "auto-register")]
+pub use auto_register::*;
+#[cfg(feature = "ron")]
+pub use data::*;
+#[cfg(feature = "debug")]
+pub use debug::*;
+#[cfg(feature = "download")]
+pub use download::*;
+#[cfg(any(feature = "audio", feature = "render", feature = "window"))]
+pub use engine::*;
+#[cfg(feature = "files")]
+pub use files::*;
+#[cfg(feature = "iyes-progress")]
+pub use iyes::*;
+#[cfg(feature = "loading-screen")]
+pub use loading_screen::*;
+#[cfg(feature = "remote")]
+pub use remote::*;
+#[cfg(feature = "render")]
+pub use render::*;
+#[cfg(feature = "serialize")]
+pub use report::*;
+#[cfg(feature = "scene")]
+pub use scene::*;
+#[cfg(feature = "serialize")]
+pub use snapshot::*;
 #[cfg(feature = "visualization")]
 pub use visualization::*;
 
@@ -117,8 +229,42 @@ pub trait SetupKey: Eq + Hash + Clone + Send + Sync + 'static {
 	///
 	/// This will be called the first time each key appears in a [Provider]'s `requires` or
 	/// `provides` list. The SystemId will be cached and used for any further appearances.
+	///
+	/// The tracker calls it through [`progress_checker`](Self::progress_checker), so keys that
+	/// override that only need this for the keys they don't return another checker for.
 	fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress>;
 
+	/// Returns the progress checker for this setup entry.
+	///
+	/// This is called instead of [`register_progress_checker`](Self::register_progress_checker)
+	/// by the tracker, and by default wraps it in a [`ProgressChecker::Unique`]. Override it to
+	/// return a system that is shared by many keys, e.g. with [`ProgressChecker::cached`] or
+	/// [`ProgressChecker::cached_keyed`], to avoid registering a system for every key of a large,
+	/// data-driven key set. Trivial checks can return [`ProgressChecker::Resource`] or
+	/// [`ProgressChecker::Fn`] to avoid the overhead of running a system at all.
+	fn progress_checker(&self, world: &mut World) -> ProgressChecker<Self> {
+		ProgressChecker::Unique(self.register_progress_checker(world))
+	}
+
+	/// The resources and components this key's progress checker reads, so it only has to be
+	/// re-run when they change. See [`CheckerReads`].
+	///
+	/// Defaults to `None`, which runs the checker every time setup advances.
+	fn checker_reads(&self) -> Option<CheckerReads> {
+		None
+	}
+
+	/// Keys that must always finish before this one can be provided, e.g. building the scene always
+	/// needs the assets to be loaded.
+	///
+	/// These are added to the `requires` list of every provider of this key when it is registered,
+	/// so they are dispatched, staged, and validated exactly like requirements declared on the
+	/// provider. Unlike those, they can't be removed with
+	/// [`SetupTracker::set_requires`](crate::SetupTracker::set_requires). Defaults to none.
+	fn requires(&self) -> Vec<Self> {
+		Vec::new()
+	}
+
 	/// A scale factor to apply to this entry when calculating total progress.
 	///
 	/// This allows you to weight different setup tasks based on their expected duration
@@ -126,16 +272,155 @@ pub trait SetupKey: Eq + Hash + Clone + Send + Sync + 'static {
 	fn relative_time_estimate(&self) -> f32 {
 		1.0
 	}
+
+	/// The group this key belongs to, e.g. "Assets", "World", or "Network", for showing separate
+	/// progress bars with [`SetupTracker::group_progress`].
+	///
+	/// Defaults to `None`.
+	fn group(&self) -> Option<Cow<'static, str>> {
+		None
+	}
+
+	/// Returns a human-readable name for this key, e.g. "Loading configuration…", for use in
+	/// visualization and loading screens.
+	///
+	/// Defaults to the key's `Debug` representation. Validation errors keep using `Debug`, so
+	/// they point at the key as written in code.
+	fn display_name(&self) -> Cow<'static, str>
+	where
+		Self: Debug,
+	{
+		Cow::Owned(format!("{self:?}"))
+	}
 }
 
 /// Type alias for progress checker system IDs.
 pub type ProgressCheckerId = SystemId<(), Progress>;
 
+/// A registered progress checker for a setup key. See [`SetupKey::progress_checker`].
+#[derive(Debug)]
+pub enum ProgressChecker<K: SetupKey> {
+	/// A checker used by a single key.
+	///
+	/// The tracker unregisters it when the key is removed.
+	Unique(ProgressCheckerId),
+	/// A checker shared by many keys, which is passed the key to check.
+	///
+	/// The tracker never unregisters it, since other keys may still use it.
+	Keyed(SystemId<In<K>, Progress>),
+	/// A checker shared by many keys, which isn't passed the key, e.g. one registered with
+	/// [`ProgressChecker::cached`].
+	///
+	/// The tracker never unregisters it, since other keys may still use it.
+	Shared(ProgressCheckerId),
+	/// Looks up progress reported by ordinary systems in the [`SetupProgressStore`], so checking
+	/// progress doesn't block the schedule. See [`ReportProgress`].
+	Reported,
+	/// Checks whether the resource with this `TypeId` exists, without running a system.
+	///
+	/// See [`ProgressChecker::resource`].
+	Resource(TypeId),
+	/// A plain function of the world, which skips system dispatch entirely.
+	///
+	/// Cheaper than a system for trivial checks of large key sets, but it can only read the world,
+	/// and can't use system parameters like `Local` or change detection.
+	Fn(fn(&World) -> Progress),
+}
+
+impl<K: SetupKey> ProgressChecker<K> {
+	/// Runs the checker for `key`.
+	///
+	/// # Panics
+	///
+	/// Panics if the checker's system has been unregistered or fails to run.
+	pub fn run(&self, key: &K, world: &mut World) -> Progress {
+		match self {
+			ProgressChecker::Unique(system) => world.run_system(*system).unwrap(),
+			ProgressChecker::Keyed(system) => world.run_system_with(*system, key.clone()).unwrap(),
+			ProgressChecker::Shared(system) => world.run_system(*system).unwrap(),
+			ProgressChecker::Reported => world
+				.get_resource::<SetupProgressStore<K>>()
+				.and_then(|store| store.get(key))
+				.unwrap_or(Progress::ZERO),
+			ProgressChecker::Resource(type_id) => world
+				.components()
+				.get_resource_id(*type_id)
+				.is_some_and(|id| world.contains_resource_by_id(id))
+				.into(),
+			ProgressChecker::Fn(check) => check(world),
+		}
+	}
+
+	/// Registers `system` with `World::register_system_cached`, so every key using the same
+	/// checker function shares one system.
+	///
+	/// Prefer this over registering a new system in
+	/// [`register_progress_checker`](SetupKey::register_progress_checker) for keys that are
+	/// created at runtime, since the cached system is only registered once no matter how many keys
+	/// are added, removed, or how many times the tracker is rebuilt.
+	///
+	/// # Panics
+	///
+	/// Panics if `system` isn't zero-sized, e.g. a closure that captures variables.
+	pub fn cached<M, S: IntoSystem<(), Progress, M> + 'static>(
+		world: &mut World,
+		system: S,
+	) -> Self {
+		ProgressChecker::Shared(world.register_system_cached(system))
+	}
+
+	/// Like [`cached`](Self::cached), but the system is passed the key to check, as in
+	/// [`ProgressChecker::Keyed`].
+	///
+	/// # Panics
+	///
+	/// Panics if `system` isn't zero-sized, e.g. a closure that captures variables.
+	pub fn cached_keyed<M, S: IntoSystem<In<K>, Progress, M> + 'static>(
+		world: &mut World,
+		system: S,
+	) -> Self {
+		ProgressChecker::Keyed(world.register_system_cached(system))
+	}
+
+	/// A checker for whether the resource `R` exists, like [`resource_progress`], but without
+	/// running a system.
+	pub fn resource<R: Resource>() -> Self {
+		ProgressChecker::Resource(TypeId::of::<R>())
+	}
+}
+
+impl<K: SetupKey> Clone for ProgressChecker<K> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<K: SetupKey> Copy for ProgressChecker<K> {}
+
+impl<K: SetupKey> From<ProgressCheckerId> for ProgressChecker<K> {
+	fn from(system: ProgressCheckerId) -> Self {
+		ProgressChecker::Unique(system)
+	}
+}
+
 /// Helper function to check progress based on whether a single entity exists matching the given filter.
 pub fn single_spawn_progress<F: QueryFilter>(q: Option<Single<(), F>>) -> Progress {
 	q.is_some().into()
 }
 
+/// Helper function to check progress based on the fraction of entities matching `All` that also
+/// match `Done`, e.g. `fraction_of_entities_progress::<With<Initialized>, With<NeedsInit>>()`.
+///
+/// The total is counted every time progress is checked, so entities can keep being spawned while
+/// setup is running. Returns [`Progress::DONE`] if no entities match `All`, so the key should
+/// require whichever key spawns them.
+pub fn fraction_of_entities_progress<Done: QueryFilter + 'static, All: QueryFilter + 'static>()
+-> impl System<In = (), Out = Progress> {
+	IntoSystem::into_system(|all: Query<(), All>, done: Query<(), (All, Done)>| {
+		Progress::from_steps(done.iter().count(), all.iter().count())
+	})
+}
+
 /// Helper function to check progress based on whether a resource exists.
 pub fn resource_progress<R: Resource>(res: Option<Res<R>>) -> Progress {
 	res.is_some().into()
@@ -149,44 +434,10 @@ pub fn state_progress<S: States>(state: S) -> impl System<In = (), Out = Progres
 	})
 }
 
-#[cfg(feature = "assets")]
-/// Helper function to check asset loading progress for an asset collection.
-pub fn assets_progress<C: AssetCollection>(
-	collection: Option<Res<C>>,
-	server: Res<AssetServer>,
-) -> Progress {
-	let Some(collection) = collection else {
-		return Progress::ZERO;
-	};
-
-	let (done, total) = collection.iter_ids().fold((0, 0), |(done, total), id| {
-		let Some(state) = server.get_load_state(id) else {
-			return (done, total + 1);
-		};
-
-		let done = if state.is_loaded() { done + 1 } else { done };
-
-		(done, total + 1)
-	});
-
-	Progress::new(done as f32 / total as f32)
-}
-
-#[cfg(feature = "assets")]
-/// Trait for asset collections that can be tracked for loading progress.
-pub trait AssetCollection: Resource {
-	/// Returns an iterator over all asset IDs in this collection.
-	fn iter_ids(&self) -> impl Iterator<Item = UntypedAssetId>;
-}
-
-#[cfg(feature = "assets")]
-/// Helper system to load assets for an asset collection.
-pub fn load_assets<C: AssetCollection + FromWorld>(mut cmds: Commands, collection: Option<Res<C>>) {
-	if collection.is_some() {
-		return;
-	}
-
-	cmds.init_resource::<C>();
+#[doc(hidden)]
+#[cfg(feature = "derive")]
+pub mod __private {
+	pub use bevy_asset::UntypedAssetId;
 }
 
 #[cfg(test)]
@@ -235,5 +486,77 @@ mod tests {
 		// Test relative time estimates
 		assert_eq!(key_a.relative_time_estimate(), 2.0);
 		assert_eq!(key_b.relative_time_estimate(), 1.0);
+
+		// Test default display name
+		assert_eq!(key_a.display_name(), "A");
+	}
+
+	#[test]
+	fn test_fraction_of_entities_progress() {
+		#[derive(Component)]
+		struct NeedsInit;
+
+		#[derive(Component)]
+		struct Initialized;
+
+		let mut world = World::new();
+		let checker = world.register_system(fraction_of_entities_progress::<
+			With<Initialized>,
+			With<NeedsInit>,
+		>());
+		assert_eq!(world.run_system(checker).unwrap(), Progress::DONE);
+
+		world.spawn((NeedsInit, Initialized));
+		world.spawn(NeedsInit);
+		world.spawn(Initialized);
+		assert_eq!(world.run_system(checker).unwrap(), Progress::new(0.5));
+	}
+
+	#[test]
+	fn test_fast_path_checkers() {
+		#[derive(Resource)]
+		struct Loaded;
+
+		#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+		struct TestSetupKey;
+
+		impl SetupKey for TestSetupKey {
+			fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+				world.register_system(|loaded: Option<Res<Loaded>>| loaded.is_some().into())
+			}
+
+			fn progress_checker(&self, _: &mut World) -> ProgressChecker<Self> {
+				ProgressChecker::resource::<Loaded>()
+			}
+		}
+
+		let mut world = World::new();
+		let resource = TestSetupKey.progress_checker(&mut world);
+		let function =
+			ProgressChecker::<TestSetupKey>::Fn(|world| world.contains_resource::<Loaded>().into());
+		assert_eq!(resource.run(&TestSetupKey, &mut world), Progress::ZERO);
+		assert_eq!(function.run(&TestSetupKey, &mut world), Progress::ZERO);
+
+		world.insert_resource(Loaded);
+		assert_eq!(resource.run(&TestSetupKey, &mut world), Progress::DONE);
+		assert_eq!(function.run(&TestSetupKey, &mut world), Progress::DONE);
+	}
+
+	#[test]
+	fn test_display_name_override() {
+		#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+		struct LoadCfg;
+
+		impl SetupKey for LoadCfg {
+			fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+				world.register_system(|| Progress::DONE)
+			}
+
+			fn display_name(&self) -> Cow<'static, str> {
+				"Loading configuration…".into()
+			}
+		}
+
+		assert_eq!(LoadCfg.display_name(), "Loading configuration…");
 	}
 }
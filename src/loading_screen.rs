@@ -0,0 +1,308 @@
+//! A ready-made loading screen built with `bevy_ui`.
+//!
+//! [`LoadingScreenPlugin`] spawns a full-screen overlay with a progress bar, a label showing the
+//! keys currently being worked on, and optionally a list of every key with its progress. The
+//! overlay despawns itself when setup completes.
+//!
+//! Like any `bevy_ui` node, the overlay is only rendered if there is a camera.
+
+use crate::{OnSetupComplete, SetupKey, SetupKeyState, SetupTracker};
+use bevy_app::{App, Plugin, Startup, Update};
+use bevy_color::Color;
+use bevy_ecs::prelude::*;
+use bevy_text::{TextColor, TextFont};
+use bevy_ui::widget::Text;
+use bevy_ui::{AlignItems, BackgroundColor, FlexDirection, JustifyContent, Node, Val};
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+/// Plugin that shows a loading screen while the `SetupTracker<K>` is running.
+///
+/// Requires [`SetupTrackingPlugin`](crate::SetupTrackingPlugin) (or a manually inserted
+/// `SetupTracker<K>`) for the same key type.
+pub struct LoadingScreenPlugin<K: SetupKey> {
+	style: LoadingScreenStyle,
+	_marker: PhantomData<K>,
+}
+
+impl<K: SetupKey> Default for LoadingScreenPlugin<K> {
+	fn default() -> Self {
+		Self {
+			style: LoadingScreenStyle::default(),
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<K: SetupKey> LoadingScreenPlugin<K> {
+	/// Sets the style of the loading screen.
+	///
+	/// # Parameters
+	///
+	/// - `style`: Colors, font size, and whether to list every key
+	pub fn with_style(self, style: LoadingScreenStyle) -> Self {
+		Self { style, ..self }
+	}
+}
+
+impl<K: SetupKey + Debug> Plugin for LoadingScreenPlugin<K> {
+	fn build(&self, app: &mut App) {
+		app.insert_resource(self.style.clone())
+			.add_systems(Startup, spawn_loading_screen::<K>)
+			.add_systems(
+				Update,
+				update_loading_screen::<K>.run_if(resource_exists::<SetupTracker<K>>),
+			)
+			.add_observer(despawn_loading_screen::<K>);
+	}
+}
+
+/// Appearance of the [`LoadingScreenPlugin`] overlay.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct LoadingScreenStyle {
+	/// Color behind everything else.
+	pub background: Color,
+	/// Color of the filled part of the progress bar.
+	pub bar: Color,
+	/// Color of the empty part of the progress bar.
+	pub bar_background: Color,
+	/// Color of all text.
+	pub text: Color,
+	/// Font size of all text.
+	pub font_size: f32,
+	/// Whether to list every key with its progress below the bar.
+	pub show_keys: bool,
+}
+
+impl Default for LoadingScreenStyle {
+	fn default() -> Self {
+		Self {
+			background: Color::BLACK,
+			bar: Color::WHITE,
+			bar_background: Color::srgb(0.2, 0.2, 0.2),
+			text: Color::WHITE,
+			font_size: 20.0,
+			show_keys: false,
+		}
+	}
+}
+
+/// Marks the root node of the loading screen for `SetupTracker<K>`.
+#[derive(Component, Debug)]
+pub struct LoadingScreen<K: SetupKey>(PhantomData<K>);
+
+/// Marks the filled part of the loading screen's progress bar.
+#[derive(Component, Debug)]
+pub struct LoadingScreenBar<K: SetupKey>(PhantomData<K>);
+
+/// Marks the label showing the keys currently being worked on.
+#[derive(Component, Debug)]
+pub struct LoadingScreenLabel<K: SetupKey>(PhantomData<K>);
+
+/// Marks the text listing every key, if [`LoadingScreenStyle::show_keys`] is set.
+#[derive(Component, Debug)]
+pub struct LoadingScreenKeyList<K: SetupKey>(PhantomData<K>);
+
+/// Spawns the loading screen.
+pub fn spawn_loading_screen<K: SetupKey>(mut commands: Commands, style: Res<LoadingScreenStyle>) {
+	let font = TextFont {
+		font_size: style.font_size,
+		..Default::default()
+	};
+	commands
+		.spawn((
+			LoadingScreen::<K>(PhantomData),
+			Node {
+				width: Val::Percent(100.0),
+				height: Val::Percent(100.0),
+				flex_direction: FlexDirection::Column,
+				justify_content: JustifyContent::Center,
+				align_items: AlignItems::Center,
+				row_gap: Val::Px(12.0),
+				..Default::default()
+			},
+			BackgroundColor(style.background),
+		))
+		.with_children(|parent| {
+			parent.spawn((
+				LoadingScreenLabel::<K>(PhantomData),
+				Text::default(),
+				font.clone(),
+				TextColor(style.text),
+			));
+			parent
+				.spawn((
+					Node {
+						width: Val::Percent(60.0),
+						height: Val::Px(16.0),
+						..Default::default()
+					},
+					BackgroundColor(style.bar_background),
+				))
+				.with_child((
+					LoadingScreenBar::<K>(PhantomData),
+					Node {
+						width: Val::Percent(0.0),
+						height: Val::Percent(100.0),
+						..Default::default()
+					},
+					BackgroundColor(style.bar),
+				));
+			if style.show_keys {
+				parent.spawn((
+					LoadingScreenKeyList::<K>(PhantomData),
+					Text::default(),
+					font,
+					TextColor(style.text),
+				));
+			}
+		});
+}
+
+type LabelFilter<K> = (
+	With<LoadingScreenLabel<K>>,
+	Without<LoadingScreenKeyList<K>>,
+);
+type KeyListFilter<K> = (
+	With<LoadingScreenKeyList<K>>,
+	Without<LoadingScreenLabel<K>>,
+);
+
+/// Updates the loading screen's progress bar and labels from the `SetupTracker<K>`.
+///
+/// The label shows the pending keys in the earliest unfinished [stage](SetupTracker::stages).
+pub fn update_loading_screen<K: SetupKey + Debug>(
+	tracker: Res<SetupTracker<K>>,
+	keys: Query<&SetupKeyState<K>>,
+	mut bars: Query<&mut Node, With<LoadingScreenBar<K>>>,
+	mut labels: Query<&mut Text, LabelFilter<K>>,
+	mut lists: Query<&mut Text, KeyListFilter<K>>,
+) {
+	let progress = tracker.last_progress();
+	let percent = if progress.is_finite() { *progress } else { 0.0 } * 100.0;
+	for mut bar in bars.iter_mut() {
+		bar.width = Val::Percent(percent);
+	}
+
+	let states = tracker
+		.entries()
+		.keys()
+		.filter_map(|key| tracker.key_entity(key))
+		.filter_map(|entity| keys.get(entity).ok())
+		.collect::<Vec<_>>();
+	let pending = states
+		.iter()
+		.filter(|state| !state.progress.finished())
+		.filter_map(|state| Some((tracker.stage_of_key(&state.key)?, &state.key)))
+		.collect::<Vec<_>>();
+	let current_stage = pending.iter().map(|(stage, _)| *stage).min();
+	let current = pending
+		.iter()
+		.filter(|(stage, _)| Some(*stage) == current_stage)
+		.map(|(_, key)| tracker.key_label(key))
+		.collect::<Vec<_>>()
+		.join(", ");
+	for mut label in labels.iter_mut() {
+		if label.0 != current {
+			label.0.clone_from(&current);
+		}
+	}
+
+	if !lists.is_empty() {
+		let list = states
+			.iter()
+			.map(|state| format!("{}: {:.0}", tracker.key_label(&state.key), state.progress))
+			.collect::<Vec<_>>()
+			.join("\n");
+		for mut text in lists.iter_mut() {
+			if text.0 != list {
+				text.0.clone_from(&list);
+			}
+		}
+	}
+}
+
+/// Despawns the loading screen once setup completes.
+pub fn despawn_loading_screen<K: SetupKey>(
+	_trigger: Trigger<OnSetupComplete<K>>,
+	mut commands: Commands,
+	screens: Query<Entity, With<LoadingScreen<K>>>,
+) {
+	for screen in screens.iter() {
+		commands.entity(screen).despawn();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{IntoDependencyProvider, Progress, RegisterProvider, SetupTrackingPlugin};
+	use bevy_ecs::system::SystemId;
+
+	#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+	enum TestSetupKey {
+		A,
+		B,
+	}
+
+	#[derive(Resource, Default)]
+	struct BFinished(bool);
+
+	impl SetupKey for TestSetupKey {
+		fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+			match self {
+				TestSetupKey::A => world.register_system(|| Progress::DONE),
+				TestSetupKey::B => {
+					world.register_system(|finished: Res<BFinished>| finished.0.into())
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_loading_screen() {
+		let mut app = App::new();
+		app.init_resource::<BFinished>()
+			.add_plugins((
+				SetupTrackingPlugin::<TestSetupKey>::builder().build(),
+				LoadingScreenPlugin::<TestSetupKey>::default().with_style(LoadingScreenStyle {
+					show_keys: true,
+					..Default::default()
+				}),
+			))
+			.register_provider((|| {}).provides([TestSetupKey::A]))
+			.register_provider(
+				(|| {})
+					.requires([TestSetupKey::A])
+					.provides([TestSetupKey::B]),
+			);
+
+		app.update();
+		app.update();
+		let world = app.world_mut();
+		let label = world
+			.query_filtered::<&Text, With<LoadingScreenLabel<TestSetupKey>>>()
+			.single(world)
+			.unwrap();
+		assert_eq!(label.0, "B");
+		let list = world
+			.query_filtered::<&Text, With<LoadingScreenKeyList<TestSetupKey>>>()
+			.single(world)
+			.unwrap();
+		assert_eq!(list.0, "A: 100%\nB: 0%");
+		let bar = world
+			.query_filtered::<&Node, With<LoadingScreenBar<TestSetupKey>>>()
+			.single(world)
+			.unwrap();
+		assert_eq!(bar.width, Val::Percent(50.0));
+
+		app.world_mut().resource_mut::<BFinished>().0 = true;
+		app.update();
+		let world = app.world_mut();
+		let screens = world
+			.query::<&LoadingScreen<TestSetupKey>>()
+			.iter(world)
+			.count();
+		assert_eq!(screens, 0);
+	}
+}
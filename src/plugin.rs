@@ -1,12 +1,23 @@
-use crate::{SetupKey, SetupTracker, validate_setup_graph};
-use bevy_app::{App, Plugin, Startup, Update};
+use crate::{
+	CurrentCancellationToken, CurrentFrameDeadline, FrameDiff, GraphLimits, InvalidSetupGraph,
+	Progress, SetupIncomplete, SetupKey, SetupProgressLog, SetupProgressStore, SetupTracker,
+	log_setup_progress,
+};
+use bevy_app::{App, Plugin, PostStartup, Startup, Update};
 use bevy_ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
-use bevy_ecs::{prelude::*, schedule::Condition, system::SystemParamFunction};
-use bevy_log::{debug, error};
+use bevy_ecs::{
+	prelude::*,
+	schedule::Condition,
+	system::{BoxedSystem, SystemId, SystemParamFunction},
+};
+use bevy_log::{Level, debug, error, info, info_span, warn};
 use bevy_platform::collections::HashSet;
+use bevy_platform::time::Instant;
+use std::convert::Infallible;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::sync::Mutex;
+use std::time::Duration;
 
 /// A Bevy plugin that manages setup tracking for a [`SetupKey`](SetupKey).
 ///
@@ -19,6 +30,10 @@ use std::sync::Mutex;
 /// - `C`: A condition system that determines when setup should run
 /// - `M`: Marker type for the condition system (needed by Bevy, can usually be ignored)
 /// - `Fin`: The completion callback system type
+/// - `Marker`: Marker type for the completion callback (needed by Bevy, can usually be ignored)
+///
+/// All but `K` are only used to infer the types passed to [`new`](Self::new). Use
+/// [`builder`](Self::builder) to get a plugin whose type only mentions `K`.
 ///
 /// # Examples
 ///
@@ -51,24 +66,51 @@ use std::sync::Mutex;
 /// ```
 ///
 /// Note that the plugin requires your `SetupKey` to implement `Debug` in order to implement
-/// `Plugin`, because it adds [`validate_setup_graph`] to the app, which requires `K: Debug` for
-/// error reporting. If your keys cannot implement `Debug`, you can still manually insert a
-/// `SetupTracker` and `advance_setup` system to your app.
-pub struct SetupTrackingPlugin<
-	K: SetupKey,
-	C: Condition<M>,
-	M,
-	Fin: SystemParamFunction<Marker, In = (), Out = ()>,
-	Marker,
-> {
-	condition: Mutex<Option<C>>,
-	on_finished: Mutex<Option<Fin>>,
+/// `Plugin`, because it validates the setup graph at startup (see [`ValidationPolicy`]), which
+/// requires `K: Debug` for error reporting. If your keys cannot implement `Debug`, you can still
+/// manually insert a `SetupTracker` and `advance_setup` system to your app.
+///
+/// Only one plugin can be added for each key type, since it owns the `SetupTracker<K>` resource.
+/// Adding a second one, even with a different run condition or callback, panics rather than
+/// silently replacing the tracker and losing the first plugin's callback. Register additional
+/// callbacks with [`add_completion_listener`](AddCompletionListener::add_completion_listener)
+/// instead.
+pub struct SetupTrackingPlugin<K: SetupKey, C = (), M = (), Fin = (), Marker = ()> {
+	add_advance_setup: Mutex<Option<AddAdvanceSetup>>,
+	on_finished: Mutex<Option<BoxedSystem>>,
 	schedule: InternedScheduleLabel,
-	_marker: PhantomData<(K, M, Marker)>,
+	limits: GraphLimits,
+	validation: ValidationPolicy,
+	regression_policy: RegressionPolicy,
+	frame_budget: Option<Duration>,
+	check_interval: Option<Duration>,
+	startup_timeout: Option<Duration>,
+	throttle: Throttle,
+	strict_stages: bool,
+	cleanup_on_finish: bool,
+	finish_at: Option<Progress>,
+	finish_when: Vec<K>,
+	terminal: Vec<K>,
+	milestones: Mutex<Vec<(Progress, BoxedSystem)>>,
+	progress_log: Option<(Duration, Level)>,
+	log_frame_diffs: bool,
+	_marker: PluginMarker<K, C, M, Fin, Marker>,
 }
 
-impl<K: SetupKey, C: Condition<M>, M, Fin: SystemParamFunction<Marker, In = (), Out = ()>, Marker>
-	SetupTrackingPlugin<K, C, M, Fin, Marker>
+/// The plugin's type parameters other than `K` are only used for type inference in `new`, so they
+/// don't need to be `Send` or `Sync`.
+type PluginMarker<K, C, M, Fin, Marker> = PhantomData<(K, fn() -> (C, M, Fin, Marker))>;
+
+/// Adds [`advance_setup`] to the given schedule, with the plugin's run condition.
+type AddAdvanceSetup = Box<dyn FnOnce(&mut App, InternedScheduleLabel) + Send>;
+
+impl<
+	K: SetupKey,
+	C: Condition<M> + Send + 'static,
+	M: 'static,
+	Fin: SystemParamFunction<Marker, In = (), Out = ()>,
+	Marker: 'static,
+> SetupTrackingPlugin<K, C, M, Fin, Marker>
 {
 	/// Creates a new setup tracking plugin.
 	///
@@ -89,9 +131,75 @@ impl<K: SetupKey, C: Condition<M>, M, Fin: SystemParamFunction<Marker, In = (),
 	/// - `on_finished`: A system to run when all setup tasks are complete
 	pub fn new_in_schedule(schedule: impl ScheduleLabel, condition: C, on_finished: Fin) -> Self {
 		Self {
-			condition: Mutex::new(Some(condition)),
-			on_finished: Mutex::new(Some(on_finished)),
+			add_advance_setup: Mutex::new(Some(add_advance_setup::<K, _, _>(condition))),
+			on_finished: Mutex::new(Some(Box::new(IntoSystem::into_system(on_finished)))),
+			..Self::empty(schedule)
+		}
+	}
+}
+
+impl<K: SetupKey> SetupTrackingPlugin<K> {
+	/// Returns a builder for a plugin whose only type parameter is the setup key type.
+	///
+	/// The run condition and completion callback are boxed, so they don't appear in the plugin's
+	/// type. If no run condition is given, setup runs every time the schedule runs. If no
+	/// completion callback is given, nothing runs when setup finishes, but
+	/// [`OnSetupComplete`] is still triggered.
+	///
+	/// # Examples
+	///
+	/// ```rust,no_run
+	/// use bevy::prelude::*;
+	/// use bevy::ecs::system::SystemId;
+	/// use bird_barrier::*;
+	///
+	/// #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+	/// enum MySetupKey {
+	///     LoadAssets,
+	/// }
+	///
+	/// impl SetupKey for MySetupKey {
+	///     fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+	///         world.register_system(|| Progress::DONE)
+	///     }
+	/// }
+	///
+	/// App::new().add_plugins(
+	///     SetupTrackingPlugin::<MySetupKey>::builder()
+	///         .run_if(|| true)
+	///         .on_finished(|| println!("Setup finished!"))
+	///         .build(),
+	/// );
+	/// ```
+	pub fn builder() -> SetupTrackingPluginBuilder<K> {
+		SetupTrackingPluginBuilder {
+			plugin: Self::empty(Update),
+		}
+	}
+}
+
+impl<K: SetupKey, C, M, Fin, Marker> SetupTrackingPlugin<K, C, M, Fin, Marker> {
+	/// Creates a plugin with no run condition or completion callback, and default settings.
+	fn empty(schedule: impl ScheduleLabel) -> Self {
+		Self {
+			add_advance_setup: Mutex::new(None),
+			on_finished: Mutex::new(None),
 			schedule: schedule.intern(),
+			limits: GraphLimits::default(),
+			validation: ValidationPolicy::default(),
+			regression_policy: RegressionPolicy::default(),
+			frame_budget: None,
+			check_interval: None,
+			startup_timeout: None,
+			throttle: Throttle::default(),
+			strict_stages: false,
+			cleanup_on_finish: false,
+			finish_at: None,
+			finish_when: Vec::new(),
+			terminal: Vec::new(),
+			milestones: Mutex::new(Vec::new()),
+			progress_log: None,
+			log_frame_diffs: false,
 			_marker: PhantomData,
 		}
 	}
@@ -107,56 +215,843 @@ impl<K: SetupKey, C: Condition<M>, M, Fin: SystemParamFunction<Marker, In = (),
 			..self
 		}
 	}
+
+	/// Sets the size limits for the setup graph.
+	///
+	/// # Parameters
+	///
+	/// - `limits`: The maximum number of keys and edges allowed before validation fails
+	pub fn with_limits(self, limits: GraphLimits) -> Self {
+		Self { limits, ..self }
+	}
+
+	/// Sets the maximum time to spend dispatching providers each frame.
+	///
+	/// Running every ready provider in a single frame can cause a noticeable hitch. With a budget,
+	/// [`advance_setup`] stops dispatching providers once the budget is exceeded, and resumes with
+	/// the remaining providers on the next frame.
+	///
+	/// # Parameters
+	///
+	/// - `budget`: The time budget per frame, e.g. `Duration::from_millis(4)`
+	pub fn frame_budget(self, budget: Duration) -> Self {
+		Self {
+			frame_budget: Some(budget),
+			..self
+		}
+	}
+
+	/// Only checks progress and dispatches providers once per `interval`, instead of every frame.
+	///
+	/// Checking progress 10 times a second is plenty for a loading bar, and much cheaper with
+	/// expensive progress checkers. See [`SetupTracker::set_check_interval`].
+	///
+	/// # Parameters
+	///
+	/// - `interval`: The minimum time between checks, e.g. `Duration::from_millis(100)`
+	pub fn check_every(self, interval: Duration) -> Self {
+		Self {
+			check_interval: Some(interval),
+			..self
+		}
+	}
+
+	/// Drives setup to completion synchronously in `PostStartup`, with
+	/// [`SetupTracker::run_to_completion`], instead of spreading it over frames. Useful for
+	/// headless tools, asset bakers, and tests where frame pacing doesn't matter.
+	///
+	/// The run condition is ignored while doing so. If setup doesn't finish within `timeout`, an
+	/// error is logged, and setup continues in the plugin's schedule as usual. Setup that waits for
+	/// [scheduled providers](crate::Provider::in_schedule) or
+	/// [reported](crate::ProgressChecker::Reported) keys continues there as soon as it stops
+	/// making progress, without an error.
+	///
+	/// # Parameters
+	///
+	/// - `timeout`: The maximum time to block startup for
+	pub fn run_on_startup(self, timeout: Duration) -> Self {
+		Self {
+			startup_timeout: Some(timeout),
+			..self
+		}
+	}
+
+	/// Limits how many providers are dispatched each frame.
+	///
+	/// This is a simpler alternative to [`frame_budget`](Self::frame_budget) for keeping loading
+	/// screens responsive on low-end hardware. Both can be used together.
+	///
+	/// # Parameters
+	///
+	/// - `throttle`: The maximum number of providers, or stages, to dispatch per frame
+	pub fn throttle(self, throttle: Throttle) -> Self {
+		Self { throttle, ..self }
+	}
+
+	/// Enables strict stage mode, where only providers in the current stage are dispatched.
+	///
+	/// See [`SetupTracker::set_strict_stages`].
+	pub fn strict_stages(self) -> Self {
+		Self {
+			strict_stages: true,
+			..self
+		}
+	}
+
+	/// Sets whether to [clean up](SetupTracker::cleanup) the tracker once setup finishes.
+	///
+	/// When enabled, the `SetupTracker<K>` resource is removed and all of its systems and entities
+	/// are unregistered and despawned right after the completion callbacks run for the first time.
+	///
+	/// # Parameters
+	///
+	/// - `cleanup`: Whether to clean up after setup finishes. Defaults to `false`.
+	pub fn cleanup_on_finish(self, cleanup: bool) -> Self {
+		Self {
+			cleanup_on_finish: cleanup,
+			..self
+		}
+	}
+
+	/// Considers setup finished once overall progress reaches `threshold`, so the game can start
+	/// while the last, non-critical steps are still running.
+	///
+	/// See [`SetupTracker::set_finish_at`].
+	///
+	/// # Parameters
+	///
+	/// - `threshold`: The progress at which setup counts as finished, from `0.0` to `1.0`
+	pub fn finish_at(self, threshold: impl Into<Progress>) -> Self {
+		Self {
+			finish_at: Some(threshold.into()),
+			..self
+		}
+	}
+
+	/// Considers setup finished once all of `keys` are finished, so the game can start while the
+	/// other steps are still running.
+	///
+	/// See [`SetupTracker::set_finish_when`].
+	///
+	/// # Parameters
+	///
+	/// - `keys`: The keys that must be finished
+	pub fn finish_when(mut self, keys: impl IntoIterator<Item = K>) -> Self {
+		self.finish_when.extend(keys);
+		self
+	}
+
+	/// Marks keys as intended final outputs of the setup graph, so the startup
+	/// [lint](SetupTracker::lint) can warn about other keys that are provided but never required.
+	///
+	/// See [`SetupTracker::mark_terminal`].
+	///
+	/// # Parameters
+	///
+	/// - `keys`: The final outputs
+	pub fn terminal(mut self, keys: impl IntoIterator<Item = K>) -> Self {
+		self.terminal.extend(keys);
+		self
+	}
+
+	/// Adds a system to run once overall progress reaches `threshold`, e.g. to start fading in
+	/// menu music halfway through loading.
+	///
+	/// Each milestone fires exactly once. See [`SetupTracker::add_milestone`].
+	///
+	/// # Parameters
+	///
+	/// - `threshold`: The progress at which to run the system, from `0.0` to `1.0`
+	/// - `system`: The system to run
+	pub fn on_progress<S: IntoSystem<(), (), SM>, SM>(
+		mut self,
+		threshold: impl Into<Progress>,
+		system: S,
+	) -> Self {
+		self.milestones
+			.get_mut()
+			.unwrap()
+			.push((threshold.into(), Box::new(IntoSystem::into_system(system))));
+		self
+	}
+
+	/// Periodically logs a summary of setup progress, with newly completed keys and keys that are
+	/// taking a long time. See [`log_setup_progress`].
+	///
+	/// # Parameters
+	///
+	/// - `interval`: How often to log, e.g. `Duration::from_secs(1)`
+	/// - `level`: The level to log at
+	pub fn log_progress(self, interval: Duration, level: Level) -> Self {
+		Self {
+			progress_log: Some((interval, level)),
+			..self
+		}
+	}
+
+	/// Logs which keys changed progress and which providers ran every time [`advance_setup`]
+	/// changes anything. See [`SetupTracker::last_frame_diff`].
+	pub fn log_frame_diffs(self) -> Self {
+		Self {
+			log_frame_diffs: true,
+			..self
+		}
+	}
+
+	/// Sets what happens when the setup graph fails validation at startup.
+	///
+	/// # Parameters
+	///
+	/// - `policy`: How to handle validation failures. Defaults to [`ValidationPolicy::Panic`].
+	pub fn validation(self, policy: ValidationPolicy) -> Self {
+		Self {
+			validation: policy,
+			..self
+		}
+	}
+
+	/// Sets what happens when a key's progress decreases between checks.
+	///
+	/// # Parameters
+	///
+	/// - `policy`: How to handle regressions. Defaults to [`RegressionPolicy::Warn`].
+	pub fn regression_policy(self, policy: RegressionPolicy) -> Self {
+		Self {
+			regression_policy: policy,
+			..self
+		}
+	}
 }
 
-impl<
-	K: SetupKey + Debug,
-	C: Condition<M> + Send + 'static,
-	M: Send + Sync + 'static,
-	Fin: SystemParamFunction<Marker, In = (), Out = ()> + Send + 'static,
-	Marker: Send + Sync + 'static,
-> Plugin for SetupTrackingPlugin<K, C, M, Fin, Marker>
+impl<K: SetupKey + Debug, C: 'static, M: 'static, Fin: 'static, Marker: 'static> Plugin
+	for SetupTrackingPlugin<K, C, M, Fin, Marker>
 {
 	fn build(&self, app: &mut App) {
-		let on_finished = self.on_finished.lock().unwrap().take().unwrap();
-		let fin = app.register_system(IntoSystem::into_system(on_finished));
-		app.insert_resource(SetupTracker::<K>::new(fin))
-			.add_systems(Startup, validate_setup_graph::<K>)
-			.add_systems(
+		if app.world().contains_resource::<SetupTracker<K>>() {
+			panic!(
+				"A `SetupTracker<{}>` already exists, so `SetupTrackingPlugin` was added more than \
+				once for the same key type. Add it once, and register further completion callbacks \
+				with `add_completion_listener`.",
+				std::any::type_name::<K>()
+			);
+		}
+		let fin = match self.on_finished.lock().unwrap().take() {
+			Some(on_finished) => app.world_mut().register_boxed_system(on_finished),
+			None => app.register_system(|| {}),
+		};
+		let mut tracker = SetupTracker::<K>::new(fin);
+		tracker.use_display_names();
+		tracker.set_limits(self.limits);
+		tracker.set_frame_budget(self.frame_budget);
+		tracker.set_check_interval(self.check_interval);
+		tracker.set_log_frame_diffs(self.log_frame_diffs);
+		tracker.set_regression_policy(self.regression_policy);
+		tracker.set_throttle(self.throttle);
+		tracker.set_strict_stages(self.strict_stages);
+		tracker.set_cleanup_on_finish(self.cleanup_on_finish);
+		tracker.set_finish_at(self.finish_at);
+		tracker.set_finish_when(self.finish_when.iter().cloned());
+		tracker.mark_terminal(self.terminal.iter().cloned());
+		for (threshold, system) in self.milestones.lock().unwrap().drain(..) {
+			tracker.add_milestone(threshold, app.world_mut().register_boxed_system(system));
+		}
+		app.insert_resource(tracker)
+			.init_resource::<SetupProgressStore<K>>()
+			.configure_sets(
 				self.schedule,
-				advance_setup::<K>.run_if(self.condition.lock().unwrap().take().unwrap()),
+				(
+					SetupSet::<K>::Report,
+					SetupSet::<K>::Advance,
+					SetupSet::<K>::Provide,
+				)
+					.chain(),
 			);
+		#[cfg(feature = "reflect")]
+		app.register_type::<Progress>()
+			.register_type::<crate::ProviderMetadata>()
+			.register_type::<crate::SetupStatus>();
+		#[cfg(feature = "auto-register")]
+		crate::AutoProvider::register_all::<K>(app.world_mut());
+		if self.regression_policy == RegressionPolicy::Event {
+			app.add_event::<ProgressRegressed<K>>();
+		}
+		match self.validation {
+			ValidationPolicy::Skip => {}
+			policy => {
+				if policy == ValidationPolicy::Event {
+					app.add_event::<SetupGraphInvalid<K>>();
+				}
+				app.add_systems(Startup, move |world: &mut World| {
+					for warning in world.resource::<SetupTracker<K>>().lint() {
+						warn!("{warning}");
+					}
+					if let Err(e) = SetupTracker::<K>::validate(world) {
+						policy.handle(e, world);
+					}
+				});
+			}
+		}
+		match self.add_advance_setup.lock().unwrap().take() {
+			Some(add_advance_setup) => add_advance_setup(app, self.schedule),
+			None => {
+				app.add_systems(
+					self.schedule,
+					advance_setup::<K>.in_set(SetupSet::<K>::Advance),
+				);
+			}
+		}
+		if let Some(timeout) = self.startup_timeout {
+			app.add_systems(PostStartup, move |world: &mut World| {
+				match SetupTracker::<K>::run_to_completion(world, timeout) {
+					Ok(()) => {}
+					// Expected whenever setup waits for tasks, see `run_to_completion`
+					Err(e) if cfg!(target_arch = "wasm32") => debug!("{e}"),
+					// Setup continues as soon as the app updates
+					Err(e @ SetupIncomplete::NeedsSchedule { .. }) => debug!("{e}"),
+					Err(e) => error!("{e}"),
+				}
+			});
+		}
+		if let Some((interval, level)) = self.progress_log {
+			app.insert_resource(SetupProgressLog::<K>::new(interval, level))
+				.add_systems(
+					self.schedule,
+					log_setup_progress::<K>.after(SetupSet::<K>::Advance),
+				);
+		}
 	}
 }
 
+/// Returns a function that adds [`advance_setup`] to an app with the given run condition.
+fn add_advance_setup<K: SetupKey, C: Condition<M> + Send + 'static, M: 'static>(
+	condition: C,
+) -> AddAdvanceSetup {
+	Box::new(move |app, schedule| {
+		app.add_systems(
+			schedule,
+			advance_setup::<K>
+				.run_if(condition)
+				.in_set(SetupSet::<K>::Advance),
+		);
+	})
+}
+
+/// Builder for a [`SetupTrackingPlugin`] with a boxed run condition and completion callback.
+///
+/// See [`SetupTrackingPlugin::builder`].
+pub struct SetupTrackingPluginBuilder<K: SetupKey> {
+	plugin: SetupTrackingPlugin<K>,
+}
+
+impl<K: SetupKey> SetupTrackingPluginBuilder<K> {
+	/// Sets the condition that determines when setup systems should run.
+	pub fn run_if<M: 'static>(self, condition: impl Condition<M> + Send + 'static) -> Self {
+		*self.plugin.add_advance_setup.lock().unwrap() =
+			Some(add_advance_setup::<K, _, _>(condition));
+		self
+	}
+
+	/// Sets the system to run when all setup tasks are complete.
+	pub fn on_finished<M>(self, system: impl IntoSystem<(), (), M>) -> Self {
+		*self.plugin.on_finished.lock().unwrap() = Some(Box::new(IntoSystem::into_system(system)));
+		self
+	}
+
+	/// Sets the schedule to run the setup systems in. Defaults to `Update`.
+	pub fn in_schedule(self, schedule: impl ScheduleLabel) -> Self {
+		Self {
+			plugin: self.plugin.in_schedule(schedule),
+		}
+	}
+
+	/// Builds the plugin. Other settings can be configured on the returned plugin.
+	pub fn build(self) -> SetupTrackingPlugin<K> {
+		self.plugin
+	}
+}
+
+/// System sets for ordering systems relative to the setup tracking of `K`, configured in the
+/// plugin's schedule by [`SetupTrackingPlugin`].
+///
+/// For example, `my_system.after(SetupSet::<MySetupKey>::Advance)` sees the progress and events
+/// of this frame's [`advance_setup`].
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash)]
+pub enum SetupSet<K: SetupKey> {
+	/// Systems that report progress to the [`SetupProgressStore`], so it's up to date before
+	/// [`advance_setup`] reads it.
+	Report,
+	/// [`advance_setup`] itself, including the providers and callbacks it runs.
+	Advance,
+	/// Providers that run as regular systems, once [`advance_setup`] dispatches them. See
+	/// [`Provider::in_schedule`](crate::Provider::in_schedule).
+	Provide,
+	#[doc(hidden)]
+	_Marker(PhantomData<fn() -> K>, Infallible),
+}
+
+impl<K: SetupKey> Copy for SetupSet<K> {}
+
+impl<K: SetupKey> Debug for SetupSet<K> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			SetupSet::Report => f.write_str("Report"),
+			SetupSet::Advance => f.write_str("Advance"),
+			SetupSet::Provide => f.write_str("Provide"),
+			SetupSet::_Marker(_, never) => match *never {},
+		}
+	}
+}
+
+/// What to do when the setup graph fails validation at startup.
+///
+/// See [`SetupTrackingPlugin::validation`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationPolicy {
+	/// Panic with the validation report.
+	#[default]
+	Panic,
+	/// Log the validation report as an error and continue.
+	Log,
+	/// Send a [`SetupGraphInvalid`] event containing the error and continue.
+	Event,
+	/// Don't validate the setup graph at all, e.g. to save startup time in release builds.
+	Skip,
+}
+
+impl ValidationPolicy {
+	/// Handles a validation error according to this policy.
+	pub fn handle<K: SetupKey + Debug>(self, error: InvalidSetupGraph<K>, world: &mut World) {
+		match self {
+			ValidationPolicy::Panic => panic!("{error}"),
+			ValidationPolicy::Log => error!("{error}"),
+			ValidationPolicy::Event => {
+				if world.send_event(SetupGraphInvalid(error)).is_none() {
+					warn!("`SetupGraphInvalid` event is not registered, so it was dropped");
+				}
+			}
+			ValidationPolicy::Skip => {}
+		}
+	}
+}
+
+/// Limits how many providers [`advance_setup`] dispatches per frame.
+///
+/// See [`SetupTrackingPlugin::throttle`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Throttle {
+	/// Dispatch every ready provider each frame.
+	#[default]
+	Unlimited,
+	/// Dispatch at most this many providers each frame.
+	MaxProviders(usize),
+	/// Dispatch the ready providers of a single [stage](SetupTracker::stages) each frame, cycling
+	/// through the stages that have ready providers.
+	OneStage,
+}
+
+/// What to do when a key's progress decreases between checks, which usually means its progress
+/// checker is buggy. Keys that were deliberately [invalidated](SetupTracker::invalidate) don't
+/// count.
+///
+/// See [`SetupTrackingPlugin::regression_policy`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegressionPolicy {
+	/// Log a warning.
+	#[default]
+	Warn,
+	/// Send a [`ProgressRegressed`] event.
+	Event,
+	/// Panic, e.g. only with `cfg!(debug_assertions)` to catch buggy checkers during development.
+	Panic,
+	/// Don't check for regressions.
+	Ignore,
+}
+
+/// Event sent when a key's progress decreases with [`RegressionPolicy::Event`].
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct ProgressRegressed<K: SetupKey> {
+	/// The key whose progress decreased.
+	pub key: K,
+	/// The key's progress at the previous check.
+	pub previous: Progress,
+	/// The key's new, lower progress.
+	pub progress: Progress,
+}
+
+/// Event sent when the setup graph fails validation with [`ValidationPolicy::Event`].
+#[derive(Event, Debug, Clone)]
+pub struct SetupGraphInvalid<K: SetupKey>(pub InvalidSetupGraph<K>);
+
+/// Trait for adding [completion listeners](SetupTracker::add_completion_listener) to a world or
+/// app's setup tracker.
+pub trait AddCompletionListener {
+	/// Registers `system` and adds it as a completion listener of the `SetupTracker<K>` resource.
+	///
+	/// The tracker must already be inserted, e.g. by adding [`SetupTrackingPlugin`] first.
+	fn add_completion_listener<K: SetupKey, S: IntoSystem<(), (), M> + 'static, M>(
+		&mut self,
+		system: S,
+	) -> &mut Self;
+}
+
+impl AddCompletionListener for World {
+	fn add_completion_listener<K: SetupKey, S: IntoSystem<(), (), M> + 'static, M>(
+		&mut self,
+		system: S,
+	) -> &mut Self {
+		let system = self.register_system(system);
+		self.resource_mut::<SetupTracker<K>>()
+			.add_completion_listener(system);
+		self
+	}
+}
+
+impl AddCompletionListener for App {
+	fn add_completion_listener<K: SetupKey, S: IntoSystem<(), (), M> + 'static, M>(
+		&mut self,
+		system: S,
+	) -> &mut Self {
+		self.world_mut().add_completion_listener::<K, S, M>(system);
+		self
+	}
+}
+
+/// Observer trigger for when a setup key finishes.
+///
+/// This is triggered once per key, by [`advance_setup`], the first time it sees the key's progress
+/// checker report that it is finished. If the key has an entity (see
+/// [`SetupTracker::key_entity`]), the trigger targets it, so both global observers added with
+/// `app.add_observer(...)` and observers on the key's entity will see it.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct OnSetupKeyComplete<K: SetupKey> {
+	/// The key that finished.
+	pub key: K,
+}
+
+/// Observer trigger for when all setup tracked by `SetupTracker<K>` finishes.
+///
+/// This is triggered by [`advance_setup`] when overall progress becomes finished, after
+/// [`OnSetupKeyComplete`] has been triggered for every key, and before the tracker's
+/// `on_finished` callback runs. Unlike the callback, it is only triggered once, unless progress
+/// regresses and finishes again (e.g. because new providers were registered).
+///
+/// With a [finish condition](SetupTracker::set_finish_at), it is triggered once the condition is
+/// reached instead, even if some keys are still unfinished.
+#[derive(Event, Debug)]
+pub struct OnSetupComplete<K: SetupKey>(PhantomData<K>);
+
+impl<K: SetupKey> Default for OnSetupComplete<K> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+/// Observer trigger for when every [deferred](SetupTracker::mark_deferred) key of
+/// `SetupTracker<K>` finishes.
+///
+/// Like [`OnSetupComplete`], this is only triggered again if deferred progress regresses and
+/// finishes again.
+#[derive(Event, Debug)]
+pub struct OnDeferredSetupComplete<K: SetupKey>(PhantomData<K>);
+
+impl<K: SetupKey> Default for OnDeferredSetupComplete<K> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+/// Observer trigger for when setup tracked by `SetupTracker<K>` is cancelled with
+/// [`cancel_setup`], after the cleanup systems have run.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct SetupCancelled<K: SetupKey> {
+	/// The keys that had finished before setup was cancelled, in the order they finished.
+	pub completed: Vec<K>,
+}
+
+/// Cancels setup tracked by `SetupTracker<K>`, e.g. when the player backs out of joining a server
+/// while it is loading.
+///
+/// [`advance_setup`] stops checking progress and dispatching providers. Then the
+/// [`on_cancel`](crate::Provider::on_cancel) systems of providers that finished any of their
+/// provisions run, in the reverse order of those provisions finishing, so later work is undone
+/// before the work it depended on. Finally, [`SetupCancelled`] is triggered.
+///
+/// The tracker's [`CancellationToken`](crate::CancellationToken) is cancelled first, so work that
+/// providers started in the background can stop. Background work that doesn't check the token keeps
+/// running.
+///
+/// The tracker is kept, so it can still be inspected or [cleaned up](SetupTracker::cleanup). Does
+/// nothing if the tracker doesn't exist or was already cancelled.
+pub fn cancel_setup<K: SetupKey>(world: &mut World) {
+	let Some(mut tracker) = world.get_resource_mut::<SetupTracker<K>>() else {
+		return;
+	};
+	if tracker.cancelled {
+		return;
+	}
+	tracker.cancelled = true;
+	tracker.cancellation.cancel();
+
+	let mut completed = tracker
+		.completed
+		.iter()
+		.map(|(key, finished)| (key.clone(), *finished))
+		.collect::<Vec<_>>();
+	completed.sort_by_key(|(key, finished)| (*finished, tracker.entries.get_index_of(key)));
+	let mut cleanups = tracker
+		.providers
+		.values()
+		.filter_map(|info| {
+			let on_cancel = info.on_cancel()?;
+			let finished = info
+				.provides()
+				.iter()
+				.filter_map(|key| tracker.completed.get(key))
+				.max()?;
+			Some((*finished, info.name().to_owned(), on_cancel))
+		})
+		.collect::<Vec<_>>();
+	// Latest first, with ties undone in reverse registration order
+	cleanups.reverse();
+	cleanups.sort_by(|(a, ..), (b, ..)| b.cmp(a));
+
+	for (_, name, system) in cleanups {
+		if let Err(e) = world.run_system(system) {
+			error!("Failed to run cancellation cleanup of `{name}`: {e}");
+		}
+	}
+	world.trigger(SetupCancelled {
+		completed: completed.into_iter().map(|(key, _)| key).collect(),
+	});
+}
+
+/// Triggers [`OnSetupKeyComplete`] for each of `keys` that it hasn't been triggered for yet.
+fn trigger_key_completions<'a, K: SetupKey>(
+	keys: impl IntoIterator<Item = &'a K>,
+	tracker: &mut SetupTracker<K>,
+	world: &mut World,
+) {
+	for key in keys {
+		if tracker.completed.contains_key(key) {
+			continue;
+		}
+		tracker.completed.insert(key.clone(), Instant::now());
+		let event = OnSetupKeyComplete { key: key.clone() };
+		match tracker.key_entity(key) {
+			Some(entity) => world.trigger_targets(event, entity),
+			None => world.trigger(event),
+		}
+		#[cfg(feature = "debug")]
+		crate::debug::check_breakpoint(key, tracker, world);
+	}
+}
+
+/// Handles `key`'s progress decreasing according to the tracker's
+/// [`RegressionPolicy`].
+fn handle_regression<K: SetupKey>(
+	tracker: &SetupTracker<K>,
+	key: &K,
+	previous: Progress,
+	progress: Progress,
+	world: &mut World,
+) {
+	let message = || {
+		format!(
+			"Progress of {} regressed from {previous:.0} to {progress:.0}",
+			tracker.key_span_name(key)
+		)
+	};
+	match tracker.regression_policy {
+		RegressionPolicy::Warn => warn!("{}", message()),
+		RegressionPolicy::Event => {
+			let event = ProgressRegressed {
+				key: key.clone(),
+				previous,
+				progress,
+			};
+			if world.send_event(event).is_none() {
+				warn!("`ProgressRegressed` event is not registered, so it was dropped");
+			}
+		}
+		RegressionPolicy::Panic => panic!("{}", message()),
+		RegressionPolicy::Ignore => {}
+	}
+}
+
+/// Logs which keys changed progress and which providers ran in `diff`.
+fn log_frame_diff<K: SetupKey>(tracker: &SetupTracker<K>, diff: &FrameDiff<K>) {
+	let progress = diff
+		.progress
+		.iter()
+		.map(|(key, previous, progress)| {
+			format!(
+				"{} {previous:.0} -> {progress:.0}",
+				tracker.key_span_name(key)
+			)
+		})
+		.collect::<Vec<_>>();
+	let ran = diff
+		.ran
+		.iter()
+		.map(|system| tracker.providers[system].name())
+		.collect::<Vec<_>>();
+	info!(
+		"{} setup changed: [{}], ran: [{}]",
+		disqualified::ShortName::of::<K>(),
+		progress.join(", "),
+		ran.join(", ")
+	);
+}
+
 /// System that advances the setup process by running ready providers.
 ///
 /// This system:
 /// 1. Checks which setup keys are ready (their progress checkers return finished)
-/// 2. Runs provider systems whose requirements are met and provisions aren't already all finished
-/// 3. Runs the completion callback if all setup is finished
+/// 2. Runs provider systems whose requirements are met and provisions aren't already all finished,
+///    running [fallbacks](crate::IntoDependencyProvider::fallback_for) instead of primary providers
+///    for keys that have failed. Each provider only runs once unless it
+///    [repeats until provided](crate::Provider::repeat_until_provided)
+/// 3. Triggers [`OnSetupKeyComplete`] and [`OnSetupComplete`] as keys and the whole barrier finish
+/// 4. Runs [milestones](SetupTracker::add_milestone) that overall progress has reached
+/// 5. Runs the completion callback and [completion listeners](SetupTracker::add_completion_listener)
+///    if all setup is finished, then [cleans up](SetupTracker::cleanup) the tracker if
+///    [`cleanup_on_finish`](SetupTracker::set_cleanup_on_finish) is set
+///
+/// It's an exclusive system, so checkers and providers run one after another while the rest of
+/// the schedule waits. Keys [reported](crate::ProgressChecker::Reported) by systems in
+/// [`SetupSet::Report`] and providers that run [in a schedule](crate::Provider::in_schedule) are
+/// only looked up and dispatched here, and can run in parallel.
 pub fn advance_setup<K: SetupKey>(world: &mut World) {
+	// This stays an exclusive system that coordinates everything. Only the expensive parts can opt
+	// out: checkers can be replaced with parallel systems reporting to the `SetupProgressStore`, and
+	// providers can be dispatched to run in a schedule instead.
 	// TODO: condition hackery might be able to eliminate this single-threaded, manual system running,
 	// but it would be hard to take advantage of collecting all finished entries up-front to avoid
 	// re-running progress checkers multiple times. It could also introduce race conditions between
 	// different providers checking the same key in the same tick and getting different results, but
 	// it's not clear if that would cause any real issues.
-	world.resource_scope::<SetupTracker<K>, _>(|world, mut tracker| {
+	if !world.contains_resource::<SetupTracker<K>>() {
+		// Already cleaned up
+		return;
+	}
+	let mut tracker = world.resource_mut::<SetupTracker<K>>();
+	if tracker.cancelled {
+		return;
+	}
+	let now = Instant::now();
+	if let (Some(interval), Some(last)) = (tracker.check_interval, tracker.last_checked_at) {
+		if now.duration_since(last) < interval {
+			return;
+		}
+	}
+	tracker.bypass_change_detection().last_checked_at = Some(now);
+	let cleanup = world.resource_scope::<SetupTracker<K>, _>(|world, mut resource| {
+		// Only mark the tracker as changed if this pass changes anything visible, so observers of
+		// its change ticks aren't woken up every frame
+		let tracker = resource.bypass_change_detection();
+		let frame_start = Instant::now();
 		let mut pending = HashSet::new();
-		let ready = tracker
+		let mut ready = HashSet::new();
+		let mut newly_failed = Vec::new();
+		let mut key_progress = Vec::with_capacity(tracker.entries.len());
+		let mut checked = Vec::new();
+		// Changes made from here on, including by providers run below, are seen by the next check
+		let this_check = world.increment_change_tick();
+		let last_check = tracker.last_check.replace(this_check);
+		for (key, checker) in tracker.entries.iter() {
+			let progress = if tracker.invalidated.contains(key) {
+				Progress::ZERO
+			} else if tracker.restored.contains(key) {
+				Progress::DONE
+			} else if let Some(progress) =
+				tracker.unchanged_progress(key, last_check, this_check, world)
+			{
+				progress
+			} else {
+				let _span =
+					info_span!("setup_progress_check", key = %tracker.key_span_name(key)).entered();
+				let progress = checker.run(key, world);
+				if tracker.reads.contains_key(key) {
+					checked.push((key.clone(), progress));
+				}
+				progress
+			};
+			key_progress.push((key.clone(), progress));
+			if progress.finished() {
+				ready.insert(key.clone());
+			} else {
+				if !progress.is_finite() {
+					newly_failed.push(key.clone());
+				}
+				pending.insert(key.clone());
+			}
+		}
+		tracker.failed.extend(newly_failed);
+		tracker.checked_progress.extend(checked);
+		// Keep registration order for deterministic trigger order
+		let newly_ready = tracker
 			.entries
-			.iter()
-			.filter_map(|(key, checker)| {
-				if world.run_system(*checker).unwrap().finished() {
-					Some(key.clone())
+			.keys()
+			.filter(|key| ready.contains(*key))
+			.cloned()
+			.collect::<Vec<_>>();
+		trigger_key_completions(&newly_ready, tracker, world);
+
+		// Keys that have at least one fallback or primary provider, respectively
+		let mut has_fallback = HashSet::new();
+		let mut has_primary = HashSet::new();
+		for info in tracker.providers.values() {
+			for key in info.provides() {
+				if info.fallback_for().contains(key) {
+					has_fallback.insert(key.clone());
 				} else {
-					pending.insert(key.clone());
-					None
+					has_primary.insert(key.clone());
 				}
-			})
-			.collect::<HashSet<_>>();
+			}
+		}
 
-		let should_run = move |info: &crate::ProviderInfo<K>| {
+		// Provisions of providers that have exceeded their timeout are considered failed
+		let now = Instant::now();
+		let mut timed_out = Vec::new();
+		for (system, started) in tracker.started.iter() {
+			let info = &tracker.providers[system];
+			if info
+				.timeout()
+				.is_some_and(|timeout| now.duration_since(*started) >= timeout)
+			{
+				timed_out.extend(
+					info.provides()
+						.iter()
+						.filter(|key| pending.contains(*key) && !info.fallback_for().contains(key))
+						.cloned(),
+				);
+			}
+		}
+		tracker.failed.extend(timed_out);
+		let mut diff = FrameDiff::default();
+		for (key, progress) in key_progress {
+			tracker.update_key_state(&key, progress, world);
+			let previous = tracker.last_key_progress.insert(key.clone(), progress);
+			let previous = previous.unwrap_or_default();
+			if previous.is_finite()
+				&& progress.is_finite()
+				&& progress < previous
+				&& !tracker.invalidated.contains(&key)
+			{
+				handle_regression(tracker, &key, previous, progress, world);
+			}
+			// NaN never equals itself, but failing again isn't a change
+			if previous != progress && (previous.is_finite() || progress.is_finite()) {
+				diff.progress.push((key, previous, progress));
+			}
+		}
+
+		let failed = &tracker.failed;
+		let started = &tracker.started;
+		let should_run = |system: &SystemId, info: &crate::ProviderInfo<K>| {
+			if started.contains_key(system) && !info.repeats_until_provided() {
+				return false;
+			}
 			for provision in info.provides() {
 				if ready.contains(provision) {
 					return false;
@@ -167,24 +1062,1210 @@ pub fn advance_setup<K: SetupKey>(world: &mut World) {
 					return false;
 				}
 			}
-			true
+			for requirement in info.requires_optional() {
+				if pending.contains(requirement) {
+					return false;
+				}
+			}
+			// Primaries give way to fallbacks for failed keys. Fallbacks only run for failed keys,
+			// or keys without a primary provider.
+			info.provides().is_empty()
+				|| info.provides().iter().any(|key| {
+					if info.fallback_for().contains(key) {
+						failed.contains(key) || !has_primary.contains(key)
+					} else {
+						!(failed.contains(key) && has_fallback.contains(key))
+					}
+				})
 		};
 
-		for (system, info) in tracker.providers.iter() {
-			if should_run(info) {
-				if let Err(e) = world.run_system(*system) {
-					error!("Failed to run setup system: {e}");
+		let mut to_run = tracker
+			.providers
+			.iter()
+			.filter(|(system, info)| should_run(system, info))
+			.map(|(system, _)| *system)
+			.collect::<Vec<_>>();
+		if tracker.strict_stages {
+			let stages = tracker.stages();
+			// The current stage is the first one whose provisions aren't all finished. This is
+			// recomputed every frame in case providers were added to earlier stages.
+			let current_stage = stages
+				.iter()
+				.position(|stage| {
+					!stage.iter().all(|system| {
+						tracker.providers[system]
+							.provides()
+							.iter()
+							.all(|key| ready.contains(key))
+					})
+				})
+				.unwrap_or(stages.len());
+			let current = stages
+				.get(current_stage)
+				.map(Vec::as_slice)
+				.unwrap_or_default();
+			to_run.retain(|system| current.contains(system));
+			tracker.current_stage = current_stage;
+		}
+
+		// Providers that were cut off by the frame budget last frame go first
+		to_run.sort_by_key(|system| !tracker.deferred.contains(system));
+		tracker.deferred.clear();
+
+		match tracker.throttle {
+			Throttle::Unlimited => {}
+			Throttle::MaxProviders(max) => {
+				if to_run.len() > max {
+					tracker.deferred.extend(to_run.drain(max..));
+				}
+			}
+			Throttle::OneStage => {
+				let stages = tracker.stage_indices();
+				let stage_of =
+					|system: &SystemId| stages.get(system).copied().unwrap_or(usize::MAX);
+				// The next stage after the last one dispatched, wrapping around to the first
+				let next = to_run
+					.iter()
+					.map(stage_of)
+					.filter(|stage| tracker.last_stage.is_none_or(|last| *stage > last))
+					.min()
+					.or_else(|| to_run.iter().map(stage_of).min());
+				if let Some(next) = next {
+					let (this_stage, later) = to_run
+						.into_iter()
+						.partition::<Vec<_>, _>(|system| stage_of(system) == next);
+					to_run = this_stage;
+					tracker.deferred.extend(later);
+					tracker.last_stage = Some(next);
 				}
 			}
 		}
+		#[cfg(feature = "debug")]
+		let stepped = world
+			.get_resource_mut::<crate::SetupStepper<K>>()
+			.is_some_and(|mut stepper| stepper.limit(&mut to_run));
+
+		let outer_token = (!to_run.is_empty()).then(|| {
+			let outer = (
+				world.remove_resource::<CurrentCancellationToken>(),
+				world.remove_resource::<CurrentFrameDeadline>(),
+			);
+			world.insert_resource(CurrentCancellationToken(tracker.cancellation.clone()));
+			world.insert_resource(CurrentFrameDeadline(
+				tracker.frame_budget.map(|budget| frame_start + budget),
+			));
+			outer
+		});
+		for (i, system) in to_run.iter().copied().enumerate() {
+			if i > 0
+				&& tracker
+					.frame_budget
+					.is_some_and(|budget| frame_start.elapsed() >= budget)
+			{
+				tracker.deferred.extend(to_run[i..].iter().copied());
+				break;
+			}
+			tracker.started.entry(system).or_insert(now);
+			diff.ran.push(system);
+			for key in tracker.providers[&system].provides.clone() {
+				// Progress reported before the key was invalidated is stale
+				if tracker.invalidated.remove(&key) {
+					if let Some(mut store) = world.get_resource_mut::<SetupProgressStore<K>>() {
+						store.clear(&key);
+					}
+				}
+			}
+			let run_start = Instant::now();
+			let result = {
+				let name = tracker.providers[&system].name();
+				let _span = info_span!("setup_provider", provider = name).entered();
+				world.run_system(system)
+			};
+			tracker.run_stats.entry(system).or_default().runs += 1;
+			// Scheduled providers record their own runs, this one only dispatched them
+			if tracker.providers[&system].runs_in_schedule() {
+				tracker.awaiting_schedule.insert(system);
+			} else {
+				let duration = run_start.elapsed();
+				let error = result.err().map(|e| e.to_string());
+				tracker.record_run(system, run_start, duration, error);
+			}
+		}
+		if let Some((outer_token, outer_deadline)) = outer_token {
+			world.remove_resource::<CurrentCancellationToken>();
+			world.remove_resource::<CurrentFrameDeadline>();
+			if let Some(outer) = outer_token {
+				world.insert_resource(outer);
+			}
+			if let Some(outer) = outer_deadline {
+				world.insert_resource(outer);
+			}
+		}
+		#[cfg(feature = "debug")]
+		if stepped {
+			crate::debug::record_step(tracker, to_run[0], world);
+		}
+		if tracker.log_frame_diffs && !diff.is_empty() {
+			log_frame_diff(tracker, &diff);
+		}
+		tracker.last_frame_diff = diff;
 
 		let progress = tracker.progress(world);
 		debug!(?progress);
-		if progress.finished() {
+		tracker.sample_progress(Instant::now(), progress);
+		tracker.fire_milestones(progress, world);
+		if progress.finished() && !tracker.last_progress.finished() {
+			// Keys that finished during this frame haven't been seen as ready yet. Deferred keys
+			// and keys without weight may still be pending.
+			let keys = tracker
+				.entries
+				.iter()
+				.filter(|(key, checker)| {
+					!tracker.completed.contains_key(*key)
+						&& tracker.check_key(key, checker, world).finished()
+				})
+				.map(|(key, _)| key.clone())
+				.collect::<Vec<_>>();
+			trigger_key_completions(&keys, tracker, world);
+		}
+		let finish_reached = tracker.finish_reached(progress);
+		if finish_reached {
+			if !tracker.finish_was_reached {
+				world.trigger(OnSetupComplete::<K>::default());
+			}
 			world.run_system(tracker.on_finished).unwrap();
+			for listener in tracker.completion_listeners.iter() {
+				if let Err(e) = world.run_system(*listener) {
+					error!("Failed to run setup completion listener: {e}");
+				}
+			}
 		}
-		if tracker.last_progress != progress {
-			tracker.last_progress = progress;
+		let deferred_progress = tracker.deferred_progress(world);
+		// NaN never equals itself, but failing again isn't a change
+		let differs = |previous: Progress, progress: Progress| {
+			previous != progress && (previous.is_finite() || progress.is_finite())
+		};
+		let changed = !tracker.last_frame_diff.is_empty()
+			|| tracker.finish_was_reached != finish_reached
+			|| differs(tracker.last_progress, progress)
+			|| differs(tracker.last_deferred_progress, deferred_progress);
+		tracker.finish_was_reached = finish_reached;
+		tracker.last_progress = progress;
+		if !tracker.deferred_keys.is_empty()
+			&& deferred_progress.finished()
+			&& !tracker.last_deferred_progress.finished()
+		{
+			world.trigger(OnDeferredSetupComplete::<K>::default());
+		}
+		tracker.last_deferred_progress = deferred_progress;
+		tracker.sync_status_entity(world);
+		let cleanup =
+			progress.finished() && deferred_progress.finished() && tracker.cleanup_on_finish;
+		if changed {
+			resource.set_changed();
 		}
+		cleanup
 	});
+	if cleanup {
+		SetupTracker::<K>::cleanup(world);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{CheckerReads, IntoDependencyProvider, RegisterProvider};
+
+	#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+	enum TestSetupKey {
+		A,
+		B,
+		Done,
+	}
+
+	impl SetupKey for TestSetupKey {
+		fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+			match self {
+				TestSetupKey::Done => world.register_system(|| Progress::DONE),
+				_ => world.register_system(|| Progress::ZERO),
+			}
+		}
+	}
+
+	fn invalid_app(policy: ValidationPolicy) -> App {
+		let mut app = App::new();
+		app.add_plugins(
+			SetupTrackingPlugin::<TestSetupKey, _, _, _, _>::new(|| true, || {}).validation(policy),
+		);
+		// A is never provided
+		app.register_provider(
+			(|| {})
+				.requires([TestSetupKey::A])
+				.provides([TestSetupKey::B]),
+		);
+		app
+	}
+
+	#[test]
+	#[should_panic(expected = "is required but never provided")]
+	fn test_validation_policy_panic() {
+		invalid_app(ValidationPolicy::Panic).update();
+	}
+
+	#[test]
+	fn test_validation_policy_event() {
+		let mut app = invalid_app(ValidationPolicy::Event);
+		app.update();
+
+		let events = app
+			.world_mut()
+			.resource_mut::<Events<SetupGraphInvalid<TestSetupKey>>>()
+			.drain()
+			.collect::<Vec<_>>();
+		assert_eq!(events.len(), 1);
+		assert_eq!(events[0].0.errors.len(), 1);
+	}
+
+	#[test]
+	fn test_validation_policy_log_and_skip() {
+		// Neither should panic
+		invalid_app(ValidationPolicy::Log).update();
+		invalid_app(ValidationPolicy::Skip).update();
+	}
+
+	#[derive(Resource, Default)]
+	struct Ran(Vec<&'static str>);
+
+	#[derive(Resource, Default)]
+	struct Completed(Vec<TestSetupKey>);
+
+	#[test]
+	fn test_requires_optional() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<TestSetupKey>::new(on_finished));
+		world.init_resource::<Ran>();
+
+		world.register_provider(
+			(|mut ran: ResMut<Ran>| ran.0.push("b"))
+				.requires_optional([TestSetupKey::A])
+				.provides([TestSetupKey::B]),
+		);
+
+		// Nothing provides A, so B's provider runs anyway
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["b"]);
+		assert!(
+			!world
+				.resource::<SetupTracker<TestSetupKey>>()
+				.entries()
+				.contains_key(&TestSetupKey::A)
+		);
+
+		// Once A has a provider, B's provider waits for it
+		world.resource_mut::<Ran>().0.clear();
+		world.register_provider(
+			(|mut ran: ResMut<Ran>| ran.0.push("a")).provides([TestSetupKey::A]),
+		);
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["a"]);
+	}
+
+	#[test]
+	fn test_fallback_for() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<TestSetupKey>::new(on_finished));
+		world.init_resource::<Ran>();
+
+		world
+			.register_provider(
+				(|mut ran: ResMut<Ran>| ran.0.push("primary")).provides([TestSetupKey::A]),
+			)
+			.register_provider(
+				(|mut ran: ResMut<Ran>| ran.0.push("fallback")).fallback_for([TestSetupKey::A]),
+			);
+
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["primary"]);
+
+		world.resource_mut::<Ran>().0.clear();
+		world
+			.resource_mut::<SetupTracker<TestSetupKey>>()
+			.mark_failed(TestSetupKey::A);
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["fallback"]);
+	}
+
+	#[test]
+	fn test_fallback_after_timeout() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<TestSetupKey>::new(on_finished));
+		world.init_resource::<Ran>();
+
+		world
+			.register_provider(
+				(|mut ran: ResMut<Ran>| ran.0.push("primary"))
+					.provides([TestSetupKey::A])
+					.timeout(std::time::Duration::ZERO),
+			)
+			.register_provider(
+				(|mut ran: ResMut<Ran>| ran.0.push("fallback")).fallback_for([TestSetupKey::A]),
+			);
+
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["primary"]);
+		let tracker = world.resource::<SetupTracker<TestSetupKey>>();
+		assert!(tracker.key_elapsed(&TestSetupKey::A).is_some());
+		assert_eq!(
+			tracker.last_key_progress(&TestSetupKey::A),
+			Some(Progress::ZERO)
+		);
+		let systems = tracker.providers().keys().copied().collect::<Vec<_>>();
+		let (primary, fallback) = (systems[0], systems[1]);
+		assert_eq!(
+			tracker.run_stats(primary).map(|stats| stats.runs()),
+			Some(1)
+		);
+		assert_eq!(tracker.run_stats(primary).unwrap().last_error(), None);
+		assert!(tracker.run_stats(fallback).is_none());
+
+		world.resource_mut::<Ran>().0.clear();
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["fallback"]);
+		assert!(
+			world
+				.resource::<SetupTracker<TestSetupKey>>()
+				.is_failed(&TestSetupKey::A)
+		);
+	}
+
+	#[test]
+	fn test_frame_budget() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		let mut tracker = SetupTracker::<TestSetupKey>::new(on_finished);
+		tracker.set_frame_budget(Some(std::time::Duration::ZERO));
+		world.insert_resource(tracker);
+		world.init_resource::<Ran>();
+
+		world
+			.register_provider((|mut ran: ResMut<Ran>| ran.0.push("a")).provides([TestSetupKey::A]))
+			.register_provider(
+				(|mut ran: ResMut<Ran>| ran.0.push("b")).provides([TestSetupKey::B]),
+			);
+
+		// Only one provider fits in the budget each frame
+		advance_setup::<TestSetupKey>(&mut world);
+		let first = world.resource::<Ran>().0.clone();
+		assert_eq!(first.len(), 1);
+
+		// The deferred provider runs first on the next frame
+		world.resource_mut::<Ran>().0.clear();
+		advance_setup::<TestSetupKey>(&mut world);
+		let second = world.resource::<Ran>().0.clone();
+		assert_eq!(second.len(), 1);
+		assert_ne!(first, second);
+	}
+
+	#[test]
+	fn test_throttle_max_providers() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		let mut tracker = SetupTracker::<TestSetupKey>::new(on_finished);
+		tracker.set_throttle(Throttle::MaxProviders(1));
+		world.insert_resource(tracker);
+		world.init_resource::<Ran>();
+
+		world
+			.register_provider((|mut ran: ResMut<Ran>| ran.0.push("a")).provides([TestSetupKey::A]))
+			.register_provider(
+				(|mut ran: ResMut<Ran>| ran.0.push("b")).provides([TestSetupKey::B]),
+			);
+
+		advance_setup::<TestSetupKey>(&mut world);
+		advance_setup::<TestSetupKey>(&mut world);
+		let mut ran = world.resource::<Ran>().0.clone();
+		ran.sort();
+		assert_eq!(ran, ["a", "b"]);
+	}
+
+	#[test]
+	fn test_throttle_one_stage() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		let mut tracker = SetupTracker::<TestSetupKey>::new(on_finished);
+		tracker.set_throttle(Throttle::OneStage);
+		world.insert_resource(tracker);
+		world.init_resource::<Ran>();
+
+		world
+			.register_provider((|| {}).provides([TestSetupKey::Done]))
+			.register_provider(
+				(|mut ran: ResMut<Ran>| ran.0.push("a"))
+					.provides([TestSetupKey::A])
+					.repeat_until_provided(),
+			)
+			.register_provider(
+				(|mut ran: ResMut<Ran>| ran.0.push("b"))
+					.requires([TestSetupKey::Done])
+					.provides([TestSetupKey::B]),
+			);
+
+		// Stage 0, then stage 1, then back to stage 0
+		for _ in 0..3 {
+			advance_setup::<TestSetupKey>(&mut world);
+		}
+		assert_eq!(world.resource::<Ran>().0, ["a", "b", "a"]);
+	}
+
+	#[test]
+	fn test_strict_stages() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		let mut tracker = SetupTracker::<TestSetupKey>::new(on_finished);
+		tracker.set_strict_stages(true);
+		world.insert_resource(tracker);
+		world.init_resource::<Ran>();
+
+		world.register_provider((|| {}).provides([TestSetupKey::Done]));
+		let a = (|mut ran: ResMut<Ran>| ran.0.push("a"))
+			.provides([TestSetupKey::A])
+			.register(&mut world);
+		world.register_provider(
+			(|mut ran: ResMut<Ran>| ran.0.push("b"))
+				.requires([TestSetupKey::Done])
+				.provides([TestSetupKey::B]),
+		);
+
+		// B's requirements are met, but A in the first stage isn't finished yet
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["a"]);
+
+		world.resource_scope::<SetupTracker<TestSetupKey>, _>(|world, mut tracker| {
+			tracker.remove_provider(a, world);
+		});
+		world.resource_mut::<Ran>().0.clear();
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["b"]);
+	}
+
+	#[test]
+	fn test_completion_triggers() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<TestSetupKey>::new(on_finished));
+		world.init_resource::<Ran>();
+
+		world.add_observer(
+			|trigger: Trigger<OnSetupKeyComplete<TestSetupKey>>, mut ran: ResMut<Ran>| {
+				ran.0.push(match trigger.event().key {
+					TestSetupKey::A => "a",
+					TestSetupKey::B => "b",
+					TestSetupKey::Done => "done",
+				});
+			},
+		);
+		world.add_observer(
+			|_: Trigger<OnSetupComplete<TestSetupKey>>, mut ran: ResMut<Ran>| {
+				ran.0.push("complete");
+			},
+		);
+		world.register_provider((|| {}).provides([TestSetupKey::Done]));
+
+		// Both triggers only fire once
+		advance_setup::<TestSetupKey>(&mut world);
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["done", "complete"]);
+
+		// A is never finished, so the barrier isn't complete again
+		world.resource_mut::<Ran>().0.clear();
+		world.register_provider((|| {}).provides([TestSetupKey::A]));
+		advance_setup::<TestSetupKey>(&mut world);
+		assert!(world.resource::<Ran>().0.is_empty());
+	}
+	#[test]
+	fn test_finish_conditions() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|mut ran: ResMut<Ran>| ran.0.push("finished"));
+		let mut tracker = SetupTracker::<TestSetupKey>::new(on_finished);
+		tracker.set_finish_when([TestSetupKey::Done]);
+		world.insert_resource(tracker);
+		world.init_resource::<Ran>();
+		world.add_observer(
+			|_: Trigger<OnSetupComplete<TestSetupKey>>, mut ran: ResMut<Ran>| {
+				ran.0.push("complete");
+			},
+		);
+		world.register_provider((|| {}).provides([TestSetupKey::Done]));
+		world.register_provider((|| {}).provides([TestSetupKey::A]));
+
+		advance_setup::<TestSetupKey>(&mut world);
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(
+			world.resource::<Ran>().0,
+			["complete", "finished", "finished"]
+		);
+		assert!(
+			!world
+				.resource::<SetupTracker<TestSetupKey>>()
+				.last_progress()
+				.finished()
+		);
+
+		// Half of the keys are finished
+		world.resource_mut::<Ran>().0.clear();
+		let mut tracker = world.resource_mut::<SetupTracker<TestSetupKey>>();
+		tracker.set_finish_when([]);
+		tracker.set_finish_at(Some(Progress::new(0.75)));
+		advance_setup::<TestSetupKey>(&mut world);
+		assert!(world.resource::<Ran>().0.is_empty());
+
+		world
+			.resource_mut::<SetupTracker<TestSetupKey>>()
+			.set_finish_at(Some(Progress::new(0.5)));
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["complete", "finished"]);
+	}
+
+	#[test]
+	fn test_deferred_keys() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|mut ran: ResMut<Ran>| ran.0.push("finished"));
+		let mut tracker = SetupTracker::<TestSetupKey>::new(on_finished);
+		tracker.mark_deferred([TestSetupKey::A]);
+		world.insert_resource(tracker);
+		world.init_resource::<Ran>();
+		world.add_observer(
+			|_: Trigger<OnDeferredSetupComplete<TestSetupKey>>, mut ran: ResMut<Ran>| {
+				ran.0.push("deferred");
+			},
+		);
+		world.init_resource::<Completed>();
+		world.add_observer(
+			|trigger: Trigger<OnSetupKeyComplete<TestSetupKey>>,
+			 mut completed: ResMut<Completed>| {
+				completed.0.push(trigger.event().key.clone());
+			},
+		);
+		world.register_provider((|| {}).provides([TestSetupKey::Done]));
+		world.register_provider(
+			(|mut ran: ResMut<Ran>| ran.0.push("provide_a")).provides([TestSetupKey::A]),
+		);
+
+		// A isn't finished, but setup is
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["provide_a", "finished"]);
+		let tracker = world.resource::<SetupTracker<TestSetupKey>>();
+		assert!(tracker.last_progress().finished());
+		assert_eq!(tracker.last_deferred_progress(), Progress::ZERO);
+		assert!(!tracker.completed.contains_key(&TestSetupKey::A));
+		assert_eq!(world.resource::<Completed>().0, [TestSetupKey::Done]);
+
+		// Pretend A finished
+		world.resource_mut::<Ran>().0.clear();
+		world
+			.resource_mut::<SetupTracker<TestSetupKey>>()
+			.restored
+			.insert(TestSetupKey::A);
+		advance_setup::<TestSetupKey>(&mut world);
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(
+			world.resource::<Ran>().0,
+			["finished", "deferred", "finished"]
+		);
+		assert_eq!(
+			world.resource::<Completed>().0,
+			[TestSetupKey::Done, TestSetupKey::A]
+		);
+	}
+
+	#[test]
+	fn test_invalidate() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<TestSetupKey>::new(on_finished));
+		world.init_resource::<Ran>();
+		world.add_observer(
+			|trigger: Trigger<OnSetupKeyComplete<TestSetupKey>>, mut ran: ResMut<Ran>| {
+				if trigger.event().key == TestSetupKey::Done {
+					ran.0.push("done");
+				}
+			},
+		);
+		world.register_provider(
+			(|mut ran: ResMut<Ran>| ran.0.push("provide_done")).provides([TestSetupKey::Done]),
+		);
+		world.register_provider(
+			(|| {})
+				.requires([TestSetupKey::Done])
+				.provides([TestSetupKey::B]),
+		);
+
+		// Already finished, so the provider doesn't run
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["done"]);
+
+		world.resource_mut::<Ran>().0.clear();
+		let invalidated = world
+			.resource_mut::<SetupTracker<TestSetupKey>>()
+			.invalidate_downstream(&TestSetupKey::Done);
+		assert_eq!(invalidated, [TestSetupKey::Done, TestSetupKey::B]);
+
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["provide_done"]);
+		let tracker = world.resource::<SetupTracker<TestSetupKey>>();
+		assert!(!tracker.is_invalidated(&TestSetupKey::Done));
+		assert!(tracker.is_invalidated(&TestSetupKey::B));
+
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["provide_done", "done"]);
+		let tracker = world.resource::<SetupTracker<TestSetupKey>>();
+		assert!(!tracker.is_invalidated(&TestSetupKey::B));
+	}
+
+	#[test]
+	fn test_cancel_setup() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<TestSetupKey>::new(on_finished));
+		world.init_resource::<Ran>();
+		world.add_observer(
+			|trigger: Trigger<SetupCancelled<TestSetupKey>>, mut ran: ResMut<Ran>| {
+				assert_eq!(
+					trigger.event().completed,
+					[TestSetupKey::Done, TestSetupKey::B]
+				);
+				ran.0.push("cancelled");
+			},
+		);
+		world.register_provider(
+			(|| {})
+				.provides([TestSetupKey::Done])
+				.on_cancel(|mut ran: ResMut<Ran>| ran.0.push("undo_done")),
+		);
+		world.register_provider(
+			(|| {})
+				.provides([TestSetupKey::B])
+				.on_cancel(|mut ran: ResMut<Ran>| ran.0.push("undo_b")),
+		);
+		world.register_provider(
+			(|| {})
+				.provides([TestSetupKey::A])
+				.on_cancel(|mut ran: ResMut<Ran>| ran.0.push("undo_a")),
+		);
+
+		advance_setup::<TestSetupKey>(&mut world);
+		// B's checker never finishes, so pretend it finished after `Done`
+		world
+			.resource_mut::<SetupTracker<TestSetupKey>>()
+			.completed
+			.insert(TestSetupKey::B, Instant::now());
+
+		cancel_setup::<TestSetupKey>(&mut world);
+		cancel_setup::<TestSetupKey>(&mut world);
+		assert_eq!(
+			world.resource::<Ran>().0,
+			["undo_b", "undo_done", "cancelled"]
+		);
+		assert!(
+			world
+				.resource::<SetupTracker<TestSetupKey>>()
+				.is_cancelled()
+		);
+
+		// No more providers are dispatched
+		world.resource_mut::<Ran>().0.clear();
+		world.register_provider(
+			(|mut ran: ResMut<Ran>| ran.0.push("late")).provides([TestSetupKey::A]),
+		);
+		advance_setup::<TestSetupKey>(&mut world);
+		assert!(world.resource::<Ran>().0.is_empty());
+	}
+
+	#[test]
+	fn test_completion_listeners() {
+		let mut app = App::new();
+		app.add_plugins(SetupTrackingPlugin::<TestSetupKey, _, _, _, _>::new(
+			|| true,
+			|mut ran: ResMut<Ran>| ran.0.push("on_finished"),
+		))
+		.init_resource::<Ran>()
+		.add_completion_listener::<TestSetupKey, _, _>(|mut ran: ResMut<Ran>| ran.0.push("first"))
+		.add_completion_listener::<TestSetupKey, _, _>(|mut ran: ResMut<Ran>| ran.0.push("second"))
+		.register_provider((|| {}).provides([TestSetupKey::Done]));
+
+		app.update();
+		assert_eq!(
+			app.world().resource::<Ran>().0,
+			["on_finished", "first", "second"]
+		);
+	}
+
+	#[test]
+	fn test_idle_pass_unchanged() {
+		let mut world = World::new();
+		let tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		world.insert_resource(tracker);
+		world.register_provider((|| {}).provides([TestSetupKey::Done]));
+
+		let last_changed = |world: &World| {
+			world
+				.resource_ref::<SetupTracker<TestSetupKey>>()
+				.last_changed()
+		};
+		let before = last_changed(&world);
+		world.increment_change_tick();
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_ne!(last_changed(&world), before);
+
+		// Nothing changes once setup has finished
+		let before = last_changed(&world);
+		world.increment_change_tick();
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(last_changed(&world), before);
+	}
+
+	#[test]
+	fn test_terminal_keys() {
+		let mut app = App::new();
+		app.add_plugins(
+			SetupTrackingPlugin::<TestSetupKey, _, _, _, _>::new(|| true, || {})
+				.terminal([TestSetupKey::Done]),
+		)
+		.register_provider((|| {}).provides([TestSetupKey::A]))
+		.register_provider((|| {}).provides([TestSetupKey::Done]));
+
+		let tracker = app.world().resource::<SetupTracker<TestSetupKey>>();
+		assert!(tracker.terminal_keys().contains(&TestSetupKey::Done));
+		// Only A is reported, since Done is an intended final output
+		assert_eq!(tracker.lint().len(), 1);
+	}
+
+	#[test]
+	#[should_panic(expected = "was added more than once")]
+	fn test_duplicate_plugin() {
+		App::new()
+			.add_plugins(SetupTrackingPlugin::<TestSetupKey>::builder().build())
+			.add_plugins(SetupTrackingPlugin::<TestSetupKey, _, _, _, _>::new(
+				|| true,
+				|| {},
+			));
+	}
+
+	#[test]
+	fn test_cleanup_on_finish() {
+		let mut app = App::new();
+		app.add_plugins(
+			SetupTrackingPlugin::<TestSetupKey, _, _, _, _>::new(
+				|| true,
+				|mut ran: ResMut<Ran>| ran.0.push("on_finished"),
+			)
+			.cleanup_on_finish(true),
+		)
+		.init_resource::<Ran>()
+		.register_provider((|| {}).provides([TestSetupKey::Done]));
+
+		app.update();
+		app.update();
+		assert_eq!(app.world().resource::<Ran>().0, ["on_finished"]);
+		assert!(
+			!app.world()
+				.contains_resource::<SetupTracker<TestSetupKey>>()
+		);
+		let mut keys = app
+			.world_mut()
+			.query::<&crate::SetupKeyState<TestSetupKey>>();
+		assert_eq!(keys.iter(app.world()).count(), 0);
+	}
+
+	#[test]
+	fn test_on_progress_milestones() {
+		let mut app = App::new();
+		app.add_plugins(
+			SetupTrackingPlugin::<TestSetupKey, _, _, _, _>::new(|| true, || {})
+				.on_progress(1.0, |mut ran: ResMut<Ran>| ran.0.push("done"))
+				.on_progress(0.5, |mut ran: ResMut<Ran>| ran.0.push("half")),
+		)
+		.init_resource::<Ran>()
+		.register_provider((|| {}).provides([TestSetupKey::Done]))
+		.register_provider((|| {}).provides([TestSetupKey::A]));
+
+		// Done is finished and A isn't, so progress is 50%
+		app.update();
+		app.update();
+		assert_eq!(app.world().resource::<Ran>().0, ["half"]);
+	}
+
+	#[test]
+	fn test_builder() {
+		let mut app = App::new();
+		app.add_plugins(
+			SetupTrackingPlugin::<TestSetupKey>::builder()
+				.run_if(|| true)
+				.on_finished(|mut ran: ResMut<Ran>| ran.0.push("on_finished"))
+				.build()
+				.cleanup_on_finish(true),
+		)
+		.init_resource::<Ran>()
+		.register_provider((|| {}).provides([TestSetupKey::Done]));
+
+		app.update();
+		assert_eq!(app.world().resource::<Ran>().0, ["on_finished"]);
+	}
+
+	#[test]
+	fn test_checker_reads() {
+		#[derive(Resource)]
+		struct Loaded(bool);
+
+		#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+		struct ReadKey;
+
+		impl SetupKey for ReadKey {
+			fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+				world.register_system(|loaded: Res<Loaded>, mut ran: ResMut<Ran>| {
+					ran.0.push("check");
+					loaded.0.into()
+				})
+			}
+
+			fn checker_reads(&self) -> Option<CheckerReads> {
+				Some(CheckerReads::new().resource::<Loaded>())
+			}
+		}
+
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<ReadKey>::new(on_finished));
+		world.init_resource::<Ran>();
+		world.insert_resource(Loaded(false));
+		world.register_provider((|| {}).provides([ReadKey]));
+
+		advance_setup::<ReadKey>(&mut world);
+		advance_setup::<ReadKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["check"]);
+
+		world.resource_mut::<Loaded>().0 = true;
+		advance_setup::<ReadKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["check", "check"]);
+		assert_eq!(
+			world.resource::<SetupTracker<ReadKey>>().last_progress(),
+			Progress::DONE
+		);
+	}
+
+	#[test]
+	fn test_check_interval() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		let mut tracker = SetupTracker::<TestSetupKey>::new(on_finished);
+		tracker.set_check_interval(Some(Duration::from_secs(3600)));
+		world.insert_resource(tracker);
+		world.init_resource::<Ran>();
+		world.register_provider(
+			(|mut ran: ResMut<Ran>| ran.0.push("a"))
+				.provides([TestSetupKey::A])
+				.repeat_until_provided(),
+		);
+
+		advance_setup::<TestSetupKey>(&mut world);
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["a"]);
+
+		world
+			.resource_mut::<SetupTracker<TestSetupKey>>()
+			.set_check_interval(None);
+		advance_setup::<TestSetupKey>(&mut world);
+		assert_eq!(world.resource::<Ran>().0, ["a", "a"]);
+	}
+
+	#[test]
+	fn test_progress_regression_event() {
+		#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+		struct Loaded;
+
+		#[derive(Resource)]
+		struct Level(f32);
+
+		impl SetupKey for Loaded {
+			fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+				world.register_system(|level: Res<Level>| Progress::new(level.0))
+			}
+		}
+
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<Loaded>::new(world.register_system(|| {}));
+		tracker.set_regression_policy(RegressionPolicy::Event);
+		world.insert_resource(tracker);
+		world.insert_resource(Level(0.5));
+		world.init_resource::<Events<ProgressRegressed<Loaded>>>();
+		world.register_provider((|| {}).provides([Loaded]));
+		advance_setup::<Loaded>(&mut world);
+
+		world.resource_mut::<Level>().0 = 0.25;
+		advance_setup::<Loaded>(&mut world);
+		let events = world
+			.resource_mut::<Events<ProgressRegressed<Loaded>>>()
+			.drain()
+			.collect::<Vec<_>>();
+		assert_eq!(
+			events,
+			[ProgressRegressed {
+				key: Loaded,
+				previous: Progress::new(0.5),
+				progress: Progress::new(0.25),
+			}]
+		);
+
+		// Invalidated keys are expected to go back to zero
+		world.resource_mut::<Level>().0 = 1.0;
+		advance_setup::<Loaded>(&mut world);
+		world
+			.resource_mut::<SetupTracker<Loaded>>()
+			.invalidate(&Loaded);
+		advance_setup::<Loaded>(&mut world);
+		assert!(
+			world
+				.resource::<Events<ProgressRegressed<Loaded>>>()
+				.is_empty()
+		);
+	}
+
+	#[test]
+	#[should_panic(expected = "Progress of Loaded regressed")]
+	fn test_progress_regression_panic() {
+		#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+		struct Loaded;
+
+		#[derive(Resource)]
+		struct Level(f32);
+
+		impl SetupKey for Loaded {
+			fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+				world.register_system(|level: Res<Level>| Progress::new(level.0))
+			}
+		}
+
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<Loaded>::new(world.register_system(|| {}));
+		tracker.set_regression_policy(RegressionPolicy::Panic);
+		tracker.use_display_names();
+		world.insert_resource(tracker);
+		world.insert_resource(Level(0.5));
+		world.register_provider((|| {}).provides([Loaded]));
+		advance_setup::<Loaded>(&mut world);
+
+		world.resource_mut::<Level>().0 = 0.25;
+		advance_setup::<Loaded>(&mut world);
+	}
+
+	#[test]
+	fn test_last_frame_diff() {
+		let mut world = World::new();
+		let tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		world.insert_resource(tracker);
+		let a = (|| {}).provides([TestSetupKey::A]).register(&mut world);
+		world.register_provider((|| {}).provides([TestSetupKey::Done]));
+
+		advance_setup::<TestSetupKey>(&mut world);
+		let diff = world
+			.resource::<SetupTracker<TestSetupKey>>()
+			.last_frame_diff();
+		assert_eq!(
+			diff.progress,
+			[(TestSetupKey::Done, Progress::ZERO, Progress::DONE)]
+		);
+		assert_eq!(diff.ran, [a]);
+
+		advance_setup::<TestSetupKey>(&mut world);
+		let diff = world
+			.resource::<SetupTracker<TestSetupKey>>()
+			.last_frame_diff();
+		assert!(diff.progress.is_empty());
+		assert!(diff.ran.is_empty());
+	}
+
+	#[test]
+	fn test_run_once() {
+		let mut world = World::new();
+		let tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		world.insert_resource(tracker);
+		world.init_resource::<Ran>();
+		world
+			.register_provider(
+				(|mut ran: ResMut<Ran>| ran.0.push("once")).provides([TestSetupKey::A]),
+			)
+			.register_provider(
+				(|mut ran: ResMut<Ran>| ran.0.push("repeat"))
+					.provides([TestSetupKey::B])
+					.repeat_until_provided(),
+			);
+
+		for _ in 0..3 {
+			advance_setup::<TestSetupKey>(&mut world);
+		}
+		let mut ran = world.resource::<Ran>().0.clone();
+		ran.sort();
+		assert_eq!(ran, ["once", "repeat", "repeat", "repeat"]);
+
+		// Invalidating a key dispatches its providers again
+		world.resource_mut::<Ran>().0.clear();
+		world
+			.resource_mut::<SetupTracker<TestSetupKey>>()
+			.invalidate(&TestSetupKey::A);
+		advance_setup::<TestSetupKey>(&mut world);
+		advance_setup::<TestSetupKey>(&mut world);
+		let mut ran = world.resource::<Ran>().0.clone();
+		ran.sort();
+		assert_eq!(ran, ["once", "repeat", "repeat"]);
+	}
+
+	#[test]
+	fn test_setup_set_ordering() {
+		let mut app = App::new();
+		app.add_plugins(SetupTrackingPlugin::<TestSetupKey, _, _, _, _>::new(
+			|| true,
+			|mut ran: ResMut<Ran>| ran.0.push("on_finished"),
+		))
+		.init_resource::<Ran>()
+		.add_systems(
+			Update,
+			(
+				(|mut ran: ResMut<Ran>| ran.0.push("after"))
+					.after(SetupSet::<TestSetupKey>::Advance),
+				(|mut ran: ResMut<Ran>| ran.0.push("before"))
+					.before(SetupSet::<TestSetupKey>::Advance),
+			),
+		)
+		.register_provider((|| {}).provides([TestSetupKey::Done]));
+
+		app.update();
+		assert_eq!(
+			app.world().resource::<Ran>().0,
+			["before", "on_finished", "after"]
+		);
+	}
+
+	#[test]
+	fn test_run_on_startup() {
+		let mut app = App::new();
+		app.add_plugins(
+			SetupTrackingPlugin::<TestSetupKey, _, _, _, _>::new(
+				|| false,
+				|mut ran: ResMut<Ran>| ran.0.push("on_finished"),
+			)
+			.run_on_startup(Duration::from_secs(10)),
+		)
+		.init_resource::<Ran>()
+		.register_provider(
+			(|mut ran: ResMut<Ran>| ran.0.push("done")).provides([TestSetupKey::Done]),
+		);
+
+		app.update();
+		assert_eq!(app.world().resource::<Ran>().0, ["on_finished"]);
+	}
+
+	#[test]
+	fn test_run_to_completion_timeout() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<TestSetupKey>::new(on_finished));
+		world.register_provider((|| {}).provides([TestSetupKey::A, TestSetupKey::Done]));
+
+		let result = SetupTracker::<TestSetupKey>::run_to_completion(&mut world, Duration::ZERO);
+		let Err(SetupIncomplete::TimedOut { progress, pending }) = result else {
+			panic!("expected a timeout, got {result:?}");
+		};
+		assert_eq!(progress, Progress::new(0.5));
+		assert_eq!(pending, [TestSetupKey::A]);
+	}
+
+	#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+	struct TaskDone;
+
+	#[derive(Resource, Default, Clone)]
+	struct TaskFlag(std::sync::Arc<std::sync::atomic::AtomicBool>);
+
+	impl SetupKey for TaskDone {
+		fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+			world.register_system(|flag: Res<TaskFlag>| {
+				flag.0.load(std::sync::atomic::Ordering::Acquire).into()
+			})
+		}
+	}
+
+	/// A world whose only provider finishes `TaskDone` from a task on the `IoTaskPool`.
+	fn task_world() -> World {
+		bevy_tasks::IoTaskPool::get_or_init(bevy_tasks::TaskPool::new);
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<TaskDone>::new(on_finished));
+		world.init_resource::<TaskFlag>();
+		world.register_provider(
+			(|flag: Res<TaskFlag>| {
+				let flag = flag.clone();
+				bevy_tasks::IoTaskPool::get()
+					.spawn(async move { flag.0.store(true, std::sync::atomic::Ordering::Release) })
+					.detach();
+			})
+			.provides([TaskDone]),
+		);
+		world
+	}
+
+	#[test]
+	#[cfg(not(target_arch = "wasm32"))]
+	fn test_run_to_completion_waits_for_tasks() {
+		let mut world = task_world();
+		SetupTracker::<TaskDone>::run_to_completion(&mut world, Duration::from_secs(10)).unwrap();
+	}
+
+	#[test]
+	#[cfg(not(target_arch = "wasm32"))]
+	fn test_run_to_completion_parks() {
+		#[derive(Resource, Default)]
+		struct Checks(u32);
+
+		#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+		struct Slow;
+
+		impl SetupKey for Slow {
+			fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+				world.register_system(|flag: Res<TaskFlag>, mut checks: ResMut<Checks>| {
+					checks.0 += 1;
+					flag.0.load(std::sync::atomic::Ordering::Acquire).into()
+				})
+			}
+		}
+
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<Slow>::new(on_finished));
+		world.init_resource::<Checks>();
+		let flag = TaskFlag::default();
+		world.insert_resource(flag.clone());
+		world.register_provider((|| {}).provides([Slow]));
+
+		let setter = std::thread::spawn(move || {
+			std::thread::sleep(Duration::from_millis(200));
+			flag.0.store(true, std::sync::atomic::Ordering::Release);
+		});
+		SetupTracker::<Slow>::run_to_completion(&mut world, Duration::from_secs(10)).unwrap();
+		setter.join().unwrap();
+		// Spinning would check thousands of times
+		assert!(world.resource::<Checks>().0 < 100);
+	}
+
+	#[test]
+	#[cfg(target_arch = "wasm32")]
+	fn test_run_to_completion_stalls_on_wasm() {
+		let mut world = task_world();
+		let start = Instant::now();
+		let result =
+			SetupTracker::<TaskDone>::run_to_completion(&mut world, Duration::from_secs(10));
+		assert!(matches!(result, Err(SetupIncomplete::TimedOut { .. })));
+		assert!(start.elapsed() < Duration::from_secs(10));
+	}
 }
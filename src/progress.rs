@@ -1,3 +1,5 @@
+#[cfg(feature = "reflect")]
+use bevy_reflect::std_traits::ReflectDefault;
 use nutype::nutype;
 use std::fmt::Formatter;
 
@@ -43,6 +45,10 @@ use std::fmt::Formatter;
 )]
 pub struct Progress(f32);
 
+// Reflected as an opaque value, so reflection can't bypass the sanitizer
+#[cfg(feature = "reflect")]
+bevy_reflect::impl_reflect_opaque!((in bird_barrier) Progress(Clone, Debug, Default, PartialEq));
+
 /// Clamps finite values to [0.0, 1.0], preserves non-finite values.
 const fn clamp_finite_0_to_1(val: f32) -> f32 {
 	if val.is_finite() {
@@ -75,6 +81,17 @@ impl Progress {
 	pub fn is_finite(self) -> bool {
 		(*self).is_finite()
 	}
+
+	/// Progress through a sequence of discrete steps, e.g. the phases of a connection handshake.
+	///
+	/// Returns [`Progress::DONE`] if there are no steps.
+	pub fn from_steps(done: usize, total: usize) -> Self {
+		if total == 0 {
+			Self::DONE
+		} else {
+			Self::new(done as f32 / total as f32)
+		}
+	}
 }
 
 impl std::fmt::Display for Progress {
@@ -93,6 +110,13 @@ impl From<bool> for Progress {
 	}
 }
 
+impl From<f32> for Progress {
+	/// Converts a float to Progress, clamping finite values to [0.0, 1.0].
+	fn from(val: f32) -> Self {
+		Self::new(val)
+	}
+}
+
 // Note: The question mark operator for Option types would require unstable features.
 // For now, users can use .is_some().into() or similar patterns.
 
@@ -112,6 +136,14 @@ mod tests {
 		assert_eq!(*done, 1.0);
 	}
 
+	#[test]
+	fn test_progress_from_steps() {
+		assert_eq!(Progress::from_steps(0, 4), Progress::ZERO);
+		assert_eq!(Progress::from_steps(1, 4), Progress::new(0.25));
+		assert_eq!(Progress::from_steps(5, 4), Progress::DONE);
+		assert_eq!(Progress::from_steps(0, 0), Progress::DONE);
+	}
+
 	#[test]
 	fn test_progress_clamping() {
 		// Values above 1.0 should be clamped to 1.0
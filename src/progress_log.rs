@@ -0,0 +1,154 @@
+//! Periodic, human-readable progress summaries in the log.
+
+use crate::{SetupKey, SetupTracker};
+use bevy_ecs::prelude::*;
+use bevy_log::{Level, debug, error, info, trace, warn};
+use bevy_platform::time::Instant;
+use disqualified::ShortName;
+use std::fmt::{Debug, Write};
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// Settings for [`log_setup_progress`], which periodically logs a summary of the
+/// `SetupTracker<K>`'s progress.
+///
+/// Added by [`SetupTrackingPlugin::log_progress`](crate::SetupTrackingPlugin::log_progress).
+#[derive(Resource, Debug, Clone)]
+pub struct SetupProgressLog<K: SetupKey> {
+	/// How often to log.
+	pub interval: Duration,
+	/// The level to log at.
+	pub level: Level,
+	last: Option<Instant>,
+	finished: bool,
+	_marker: PhantomData<K>,
+}
+
+impl<K: SetupKey> SetupProgressLog<K> {
+	/// Creates settings to log every `interval` at `level`.
+	pub fn new(interval: Duration, level: Level) -> Self {
+		Self {
+			interval,
+			level,
+			last: None,
+			finished: false,
+			_marker: PhantomData,
+		}
+	}
+}
+
+/// System that logs a summary of setup progress every [`SetupProgressLog::interval`], and once
+/// more when setup finishes.
+///
+/// Each summary has the overall progress, the keys completed since the previous summary, and the
+/// keys that have been in progress for longer than the interval, with how long they've taken so
+/// far.
+pub fn log_setup_progress<K: SetupKey + Debug>(
+	tracker: Option<Res<SetupTracker<K>>>,
+	mut log: ResMut<SetupProgressLog<K>>,
+) {
+	let Some(tracker) = tracker else {
+		return;
+	};
+	let progress = tracker.last_progress();
+	if log.finished {
+		return;
+	}
+	let now = Instant::now();
+	if !progress.finished()
+		&& log
+			.last
+			.is_some_and(|last| now.duration_since(last) < log.interval)
+	{
+		return;
+	}
+	let since = log.last.replace(now);
+	log.finished = progress.finished();
+
+	let mut message = format!("{} setup {progress:.0}", ShortName::of::<K>());
+	let completed = tracker
+		.entries()
+		.keys()
+		.filter(|key| {
+			tracker
+				.completed
+				.get(*key)
+				.is_some_and(|completed| since.is_none_or(|since| *completed > since))
+		})
+		.map(|key| tracker.key_label(key))
+		.collect::<Vec<_>>();
+	if !completed.is_empty() {
+		write!(message, "; completed {}", completed.join(", ")).unwrap();
+	}
+	let stuck = tracker
+		.entries()
+		.keys()
+		.filter(|key| !tracker.completed.contains_key(*key))
+		.filter_map(|key| {
+			let elapsed = tracker.key_elapsed(key)?;
+			(elapsed >= log.interval)
+				.then(|| format!("{} ({:.1}s)", tracker.key_label(key), elapsed.as_secs_f32()))
+		})
+		.collect::<Vec<_>>();
+	if !stuck.is_empty() {
+		write!(message, "; waiting on {}", stuck.join(", ")).unwrap();
+	}
+
+	match log.level {
+		Level::ERROR => error!("{message}"),
+		Level::WARN => warn!("{message}"),
+		Level::INFO => info!("{message}"),
+		Level::DEBUG => debug!("{message}"),
+		Level::TRACE => trace!("{message}"),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Progress, ProviderInfo, test_utils::TestSetupKey};
+	use bevy_ecs::system::RunSystemOnce;
+	use std::borrow::Cow;
+
+	#[test]
+	fn test_log_setup_progress() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(vec![], vec![TestSetupKey::A], Cow::Borrowed("load_a")),
+			&mut world,
+		);
+		world.insert_resource(tracker);
+		world.insert_resource(SetupProgressLog::<TestSetupKey>::new(
+			Duration::from_secs(60),
+			Level::INFO,
+		));
+
+		world
+			.run_system_once(log_setup_progress::<TestSetupKey>)
+			.unwrap();
+		let last = world.resource::<SetupProgressLog<TestSetupKey>>().last;
+		assert!(last.is_some());
+
+		// Within the interval, nothing is logged
+		world
+			.run_system_once(log_setup_progress::<TestSetupKey>)
+			.unwrap();
+		assert_eq!(
+			world.resource::<SetupProgressLog<TestSetupKey>>().last,
+			last
+		);
+
+		// Finishing is logged right away, and only once
+		world
+			.resource_mut::<SetupTracker<TestSetupKey>>()
+			.last_progress = Progress::DONE;
+		world
+			.run_system_once(log_setup_progress::<TestSetupKey>)
+			.unwrap();
+		let log = world.resource::<SetupProgressLog<TestSetupKey>>();
+		assert!(log.finished);
+		assert_ne!(log.last, last);
+	}
+}
@@ -1,16 +1,43 @@
-use crate::{ProgressCheckerId, SetupKey, SetupTracker};
-use bevy_app::App;
-use bevy_ecs::{prelude::*, system::IntoSystem};
-use bevy_platform::collections::HashMap;
+use crate::{Progress, ProgressChecker, ReportProgress, SetupKey, SetupSet, SetupTracker};
+use bevy_app::{App, SubApp};
+use bevy_ecs::{
+	archetype::ArchetypeComponentId,
+	component::{ComponentId, Tick},
+	prelude::*,
+	query::Access,
+	schedule::{InternedScheduleLabel, InternedSystemSet, ScheduleLabel},
+	system::{BoxedSystem, IntoSystem, SystemId, SystemIn, SystemParamValidationError},
+	world::{DeferredWorld, unsafe_world_cell::UnsafeWorldCell},
+};
+use bevy_platform::time::Instant;
+use indexmap::IndexMap;
+use smallvec::SmallVec;
+use std::any::TypeId;
 use std::borrow::Cow;
 use std::marker::PhantomData;
+use std::time::Duration;
+
+/// The keys a provider requires or provides. Most providers only have a few, so they are stored
+/// inline to avoid allocating.
+pub(crate) type ProviderKeys<K> = SmallVec<[K; 3]>;
 
 /// Information about a setup provider, including its dependencies and what it provides.
 #[derive(Debug, Clone)]
 pub struct ProviderInfo<K: SetupKey> {
-	requires: Vec<K>,
-	provides: Vec<K>,
-	name: Cow<'static, str>,
+	pub(crate) requires: ProviderKeys<K>,
+	pub(crate) requires_optional: Vec<K>,
+	pub(crate) provides: ProviderKeys<K>,
+	pub(crate) provides_any: Vec<K>,
+	pub(crate) fallback_for: Vec<K>,
+	pub(crate) timeout: Option<Duration>,
+	pub(crate) weight: Option<f32>,
+	pub(crate) name: Cow<'static, str>,
+	pub(crate) description: Option<Cow<'static, str>>,
+	pub(crate) tags: Vec<Cow<'static, str>>,
+	pub(crate) on_cancel: Option<SystemId>,
+	pub(crate) repeat_until_provided: bool,
+	pub(crate) reports_progress: bool,
+	pub(crate) scheduled: bool,
 }
 
 impl<K: SetupKey> ProviderInfo<K> {
@@ -18,20 +45,42 @@ impl<K: SetupKey> ProviderInfo<K> {
 	#[cfg(test)]
 	pub fn new(requires: Vec<K>, provides: Vec<K>, name: Cow<'static, str>) -> Self {
 		Self {
-			requires,
-			provides,
+			requires: requires.into(),
+			provides: provides.into(),
 			name,
+			..Self::empty()
+		}
+	}
+
+	/// Creates a ProviderInfo with no requirements, provisions, or name.
+	pub(crate) fn empty() -> Self {
+		Self {
+			requires: SmallVec::new(),
+			requires_optional: Vec::new(),
+			provides: SmallVec::new(),
+			provides_any: Vec::new(),
+			fallback_for: Vec::new(),
+			timeout: None,
+			weight: None,
+			name: Cow::Borrowed(""),
+			description: None,
+			tags: Vec::new(),
+			on_cancel: None,
+			repeat_until_provided: false,
+			reports_progress: false,
+			scheduled: false,
 		}
 	}
 
 	/// Checks if this provider should run based on the current state of setup entries.
 	///
-	/// A provider should run if any key it provides is still pending, and all of its requirements
-	/// are satisfied.
-	pub fn should_run(&self, entries: &HashMap<K, ProgressCheckerId>, world: &mut World) -> bool {
+	/// A provider should run if any key it provides is still pending, all of its requirements
+	/// are satisfied, and all of its [optional requirements](Self::requires_optional) that are
+	/// tracked in `entries` are satisfied.
+	pub fn should_run(&self, entries: &IndexMap<K, ProgressChecker<K>>, world: &mut World) -> bool {
 		let mut all_provisions_finished = true;
 		for provision in &self.provides {
-			if !world.run_system(entries[provision]).unwrap().finished() {
+			if !entries[provision].run(provision, world).finished() {
 				all_provisions_finished = false;
 				break;
 			}
@@ -40,27 +89,113 @@ impl<K: SetupKey> ProviderInfo<K> {
 			return false;
 		}
 		for requirement in &self.requires {
-			if !world.run_system(entries[requirement]).unwrap().finished() {
+			if !entries[requirement].run(requirement, world).finished() {
 				return false;
 			}
 		}
+		for requirement in &self.requires_optional {
+			if let Some(checker) = entries.get(requirement) {
+				if !checker.run(requirement, world).finished() {
+					return false;
+				}
+			}
+		}
 		true
 	}
 
+	/// Removes all occurrences of `key` from this provider's requirements and provisions.
+	pub(crate) fn remove_key(&mut self, key: &K) {
+		self.requires.retain(|k| k != key);
+		self.requires_optional.retain(|k| k != key);
+		self.provides.retain(|k| k != key);
+		self.provides_any.retain(|k| k != key);
+		self.fallback_for.retain(|k| k != key);
+	}
+
 	/// Returns the setup keys that this provider requires.
 	pub fn requires(&self) -> &[K] {
 		&self.requires
 	}
 
+	/// Returns the setup keys that this provider should wait for only if something provides them.
+	///
+	/// Unlike [`requires`](Self::requires), these keys don't get progress checkers registered on
+	/// their own, so they don't cause validation errors or count towards total progress when
+	/// nothing provides them.
+	pub fn requires_optional(&self) -> &[K] {
+		&self.requires_optional
+	}
+
 	/// Returns the setup keys that this provider provides.
 	pub fn provides(&self) -> &[K] {
 		&self.provides
 	}
 
+	/// Returns the setup keys that this provider provides as one of several alternative providers.
+	///
+	/// These keys are also included in [`provides`](Self::provides).
+	pub fn provides_any(&self) -> &[K] {
+		&self.provides_any
+	}
+
+	/// Returns the setup keys that this provider is a fallback for.
+	///
+	/// These keys are also included in [`provides`](Self::provides).
+	pub fn fallback_for(&self) -> &[K] {
+		&self.fallback_for
+	}
+
+	/// Returns how long this provider may take to finish its provisions before they are
+	/// considered failed.
+	pub fn timeout(&self) -> Option<Duration> {
+		self.timeout
+	}
+
 	/// Returns the name of this provider.
+	///
+	/// This is the name given with [`Provider::named`], or the system's type name as formatted by
+	/// [`SetupTracker::set_name_formatter`] otherwise.
 	pub fn name(&self) -> &str {
 		&self.name
 	}
+
+	/// Returns the description given with [`Provider::description`], if any.
+	pub fn description(&self) -> Option<&str> {
+		self.description.as_deref()
+	}
+
+	/// Returns the tags given with [`Provider::tags`].
+	pub fn tags(&self) -> &[Cow<'static, str>] {
+		&self.tags
+	}
+
+	/// Returns the weight given with [`Provider::weight`], if any.
+	pub fn weight(&self) -> Option<f32> {
+		self.weight
+	}
+
+	/// Returns the cleanup system given with [`Provider::on_cancel`], if any.
+	pub fn on_cancel(&self) -> Option<SystemId> {
+		self.on_cancel
+	}
+
+	/// Returns whether this provider runs every frame until its provisions finish, instead of
+	/// only once. See [`Provider::repeat_until_provided`].
+	pub fn repeats_until_provided(&self) -> bool {
+		self.repeat_until_provided
+	}
+
+	/// Returns whether this provider's return value is the progress of the keys it provides. See
+	/// [`IntoIncrementalProvider`].
+	pub fn reports_progress(&self) -> bool {
+		self.reports_progress
+	}
+
+	/// Returns whether this provider runs in a schedule, only being dispatched by
+	/// [`advance_setup`](crate::advance_setup). See [`Provider::in_schedule`].
+	pub fn runs_in_schedule(&self) -> bool {
+		self.scheduled
+	}
 }
 
 /// A setup provider that defines a system with its dependencies and provisions.
@@ -68,38 +203,58 @@ impl<K: SetupKey> ProviderInfo<K> {
 /// Providers are the nodes of the setup graph. Each provider represents a system that should run
 /// when its requirements are met and provides certain setup keys when complete.
 pub struct Provider<K: SetupKey, S: IntoSystem<(), (), M>, M> {
-	requires: Vec<K>,
-	provides: Vec<K>,
+	info: ProviderInfo<K>,
 	system: S,
 	name: Option<Cow<'static, str>>,
+	on_cancel: Option<BoxedSystem>,
+	schedule: Option<InternedScheduleLabel>,
+	/// The type name of the user's system, for the default name
+	type_name: &'static str,
 	_marker: PhantomData<M>,
 }
 
 impl<K: SetupKey, S: IntoSystem<(), (), M> + 'static, M> Provider<K, S, M> {
 	/// Registers this provider with the world's setup tracker.
-	fn register(self, world: &mut World) {
+	///
+	/// Returns the `SystemId` of the registered provider system, which can later be passed to
+	/// [`SetupTracker::remove_provider`].
+	pub fn register(self, world: &mut World) -> SystemId {
 		let Self {
-			requires,
-			provides,
+			mut info,
 			system,
 			name,
+			on_cancel,
+			schedule,
+			type_name,
 			..
 		} = self;
 
-		let name = name.unwrap_or_else(|| {
-			let full_name = std::any::type_name_of_val(&system);
-			Cow::<'static, str>::Borrowed(full_name)
-		});
-
-		let info = ProviderInfo {
-			requires,
-			provides,
-			name,
+		let system = match schedule {
+			Some(schedule) => {
+				// `advance_setup` runs a placeholder, which dispatches the real system
+				let placeholder = world.register_system(|| {});
+				let mut schedules = world.get_resource_or_init::<Schedules>();
+				schedules.configure_sets(
+					schedule,
+					SetupSet::<K>::Provide.after(SetupSet::<K>::Advance),
+				);
+				schedules.add_systems(
+					schedule,
+					ScheduledProvider::<K, _>::new(IntoSystem::into_system(system), placeholder)
+						.run_if(provider_dispatched::<K>(placeholder))
+						.in_set(SetupSet::<K>::Provide),
+				);
+				info.scheduled = true;
+				placeholder
+			}
+			None => world.register_system(system),
 		};
-		let system = world.register_system(system);
+		info.on_cancel = on_cancel.map(|on_cancel| world.register_boxed_system(on_cancel));
 		world.resource_scope::<SetupTracker<K>, _>(|world, mut tracker| {
+			info.name = name.unwrap_or_else(|| Cow::Owned(tracker.format_name(type_name)));
 			tracker.register_provider(system, info, world);
-		})
+		});
+		system
 	}
 }
 
@@ -132,6 +287,79 @@ impl RegisterProvider for App {
 	}
 }
 
+impl RegisterProvider for SubApp {
+	fn register_provider<K: SetupKey, S: IntoSystem<(), (), M> + 'static, M>(
+		&mut self,
+		provider: Provider<K, S, M>,
+	) -> &mut Self {
+		provider.register(self.world_mut());
+		self
+	}
+}
+
+/// Registers several providers at once, declaring the whole setup graph in one block.
+///
+/// Each entry is a system, followed by `=>`, its required keys, `->`, and its provided keys.
+/// An entry may be followed by `weight` and a single token, such as a literal or constant, to set
+/// the [weight](Provider::weight) of the keys it provides, and may end with `named "..."` to give
+/// the provider a [name](Provider::named). The first argument is anything implementing
+/// [`RegisterProvider`], such as an `App` or `World`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use bevy::prelude::*;
+/// use bevy::ecs::system::SystemId;
+/// use bird_barrier::*;
+///
+/// #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// enum MySetupKey {
+///     LoadConfig,
+///     LoadAssets,
+///     BuildScene,
+/// }
+///
+/// impl SetupKey for MySetupKey {
+///     fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+///         world.register_system(|| Progress::DONE)
+///     }
+/// }
+///
+/// fn load_config() {}
+/// fn load_assets() {}
+/// fn build_scene() {}
+///
+/// let mut app = App::new();
+/// register_providers!(app, {
+///     load_config => [] -> [MySetupKey::LoadConfig],
+///     load_assets => [MySetupKey::LoadConfig] -> [MySetupKey::LoadAssets] weight 3.0
+///         named "Load assets",
+///     build_scene => [MySetupKey::LoadAssets] -> [MySetupKey::BuildScene],
+/// });
+/// ```
+#[macro_export]
+macro_rules! register_providers {
+	($target:expr, {
+		$(
+			$system:expr => [$($requires:expr),* $(,)?] -> [$($provides:expr),* $(,)?]
+			$(weight $weight:tt)?
+			$(named $name:expr)?
+		),* $(,)?
+	}) => {{
+		#[allow(unused_imports)]
+		use $crate::{IntoDependencyProvider as _, RegisterProvider as _};
+		$(
+			$target.register_provider(
+				$system
+					.requires([$($requires),*])
+					.provides([$($provides),*])
+					$(.weight($weight))?
+					$(.named($name))?,
+			);
+		)*
+	}};
+}
+
 /// Trait for converting systems into dependency providers.
 ///
 /// This trait allows you to fluently build provider configurations by chaining
@@ -181,40 +409,487 @@ pub trait IntoDependencyProvider<K: SetupKey, S: IntoSystem<(), (), M>, M> {
 
 	/// Specifies what setup keys this provider requires.
 	fn requires(self, keys: impl IntoIterator<Item = K>) -> Provider<K, S, M>;
+
+	/// Specifies setup keys this provider provides as one of several alternatives.
+	///
+	/// Normally a key provided by more than one provider fails validation. If every provider of a
+	/// key uses `provides_any` for it, the duplicate-provider check is skipped for that key. Every
+	/// alternative whose requirements are met will run, so alternatives should usually have
+	/// mutually exclusive requirements, or check whether the work has already been done.
+	fn provides_any(self, keys: impl IntoIterator<Item = K>) -> Provider<K, S, M>;
+
+	/// Specifies setup keys this provider should wait for, but only if something provides them.
+	///
+	/// This is useful for integrating with plugins that may or may not be present: if no provider
+	/// for a key is registered, the provider runs as if the key wasn't listed at all.
+	fn requires_optional(self, keys: impl IntoIterator<Item = K>) -> Provider<K, S, M>;
+
+	/// Specifies setup keys this provider is a fallback for.
+	///
+	/// A fallback only runs once a key it is a fallback for has failed, i.e. its progress checker
+	/// returned a non-finite value, a primary provider of the key returned an error or exceeded its
+	/// [`timeout`](Provider::timeout), or the key was passed to [`SetupTracker::mark_failed`].
+	/// If a key has no primary provider, its fallbacks run like normal providers.
+	///
+	/// Fallbacks are not counted as duplicate providers during validation.
+	fn fallback_for(self, keys: impl IntoIterator<Item = K>) -> Provider<K, S, M>;
 }
 
 impl<K: SetupKey, S: IntoSystem<(), (), M>, M> IntoDependencyProvider<K, S, M> for S {
 	fn provides(self, keys: impl IntoIterator<Item = K>) -> Provider<K, S, M> {
+		Provider::from_system(self).provides(keys)
+	}
+
+	fn requires(self, keys: impl IntoIterator<Item = K>) -> Provider<K, S, M> {
+		Provider::from_system(self).requires(keys)
+	}
+
+	fn provides_any(self, keys: impl IntoIterator<Item = K>) -> Provider<K, S, M> {
+		Provider::from_system(self).provides_any(keys)
+	}
+
+	fn requires_optional(self, keys: impl IntoIterator<Item = K>) -> Provider<K, S, M> {
+		Provider::from_system(self).requires_optional(keys)
+	}
+
+	fn fallback_for(self, keys: impl IntoIterator<Item = K>) -> Provider<K, S, M> {
+		Provider::from_system(self).fallback_for(keys)
+	}
+}
+
+/// Trait for turning systems that return their own [`Progress`] into providers, for chunked work
+/// like generating a world a slice at a time, without a separate progress checker:
+///
+/// ```rust,ignore
+/// fn generate_chunks(mut world_gen: ResMut<WorldGen>) -> Progress {
+///     world_gen.generate_next_slice();
+///     Progress::from_steps(world_gen.done, world_gen.total)
+/// }
+///
+/// app.register_provider(generate_chunks.provides_incrementally([MySetupKey::Terrain]));
+/// ```
+pub trait IntoIncrementalProvider<K: SetupKey, M> {
+	/// Makes this system a provider of `keys` that runs every frame until they finish, with its
+	/// return value as their progress.
+	///
+	/// The keys' progress is [reported](ProgressChecker::Reported) to the
+	/// [`SetupProgressStore`](crate::SetupProgressStore), replacing their own progress checkers.
+	fn provides_incrementally(
+		self,
+		keys: impl IntoIterator<Item = K>,
+	) -> Provider<K, impl System<In = (), Out = ()>, ()>;
+}
+
+impl<K: SetupKey, S: IntoSystem<(), Progress, M> + 'static, M> IntoIncrementalProvider<K, M> for S {
+	fn provides_incrementally(
+		self,
+		keys: impl IntoIterator<Item = K>,
+	) -> Provider<K, impl System<In = (), Out = ()>, ()> {
+		let keys = keys.into_iter().collect::<Vec<_>>();
+		let reported = keys.clone();
+		let system = IntoSystem::into_system(self.pipe(
+			move |In(progress): In<Progress>, mut store: ReportProgress<K>| {
+				for key in &reported {
+					store.report(key.clone(), progress);
+				}
+			},
+		));
+		let mut provider = Provider::from_system(system)
+			.provides(keys)
+			.repeat_until_provided();
+		provider.info.reports_progress = true;
+		provider.type_name = std::any::type_name::<S>();
+		provider
+	}
+}
+
+impl<K: SetupKey, S: IntoSystem<(), (), M>, M> Provider<K, S, M> {
+	fn from_system(system: S) -> Self {
 		Provider {
-			provides: keys.into_iter().collect(),
-			requires: Vec::new(),
-			system: self,
+			info: ProviderInfo::empty(),
+			system,
 			name: None,
+			on_cancel: None,
+			schedule: None,
+			type_name: std::any::type_name::<S>(),
 			_marker: PhantomData,
 		}
 	}
 
-	fn requires(self, keys: impl IntoIterator<Item = K>) -> Provider<K, S, M> {
-		Provider {
-			provides: Vec::new(),
-			requires: keys.into_iter().collect(),
-			system: self,
-			name: None,
+	/// Sets how long this provider may take to finish its provisions, measured from the first time
+	/// it runs.
+	///
+	/// Once the timeout has elapsed, any of its unfinished provisions that have a
+	/// [fallback](IntoDependencyProvider::fallback_for) are considered failed, and the fallback
+	/// runs instead.
+	pub fn timeout(mut self, timeout: Duration) -> Self {
+		self.info.timeout = Some(timeout);
+		self
+	}
+
+	/// Sets the weight of the keys this provider provides, overriding
+	/// [`SetupKey::relative_time_estimate`], e.g. when it depends on a runtime setting like texture
+	/// quality.
+	///
+	/// See [`SetupTracker::set_key_weight`].
+	pub fn weight(mut self, weight: f32) -> Self {
+		self.info.weight = Some(weight);
+		self
+	}
+
+	/// Runs this provider every frame until all of its provisions finish, instead of only once.
+	///
+	/// By default, a provider is only dispatched again if one of its provisions is
+	/// [invalidated](SetupTracker::invalidate), so expensive providers don't run repeatedly while
+	/// waiting for slow progress checkers, e.g. for assets to load. Opt in for providers that do
+	/// their work incrementally, such as spawning a few entities per frame.
+	pub fn repeat_until_provided(mut self) -> Self {
+		self.info.repeat_until_provided = true;
+		self
+	}
+
+	/// Sets a human-readable name for this provider, used in logs and visualization instead of the
+	/// system's type name, which is unreadable for closures.
+	pub fn named(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+		self.name = Some(name.into());
+		self
+	}
+
+	/// Sets a longer description of what this provider does.
+	pub fn description(mut self, description: impl Into<Cow<'static, str>>) -> Self {
+		self.info.description = Some(description.into());
+		self
+	}
+
+	/// Adds tags to this provider, e.g. to group or filter providers in reports and visualization.
+	pub fn tags<T: Into<Cow<'static, str>>>(mut self, tags: impl IntoIterator<Item = T>) -> Self {
+		self.info.tags.extend(tags.into_iter().map(Into::into));
+		self
+	}
+
+	/// Sets a system that undoes this provider's work if setup is [cancelled](crate::cancel_setup)
+	/// after any of its provisions finished, e.g. despawning what it spawned.
+	pub fn on_cancel<C: IntoSystem<(), (), CM>, CM>(mut self, cleanup: C) -> Self {
+		self.on_cancel = Some(Box::new(IntoSystem::into_system(cleanup)));
+		self
+	}
+
+	/// Runs this provider as a regular system in `schedule`, in [`SetupSet::Provide`], instead of
+	/// inside [`advance_setup`](crate::advance_setup), so it can run in parallel with other
+	/// systems.
+	///
+	/// [`advance_setup`](crate::advance_setup) still decides when the provider runs, but only
+	/// dispatches it, and the system runs the next time `schedule` runs after
+	/// [`SetupSet::Advance`]. Dispatches in between are only run once, so with
+	/// [`repeat_until_provided`](Self::repeat_until_provided) the system runs at most once per
+	/// run of `schedule`. The `SystemId` returned by [`register`](Self::register) is that of the
+	/// dispatching placeholder, which is what the tracker and its reports see. Its
+	/// [run stats](SetupTracker::run_stats) count dispatches, but their durations, errors and
+	/// [traces](SetupTracker::set_record_trace) are those of the system's actual runs, recorded
+	/// once its deferred changes are applied. Its [timeout](Self::timeout) counts from its first
+	/// dispatch.
+	///
+	/// [`SetupTracker::run_to_completion`] doesn't run `schedule`, so it returns
+	/// [`SetupIncomplete::NeedsSchedule`](crate::SetupIncomplete::NeedsSchedule) once it has
+	/// nothing left to do but wait for the system.
+	///
+	/// The system is added to `schedule` when the provider is registered, so it must be registered
+	/// while building the app, or at least not while `schedule` is running. It isn't removed from
+	/// the schedule with the provider, but is never dispatched again. The
+	/// [`CurrentCancellationToken`](crate::CurrentCancellationToken) isn't available to it, but
+	/// [`SetupTracker::cancellation_token`] is.
+	pub fn in_schedule(mut self, schedule: impl ScheduleLabel) -> Self {
+		self.schedule = Some(schedule.intern());
+		self
+	}
+}
+
+/// Run condition that is true if [`advance_setup`](crate::advance_setup) ran the provider
+/// `provider` since the condition was last checked, for systems that do its work outside of
+/// [`advance_setup`](crate::advance_setup). Several dispatches between two checks are only seen
+/// once. See [`Provider::in_schedule`].
+pub fn provider_dispatched<K: SetupKey>(
+	provider: SystemId,
+) -> impl FnMut(Option<Res<SetupTracker<K>>>, Local<u32>) -> bool {
+	move |tracker, mut seen| {
+		let runs = tracker
+			.and_then(|tracker| tracker.run_stats(provider).map(|stats| stats.runs()))
+			.unwrap_or(0);
+		let dispatched = runs > *seen;
+		*seen = runs;
+		dispatched
+	}
+}
+
+/// The system of a [scheduled](Provider::in_schedule) provider, recording its runs in the
+/// [run stats](SetupTracker::run_stats) and trace of its dispatching placeholder.
+struct ScheduledProvider<K: SetupKey, S: System<In = (), Out = ()>> {
+	system: S,
+	placeholder: SystemId,
+	/// When the last run started, how long it took, and why it couldn't run, until it's recorded
+	last_run: Option<(Instant, Duration, Option<String>)>,
+	_marker: PhantomData<fn() -> K>,
+}
+
+impl<K: SetupKey, S: System<In = (), Out = ()>> ScheduledProvider<K, S> {
+	fn new(system: S, placeholder: SystemId) -> Self {
+		Self {
+			system,
+			placeholder,
+			last_run: None,
 			_marker: PhantomData,
 		}
 	}
+
+	fn record(
+		placeholder: SystemId,
+		last_run: (Instant, Duration, Option<String>),
+		world: &mut World,
+	) {
+		if let Some(mut tracker) = world.get_resource_mut::<SetupTracker<K>>() {
+			let (start, duration, error) = last_run;
+			tracker.record_run(placeholder, start, duration, error);
+		}
+	}
+}
+
+impl<K: SetupKey, S: System<In = (), Out = ()>> System for ScheduledProvider<K, S> {
+	type In = ();
+	type Out = ();
+
+	fn name(&self) -> Cow<'static, str> {
+		self.system.name()
+	}
+
+	fn type_id(&self) -> TypeId {
+		self.system.type_id()
+	}
+
+	fn component_access(&self) -> &Access<ComponentId> {
+		self.system.component_access()
+	}
+
+	fn archetype_component_access(&self) -> &Access<ArchetypeComponentId> {
+		self.system.archetype_component_access()
+	}
+
+	fn is_send(&self) -> bool {
+		self.system.is_send()
+	}
+
+	fn is_exclusive(&self) -> bool {
+		self.system.is_exclusive()
+	}
+
+	fn has_deferred(&self) -> bool {
+		// Runs are recorded when deferred buffers are applied
+		true
+	}
+
+	unsafe fn run_unsafe(&mut self, input: SystemIn<'_, Self>, world: UnsafeWorldCell) {
+		let start = Instant::now();
+		// Parameters are validated here instead of by the executor, so failures are recorded
+		// like those of providers run by `advance_setup`
+		// SAFETY: Upheld by the caller, the same as for `run_unsafe`
+		let error = match unsafe { self.system.validate_param_unsafe(world) } {
+			Ok(()) => {
+				// SAFETY: Upheld by the caller
+				unsafe { self.system.run_unsafe(input, world) };
+				None
+			}
+			Err(e) => Some(e.to_string()),
+		};
+		self.last_run = Some((start, start.elapsed(), error));
+	}
+
+	fn apply_deferred(&mut self, world: &mut World) {
+		self.system.apply_deferred(world);
+		if let Some(last_run) = self.last_run.take() {
+			Self::record(self.placeholder, last_run, world);
+		}
+	}
+
+	fn queue_deferred(&mut self, mut world: DeferredWorld) {
+		self.system.queue_deferred(world.reborrow());
+		if let Some(last_run) = self.last_run.take() {
+			let placeholder = self.placeholder;
+			world
+				.commands()
+				.queue(move |world: &mut World| Self::record(placeholder, last_run, world));
+		}
+	}
+
+	unsafe fn validate_param_unsafe(
+		&mut self,
+		_world: UnsafeWorldCell,
+	) -> Result<(), SystemParamValidationError> {
+		Ok(())
+	}
+
+	fn initialize(&mut self, world: &mut World) {
+		self.system.initialize(world);
+	}
+
+	fn update_archetype_component_access(&mut self, world: UnsafeWorldCell) {
+		self.system.update_archetype_component_access(world);
+	}
+
+	fn check_change_tick(&mut self, change_tick: Tick) {
+		self.system.check_change_tick(change_tick);
+	}
+
+	fn default_system_sets(&self) -> Vec<InternedSystemSet> {
+		self.system.default_system_sets()
+	}
+
+	fn get_last_run(&self) -> Tick {
+		self.system.get_last_run()
+	}
+
+	fn set_last_run(&mut self, last_run: Tick) {
+		self.system.set_last_run(last_run);
+	}
 }
 
 impl<K: SetupKey, S: IntoSystem<(), (), M>, M> IntoDependencyProvider<K, S, M>
 	for Provider<K, S, M>
 {
 	fn provides(mut self, keys: impl IntoIterator<Item = K>) -> Self {
-		self.provides.extend(keys);
+		self.info.provides.extend(keys);
 		self
 	}
 
 	fn requires(mut self, keys: impl IntoIterator<Item = K>) -> Self {
-		self.requires.extend(keys);
+		self.info.requires.extend(keys);
+		self
+	}
+
+	fn provides_any(mut self, keys: impl IntoIterator<Item = K>) -> Self {
+		for key in keys {
+			self.info.provides.push(key.clone());
+			self.info.provides_any.push(key);
+		}
 		self
 	}
+
+	fn requires_optional(mut self, keys: impl IntoIterator<Item = K>) -> Self {
+		self.info.requires_optional.extend(keys);
+		self
+	}
+
+	fn fallback_for(mut self, keys: impl IntoIterator<Item = K>) -> Self {
+		for key in keys {
+			self.info.provides.push(key.clone());
+			self.info.fallback_for.push(key);
+		}
+		self
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Progress, SetupTracker, test_utils::DoneSetupKey};
+
+	#[test]
+	fn test_incremental_provider() {
+		#[derive(Resource, Default)]
+		struct Slices(u32);
+
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<DoneSetupKey>::new(on_finished));
+		let system = (|mut slices: ResMut<Slices>| {
+			slices.0 += 1;
+			Progress::from_steps(slices.0 as usize, 4)
+		})
+		.provides_incrementally([DoneSetupKey::A])
+		.register(&mut world);
+		world.init_resource::<Slices>();
+		let tracker = world.resource::<SetupTracker<DoneSetupKey>>();
+		assert!(tracker.providers()[&system].reports_progress());
+		assert!(
+			tracker.providers()[&system]
+				.name()
+				.contains("test_incremental_provider")
+		);
+
+		// The key's own checker would report that it's already done
+		crate::advance_setup::<DoneSetupKey>(&mut world);
+		let tracker = world.resource::<SetupTracker<DoneSetupKey>>();
+		assert_eq!(tracker.last_progress(), Progress::new(0.25));
+		for _ in 0..4 {
+			crate::advance_setup::<DoneSetupKey>(&mut world);
+		}
+		assert_eq!(world.resource::<Slices>().0, 4);
+		let tracker = world.resource::<SetupTracker<DoneSetupKey>>();
+		assert_eq!(tracker.last_progress(), Progress::DONE);
+	}
+
+	#[test]
+	fn test_provider_metadata() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<DoneSetupKey>::new(on_finished));
+
+		let named = (|| {})
+			.provides([DoneSetupKey::A])
+			.named("Load terrain")
+			.description("Generates the terrain mesh")
+			.tags(["world", "slow"])
+			.register(&mut world);
+		let unnamed = (|| {}).requires([DoneSetupKey::A]).register(&mut world);
+
+		let tracker = world.resource::<SetupTracker<DoneSetupKey>>();
+		let info = &tracker.providers()[&named];
+		assert_eq!(info.name(), "Load terrain");
+		assert_eq!(info.description(), Some("Generates the terrain mesh"));
+		assert_eq!(info.tags(), ["world", "slow"]);
+
+		let info = &tracker.providers()[&unnamed];
+		assert!(info.name().starts_with("test_provider_metadata::"));
+		assert_eq!(info.description(), None);
+		assert!(info.tags().is_empty());
+	}
+
+	#[test]
+	fn test_name_formatter() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		let mut tracker = SetupTracker::<DoneSetupKey>::new(on_finished);
+		assert_eq!(tracker.format_name("a::b<c::D, é::Ü>"), "b<D, Ü>");
+
+		tracker.set_name_formatter(|name| name.to_uppercase());
+		world.insert_resource(tracker);
+		fn load_assets() {}
+		let system = load_assets.provides([DoneSetupKey::A]).register(&mut world);
+		let name = world.resource::<SetupTracker<DoneSetupKey>>().providers()[&system]
+			.name()
+			.to_owned();
+		assert!(name.ends_with("::LOAD_ASSETS"));
+	}
+
+	#[test]
+	fn test_register_providers_macro() {
+		let mut world = World::new();
+		let on_finished = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<DoneSetupKey>::new(on_finished));
+
+		fn load_a() {}
+		fn use_a() {}
+		crate::register_providers!(world, {
+			load_a => [] -> [DoneSetupKey::A] named "Load A",
+			use_a => [DoneSetupKey::A] -> [DoneSetupKey::B] weight 2.5,
+		});
+
+		let tracker = world.resource::<SetupTracker<DoneSetupKey>>();
+		let providers = tracker.providers().values().collect::<Vec<_>>();
+		assert_eq!(providers.len(), 2);
+		assert_eq!(providers[0].name(), "Load A");
+		assert_eq!(providers[0].provides(), [DoneSetupKey::A]);
+		assert_eq!(providers[1].requires(), [DoneSetupKey::A]);
+		assert_eq!(providers[1].weight(), Some(2.5));
+		assert_eq!(tracker.key_weight(&DoneSetupKey::B), 2.5);
+		assert_eq!(tracker.key_weight(&DoneSetupKey::A), 1.0);
+	}
 }
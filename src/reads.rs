@@ -0,0 +1,107 @@
+//! Change detection for progress checkers.
+//!
+//! By default every progress checker runs every time [`advance_setup`](crate::advance_setup) does,
+//! which adds up for graphs with thousands of keys. A key can declare what its checker reads with
+//! [`SetupKey::checker_reads`](crate::SetupKey::checker_reads), and its checker is then only
+//! re-run when one of those resources or components changed since the previous check:
+//!
+//! ```rust,ignore
+//! fn checker_reads(&self) -> Option<CheckerReads> {
+//!     Some(CheckerReads::new().resource::<LevelData>().component::<Terrain>())
+//! }
+//! ```
+//!
+//! Until it changes, the key keeps the progress its checker last returned. Declaring too little
+//! means the key gets stuck, so only declare reads for checkers that depend on nothing else, e.g.
+//! not on time or on the state of a background task.
+
+use bevy_ecs::{component::Tick, prelude::*};
+
+type ChangedSince = fn(&World, Tick, Tick) -> bool;
+
+/// The resources and components a progress checker reads. See the [module docs](self).
+#[derive(Debug, Clone, Default)]
+pub struct CheckerReads {
+	checks: Vec<ChangedSince>,
+}
+
+impl CheckerReads {
+	/// Creates an empty set of reads, so the checker only runs once.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Re-runs the checker when the resource `R` is inserted or changed.
+	///
+	/// Removing `R` isn't detected, since the world doesn't keep track of resource removals.
+	pub fn resource<R: Resource>(mut self) -> Self {
+		self.checks.push(|world, last_check, this_check| {
+			world
+				.get_resource_change_ticks::<R>()
+				.is_some_and(|ticks| ticks.is_changed(last_check, this_check))
+		});
+		self
+	}
+
+	/// Re-runs the checker when the component `C` is added to, changed on, or removed from any
+	/// entity.
+	pub fn component<C: Component>(mut self) -> Self {
+		self.checks.push(|world, last_check, this_check| {
+			if world.removed::<C>().next().is_some() {
+				return true;
+			}
+			world.try_query::<Ref<C>>().is_some_and(|query| {
+				query
+					.iter_manual(world)
+					.any(|c| c.last_changed().is_newer_than(last_check, this_check))
+			})
+		});
+		self
+	}
+
+	/// Whether anything read by the checker changed between `last_check` and `this_check`.
+	pub fn changed(&self, world: &World, last_check: Tick, this_check: Tick) -> bool {
+		self.checks
+			.iter()
+			.any(|changed| changed(world, last_check, this_check))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Resource)]
+	struct Level(u32);
+
+	#[derive(Component)]
+	struct Terrain;
+
+	#[test]
+	fn test_checker_reads() {
+		let mut world = World::new();
+		let reads = CheckerReads::new()
+			.resource::<Level>()
+			.component::<Terrain>();
+
+		let last_check = world.increment_change_tick();
+		assert!(!reads.changed(&world, last_check, world.read_change_tick()));
+
+		world.insert_resource(Level(0));
+		assert!(reads.changed(&world, last_check, world.read_change_tick()));
+
+		let last_check = world.increment_change_tick();
+		assert!(!reads.changed(&world, last_check, world.read_change_tick()));
+		world.resource_mut::<Level>().0 += 1;
+		assert!(reads.changed(&world, last_check, world.read_change_tick()));
+
+		let last_check = world.increment_change_tick();
+		let entity = world.spawn(Terrain).id();
+		assert!(reads.changed(&world, last_check, world.read_change_tick()));
+
+		let last_check = world.increment_change_tick();
+		assert!(!reads.changed(&world, last_check, world.read_change_tick()));
+		world.despawn(entity);
+		assert!(reads.changed(&world, last_check, world.read_change_tick()));
+	}
+}
@@ -0,0 +1,73 @@
+//! Setup state over the [Bevy Remote Protocol](bevy_remote), so external tools and editors can
+//! display the loading progress of a running game.
+//!
+//! Register [`setup_status`] as a method on the `RemotePlugin`:
+//!
+//! ```rust,ignore
+//! app.add_plugins(
+//!     RemotePlugin::default().with_method(SETUP_STATUS_METHOD, setup_status::<MySetupKey>),
+//! );
+//! ```
+//!
+//! Apps with more than one `SetupTracker` should register each key type under its own method
+//! name, e.g. `"bird_barrier/status/assets"`.
+
+use crate::{SetupKey, SetupTracker};
+use bevy_ecs::prelude::*;
+use bevy_remote::{BrpError, BrpResult};
+use disqualified::ShortName;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::Debug;
+
+/// The conventional method name for [`setup_status`].
+pub const SETUP_STATUS_METHOD: &str = "bird_barrier/status";
+
+/// A Bevy Remote Protocol method handler that returns the `SetupTracker<K>`'s
+/// [report](SetupTracker::report) as JSON.
+///
+/// Takes no parameters. Returns an error if the tracker doesn't exist, e.g. because it was
+/// [cleaned up](SetupTracker::cleanup) after setup finished.
+pub fn setup_status<K: SetupKey + Serialize + Debug>(
+	In(_params): In<Option<Value>>,
+	tracker: Option<Res<SetupTracker<K>>>,
+) -> BrpResult {
+	let Some(tracker) = tracker else {
+		return Err(BrpError::resource_error(format!(
+			"`SetupTracker<{}>` doesn't exist",
+			ShortName::of::<K>()
+		)));
+	};
+	serde_json::to_value(tracker.report()).map_err(BrpError::internal)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ProviderInfo, test_utils::DoneSetupKey};
+	use bevy_ecs::system::RunSystemOnce;
+	use std::borrow::Cow;
+
+	#[test]
+	fn test_setup_status() {
+		let mut world = World::new();
+		let result = world
+			.run_system_once_with(setup_status::<DoneSetupKey>, None)
+			.unwrap();
+		assert!(result.is_err());
+
+		let mut tracker = SetupTracker::<DoneSetupKey>::new(world.register_system(|| {}));
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(vec![], vec![DoneSetupKey::A], Cow::Borrowed("load_a")),
+			&mut world,
+		);
+		world.insert_resource(tracker);
+		let status = world
+			.run_system_once_with(setup_status::<DoneSetupKey>, None)
+			.unwrap()
+			.unwrap();
+		assert_eq!(status["providers"][0]["name"], "load_a");
+		assert_eq!(status["keys"][0]["key"], "A");
+	}
+}
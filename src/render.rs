@@ -0,0 +1,236 @@
+//! Progress tracking for render pipeline compilation.
+//!
+//! Pipelines are compiled asynchronously by the `PipelineCache` in the render world, so the first
+//! frame that draws a new material can hitch while its shaders compile. [`PipelineProgressPlugin`]
+//! mirrors the state of the cache into [`PipelineCompilationStatus`] in the main world each frame,
+//! which [`pipelines_ready_progress`] turns into a key's progress.
+//!
+//! Other GPU-side setup, such as warming up pipelines or uploading resources, can be tracked with a
+//! separate [`SetupTracker`] in the render app, added by [`RenderSetupPlugin`]. Its progress is
+//! mirrored into [`RenderSetupProgress`] in the main world, so a main app key using
+//! [`render_setup_progress`] as its checker bridges it into the main barrier:
+//!
+//! ```rust,ignore
+//! app.add_plugins(RenderSetupPlugin::<RenderSetup>::default());
+//! app.sub_app_mut(RenderApp)
+//!     .register_provider(warm_up_pipelines.provides([RenderSetup::Pipelines]));
+//!
+//! // In the main app's `SetupKey` implementation
+//! MySetupKey::Gpu => world.register_system(render_setup_progress::<RenderSetup>()),
+//! ```
+
+use crate::{Progress, SetupKey, SetupSet, SetupTracker, advance_setup};
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_render::render_resource::{CachedPipelineState, PipelineCache, PipelineCacheError};
+use bevy_render::{ExtractSchedule, MainWorld, Render, RenderApp, RenderSet};
+use std::marker::PhantomData;
+
+/// Plugin that keeps [`PipelineCompilationStatus`] up to date.
+///
+/// Must be added after `RenderPlugin`. Without a render app (e.g. in headless apps), the status
+/// stays empty, so [`pipelines_ready_progress`] reports setup as done.
+pub struct PipelineProgressPlugin;
+
+impl Plugin for PipelineProgressPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<PipelineCompilationStatus>();
+	}
+
+	fn finish(&self, app: &mut App) {
+		if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+			render_app.add_systems(ExtractSchedule, sync_pipeline_status);
+		}
+	}
+}
+
+/// The number of render pipelines in each compilation state, as of the last extraction.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PipelineCompilationStatus {
+	/// Pipelines that are queued or still being created.
+	pub pending: usize,
+	/// Pipelines that have been created successfully.
+	pub ready: usize,
+	/// Pipelines that failed to compile and will not be retried.
+	pub failed: usize,
+}
+
+impl PipelineCompilationStatus {
+	/// Total number of pipelines the `PipelineCache` knows about.
+	pub fn total(&self) -> usize {
+		self.pending + self.ready + self.failed
+	}
+
+	/// Progress of compiling every pipeline.
+	///
+	/// Any failed pipeline makes progress non-finite. With no pipelines at all, returns
+	/// [`Progress::DONE`].
+	pub fn progress(&self) -> Progress {
+		if self.failed > 0 {
+			Progress::new(f32::NAN)
+		} else if self.total() == 0 {
+			Progress::DONE
+		} else {
+			Progress::new(self.ready as f32 / self.total() as f32)
+		}
+	}
+}
+
+fn sync_pipeline_status(cache: Res<PipelineCache>, mut main_world: ResMut<MainWorld>) {
+	let mut status = PipelineCompilationStatus::default();
+	for pipeline in cache.pipelines() {
+		match &pipeline.state {
+			CachedPipelineState::Ok(_) => status.ready += 1,
+			// The cache retries these once the missing shader or import is loaded.
+			CachedPipelineState::Err(
+				PipelineCacheError::ShaderNotLoaded(_)
+				| PipelineCacheError::ShaderImportNotYetAvailable,
+			) => status.pending += 1,
+			CachedPipelineState::Err(_) => status.failed += 1,
+			CachedPipelineState::Queued | CachedPipelineState::Creating(_) => status.pending += 1,
+		}
+	}
+	main_world.insert_resource(status);
+}
+
+/// Progress checker for every pipeline queued in the `PipelineCache` having compiled.
+///
+/// Requires [`PipelineProgressPlugin`]. Pipelines are only queued once something that needs them
+/// is spawned, so a key using this checker should require the keys that spawn the scene,
+/// otherwise it may report [`Progress::DONE`] before any pipelines exist.
+pub fn pipelines_ready_progress() -> impl System<In = (), Out = Progress> {
+	IntoSystem::into_system(|status: Option<Res<PipelineCompilationStatus>>| {
+		status.map_or(Progress::ZERO, |status| status.progress())
+	})
+}
+
+/// Plugin that runs a `SetupTracker<K>` in the render app, and keeps [`RenderSetupProgress<K>`]
+/// in the main world up to date with its progress.
+///
+/// Must be added after `RenderPlugin`, so that providers can be registered with the render app
+/// while building other plugins. [`advance_setup`] runs in [`RenderSet::Prepare`]. Without a
+/// render app, the progress is [`Progress::DONE`].
+pub struct RenderSetupPlugin<K: SetupKey>(PhantomData<fn() -> K>);
+
+impl<K: SetupKey> Default for RenderSetupPlugin<K> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<K: SetupKey> Plugin for RenderSetupPlugin<K> {
+	fn build(&self, app: &mut App) {
+		let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+			app.insert_resource(RenderSetupProgress::<K>::new(Progress::DONE));
+			return;
+		};
+		let on_finished = render_app.world_mut().register_system(|| {});
+		render_app
+			.insert_resource(SetupTracker::<K>::new(on_finished))
+			.add_systems(
+				Render,
+				advance_setup::<K>
+					.in_set(RenderSet::Prepare)
+					.in_set(SetupSet::<K>::Advance),
+			)
+			.add_systems(ExtractSchedule, sync_render_setup_progress::<K>);
+		app.insert_resource(RenderSetupProgress::<K>::new(Progress::ZERO));
+	}
+}
+
+/// The progress of the render app's `SetupTracker<K>`, as of the last extraction.
+///
+/// See [`RenderSetupPlugin`].
+#[derive(Resource, Debug)]
+pub struct RenderSetupProgress<K: SetupKey> {
+	/// Overall progress of the render app's tracker.
+	pub progress: Progress,
+	_marker: PhantomData<fn() -> K>,
+}
+
+impl<K: SetupKey> RenderSetupProgress<K> {
+	fn new(progress: Progress) -> Self {
+		Self {
+			progress,
+			_marker: PhantomData,
+		}
+	}
+}
+
+fn sync_render_setup_progress<K: SetupKey>(
+	tracker: Option<Res<SetupTracker<K>>>,
+	mut main_world: ResMut<MainWorld>,
+) {
+	// The tracker may have been cleaned up once finished
+	let progress = tracker.map_or(Progress::DONE, |tracker| tracker.last_progress());
+	main_world.insert_resource(RenderSetupProgress::<K>::new(progress));
+}
+
+/// Progress checker that mirrors the progress of the render app's `SetupTracker<K>`.
+///
+/// Requires [`RenderSetupPlugin<K>`].
+pub fn render_setup_progress<K: SetupKey>() -> impl System<In = (), Out = Progress> {
+	IntoSystem::into_system(|status: Option<Res<RenderSetupProgress<K>>>| {
+		status.map_or(Progress::ZERO, |status| status.progress)
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{IntoDependencyProvider, RegisterProvider};
+
+	#[test]
+	fn test_pipeline_status_progress() {
+		assert_eq!(
+			PipelineCompilationStatus::default().progress(),
+			Progress::DONE
+		);
+
+		let status = PipelineCompilationStatus {
+			pending: 3,
+			ready: 1,
+			failed: 0,
+		};
+		assert_eq!(status.progress(), Progress::new(0.25));
+
+		let status = PipelineCompilationStatus {
+			failed: 1,
+			..status
+		};
+		assert!(!status.progress().is_finite());
+	}
+
+	#[test]
+	fn test_render_setup_progress() {
+		#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+		struct RenderSetup;
+
+		impl SetupKey for RenderSetup {
+			fn register_progress_checker(
+				&self,
+				world: &mut World,
+			) -> bevy_ecs::system::SystemId<(), Progress> {
+				world.register_system(|| Progress::DONE)
+			}
+		}
+
+		let mut render_world = World::new();
+		render_world.init_resource::<MainWorld>();
+		let on_finished = render_world.register_system(|| {});
+		render_world.insert_resource(SetupTracker::<RenderSetup>::new(on_finished));
+		render_world.register_provider((|| {}).provides([RenderSetup]));
+		let sync = render_world.register_system(sync_render_setup_progress::<RenderSetup>);
+
+		render_world.run_system(sync).unwrap();
+		let mut main_world = render_world.remove_resource::<MainWorld>().unwrap();
+		let checker = main_world.register_system(render_setup_progress::<RenderSetup>());
+		assert_eq!(main_world.run_system(checker).unwrap(), Progress::ZERO);
+
+		render_world.insert_resource(main_world);
+		advance_setup::<RenderSetup>(&mut render_world);
+		render_world.run_system(sync).unwrap();
+		let mut main_world = render_world.remove_resource::<MainWorld>().unwrap();
+		assert_eq!(main_world.run_system(checker).unwrap(), Progress::DONE);
+	}
+}
@@ -0,0 +1,162 @@
+//! Serializable snapshots of a tracker's state, for CI artifacts, external dashboards, and bug
+//! reports.
+
+use crate::{SetupKey, SetupTracker};
+use serde::Serialize;
+use std::fmt::Debug;
+
+/// A snapshot of a [`SetupTracker`], built by [`SetupTracker::report`].
+///
+/// Progress is as of the last time [`advance_setup`](crate::advance_setup) ran, and durations are
+/// in seconds.
+#[derive(Serialize, Debug, Clone)]
+#[serde(bound = "K: Serialize")]
+pub struct SetupReport<K: SetupKey> {
+	/// Overall progress, from 0 to 1.
+	pub progress: f32,
+	/// Whether setup has finished.
+	pub finished: bool,
+	/// Every setup key, in registration order.
+	pub keys: Vec<KeyReport<K>>,
+	/// Every provider, in registration order.
+	pub providers: Vec<ProviderReport<K>>,
+}
+
+/// The state of a single setup key in a [`SetupReport`].
+#[derive(Serialize, Debug, Clone)]
+#[serde(bound = "K: Serialize")]
+pub struct KeyReport<K: SetupKey> {
+	/// The key itself.
+	pub key: K,
+	/// See [`SetupTracker::key_label`].
+	pub label: String,
+	/// The key's progress, or `None` if it hasn't been checked yet.
+	pub progress: Option<f32>,
+	/// See [`SetupKey::group`].
+	pub group: Option<String>,
+	/// See [`SetupTracker::key_weight`].
+	pub weight: f32,
+	/// See [`SetupTracker::is_failed`].
+	pub failed: bool,
+	/// See [`SetupTracker::stage_of_key`].
+	pub stage: Option<usize>,
+	/// See [`SetupTracker::key_elapsed`].
+	pub elapsed: Option<f32>,
+}
+
+/// The state of a single provider in a [`SetupReport`].
+#[derive(Serialize, Debug, Clone)]
+#[serde(bound = "K: Serialize")]
+pub struct ProviderReport<K: SetupKey> {
+	/// The provider's name.
+	pub name: String,
+	/// The provider's description, if any.
+	pub description: Option<String>,
+	/// The provider's tags.
+	pub tags: Vec<String>,
+	/// Keys the provider requires.
+	pub requires: Vec<K>,
+	/// Keys the provider waits for only if something provides them.
+	pub requires_optional: Vec<K>,
+	/// Keys the provider provides.
+	pub provides: Vec<K>,
+	/// The stage the provider runs in, or `None` if it can never run.
+	pub stage: Option<usize>,
+	/// See [`ProviderRunStats::runs`](crate::ProviderRunStats::runs).
+	pub runs: u32,
+	/// See [`ProviderRunStats::last_duration`](crate::ProviderRunStats::last_duration).
+	pub last_run: Option<f32>,
+	/// See [`ProviderRunStats::last_error`](crate::ProviderRunStats::last_error).
+	pub last_error: Option<String>,
+}
+
+impl<K: SetupKey + Debug> SetupTracker<K> {
+	/// Builds a serializable snapshot of the tracker's keys, providers, and their progress.
+	pub fn report(&self) -> SetupReport<K> {
+		let stages = self.stage_indices();
+		let keys = self
+			.entries
+			.keys()
+			.map(|key| KeyReport {
+				key: key.clone(),
+				label: self.key_label(key).into_owned(),
+				progress: self.last_key_progress(key).map(|progress| *progress),
+				group: key.group().map(|group| group.into_owned()),
+				weight: self.key_weight(key),
+				failed: self.is_failed(key),
+				stage: self.stage_of_key(key),
+				elapsed: self.key_elapsed(key).map(|elapsed| elapsed.as_secs_f32()),
+			})
+			.collect();
+		let providers = self
+			.providers
+			.iter()
+			.map(|(system, info)| {
+				let stats = self.run_stats(*system);
+				ProviderReport {
+					name: info.name().to_owned(),
+					description: info.description().map(ToOwned::to_owned),
+					tags: info.tags().iter().map(|tag| tag.to_string()).collect(),
+					requires: info.requires().to_vec(),
+					requires_optional: info.requires_optional().to_vec(),
+					provides: info.provides().to_vec(),
+					stage: stages.get(system).copied(),
+					runs: stats.map_or(0, |stats| stats.runs()),
+					last_run: stats.map(|stats| stats.last_duration().as_secs_f32()),
+					last_error: stats.and_then(|stats| stats.last_error().map(ToOwned::to_owned)),
+				}
+			})
+			.collect();
+		SetupReport {
+			progress: *self.last_progress,
+			finished: self.last_progress.finished(),
+			keys,
+			providers,
+		}
+	}
+}
+
+impl<K: SetupKey + Serialize> SetupReport<K> {
+	/// Serializes the report as pretty-printed JSON.
+	pub fn to_json(&self) -> String {
+		serde_json::to_string_pretty(self).expect("setup reports should always serialize")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ProviderInfo, test_utils::DoneSetupKey};
+	use bevy_ecs::world::World;
+	use std::borrow::Cow;
+
+	#[test]
+	fn test_report() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<DoneSetupKey>::new(world.register_system(|| {}));
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(vec![], vec![DoneSetupKey::A], Cow::Borrowed("load_a")),
+			&mut world,
+		);
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(
+				vec![DoneSetupKey::A],
+				vec![DoneSetupKey::B],
+				Cow::Borrowed("load_b"),
+			),
+			&mut world,
+		);
+
+		let report = tracker.report();
+		assert_eq!(report.keys.len(), 2);
+		assert_eq!(report.keys[1].stage, Some(1));
+		assert_eq!(report.providers[1].requires, [DoneSetupKey::A]);
+
+		let json = serde_json::from_str::<serde_json::Value>(&report.to_json()).unwrap();
+		assert_eq!(json["providers"][0]["name"], "load_a");
+		assert_eq!(json["keys"][0]["key"], "A");
+		assert_eq!(json["keys"][0]["progress"], serde_json::Value::Null);
+	}
+}
@@ -0,0 +1,80 @@
+//! Progress tracking for spawning scenes.
+//!
+//! A scene asset being loaded doesn't mean it has been spawned yet. The `SceneSpawner` instantiates
+//! scenes later, once every asset they reference has loaded, so these checkers wait for the
+//! instance itself to be ready.
+
+use crate::Progress;
+use bevy_asset::{AssetId, AssetServer};
+use bevy_ecs::prelude::*;
+use bevy_scene::{InstanceId, Scene, SceneInstance, SceneRoot, SceneSpawner};
+
+/// Progress checker for every [`SceneRoot`] with the given scene having been fully instantiated.
+///
+/// Progress is the fraction of matching `SceneRoot` entities whose instance is ready. Returns
+/// [`Progress::ZERO`] until at least one such entity exists, and non-finite progress if the scene
+/// failed to load.
+pub fn scene_spawned_progress(
+	scene: impl Into<AssetId<Scene>>,
+) -> impl System<In = (), Out = Progress> {
+	let scene = scene.into();
+	IntoSystem::into_system(
+		move |roots: Query<(&SceneRoot, Option<&SceneInstance>)>,
+		      spawner: Option<Res<SceneSpawner>>,
+		      server: Option<Res<AssetServer>>| {
+			if server
+				.and_then(|server| server.get_load_state(scene))
+				.is_some_and(|state| state.is_failed())
+			{
+				return Progress::new(f32::NAN);
+			}
+			let (mut total, mut ready) = (0, 0);
+			for (_, instance) in roots.iter().filter(|(root, _)| root.0.id() == scene) {
+				total += 1;
+				if let (Some(instance), Some(spawner)) = (instance, &spawner) {
+					ready += spawner.instance_is_ready(**instance) as usize;
+				}
+			}
+			if total == 0 {
+				Progress::ZERO
+			} else {
+				Progress::new(ready as f32 / total as f32)
+			}
+		},
+	)
+}
+
+/// Progress checker for a scene instance spawned directly with [`SceneSpawner::spawn`].
+pub fn scene_instance_progress(instance: InstanceId) -> impl System<In = (), Out = Progress> {
+	IntoSystem::into_system(move |spawner: Option<Res<SceneSpawner>>| {
+		spawner
+			.is_some_and(|spawner| spawner.instance_is_ready(instance))
+			.into()
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bevy_asset::Handle;
+	use bevy_ecs::system::RunSystemOnce;
+
+	#[test]
+	fn test_scene_progress_before_spawn() {
+		let mut world = World::new();
+		let mut spawner = SceneSpawner::default();
+		let instance = spawner.spawn(Handle::<Scene>::default());
+		world.insert_resource(spawner);
+		world.spawn(SceneRoot(Handle::default()));
+
+		let progress = world
+			.run_system_once(scene_instance_progress(instance))
+			.unwrap();
+		assert_eq!(progress, Progress::ZERO);
+
+		let progress = world
+			.run_system_once(scene_spawned_progress(&Handle::<Scene>::default()))
+			.unwrap();
+		assert_eq!(progress, Progress::ZERO);
+	}
+}
@@ -0,0 +1,292 @@
+//! Serializable snapshots of setup progress.
+//!
+//! [`SetupProgressSnapshot`]s are compact, for sending setup progress over the network, e.g. so a
+//! host can show each client's loading percentage in a lobby. Keys are sent once as a
+//! [`SetupKeyIndex`], after which each snapshot refers to them by their position in the index.
+//! [`ProgressSnapshotPlugin`] sends a snapshot as a buffered event whenever progress changes, for
+//! networking code to read with an `EventReader` and forward.
+//!
+//! [`SetupSnapshot`]s record which keys have finished, so work that is cached between runs, such
+//! as warming a shader cache or pre-generating a world, can be skipped on the next launch.
+
+use crate::{Progress, SetupKey, SetupSet, SetupTracker};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// Every key of a `SetupTracker<K>`, in registration order, built by
+/// [`SetupTracker::key_index`].
+///
+/// Registering or removing keys changes the index, so it must be sent again afterwards.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(bound = "K: Serialize + DeserializeOwned")]
+pub struct SetupKeyIndex<K: SetupKey> {
+	/// The keys, in the order snapshots refer to them.
+	pub keys: Vec<K>,
+}
+
+impl<K: SetupKey> SetupKeyIndex<K> {
+	/// Returns the key at `index`, if any.
+	pub fn get(&self, index: u16) -> Option<&K> {
+		self.keys.get(index as usize)
+	}
+
+	/// Returns the progress of every key in `snapshot`, in index order.
+	///
+	/// Returns `None` if the snapshot was taken with a different number of keys than this index
+	/// has, in which case an up-to-date index is needed.
+	pub fn resolve(&self, snapshot: &SetupProgressSnapshot<K>) -> Option<Vec<(&K, Progress)>> {
+		if snapshot.key_count as usize != self.keys.len() {
+			return None;
+		}
+		let mut resolved = self
+			.keys
+			.iter()
+			.map(|key| (key, Progress::DONE))
+			.collect::<Vec<_>>();
+		for &(index, progress) in &snapshot.pending {
+			resolved.get_mut(index as usize)?.1 = Progress::new(progress);
+		}
+		Some(resolved)
+	}
+}
+
+/// The progress of a `SetupTracker<K>` at one point in time, built by
+/// [`SetupTracker::progress_snapshot`].
+///
+/// Only unfinished keys are listed, so snapshots shrink as setup goes on. Keys are referred to by
+/// their index in the tracker's [`SetupKeyIndex`].
+#[derive(Event, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(bound = "")]
+pub struct SetupProgressSnapshot<K: SetupKey> {
+	/// Overall progress, from 0 to 1.
+	pub progress: f32,
+	/// The number of keys in the tracker, to detect an outdated [`SetupKeyIndex`].
+	pub key_count: u16,
+	/// The index and progress of every key that hasn't finished yet. Keys that haven't been
+	/// checked yet are listed with zero progress.
+	pub pending: Vec<(u16, f32)>,
+	#[serde(skip)]
+	_marker: PhantomData<fn() -> K>,
+}
+
+impl<K: SetupKey> SetupProgressSnapshot<K> {
+	/// Overall progress.
+	pub fn progress(&self) -> Progress {
+		Progress::new(self.progress)
+	}
+
+	/// Whether setup had finished when the snapshot was taken.
+	pub fn finished(&self) -> bool {
+		self.progress().finished()
+	}
+}
+
+impl<K: SetupKey> SetupTracker<K> {
+	/// Builds the index that [`progress_snapshot`](Self::progress_snapshot)s refer to keys by.
+	pub fn key_index(&self) -> SetupKeyIndex<K> {
+		SetupKeyIndex {
+			keys: self.entries.keys().cloned().collect(),
+		}
+	}
+
+	/// Builds a compact snapshot of the tracker's progress, as of the last time
+	/// [`advance_setup`](crate::advance_setup) ran.
+	///
+	/// # Panics
+	///
+	/// Panics if the tracker has more than `u16::MAX` keys.
+	pub fn progress_snapshot(&self) -> SetupProgressSnapshot<K> {
+		let key_count = u16::try_from(self.entries.len())
+			.expect("progress snapshots support at most `u16::MAX` keys");
+		let pending = self
+			.entries
+			.keys()
+			.enumerate()
+			.filter_map(|(index, key)| {
+				let progress = self.last_key_progress(key).unwrap_or(Progress::ZERO);
+				(!progress.finished()).then_some((index as u16, *progress))
+			})
+			.collect();
+		SetupProgressSnapshot {
+			progress: *self.last_progress,
+			key_count,
+			pending,
+			_marker: PhantomData,
+		}
+	}
+}
+
+/// The keys of a `SetupTracker<K>` that had finished, built by [`SetupTracker::snapshot`] and
+/// applied with [`SetupTracker::restore`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(bound = "K: Serialize + DeserializeOwned")]
+pub struct SetupSnapshot<K: SetupKey> {
+	/// The finished keys, in registration order.
+	pub completed: Vec<K>,
+}
+
+impl<K: SetupKey> SetupTracker<K> {
+	/// Builds a snapshot of which keys have finished, to be saved and
+	/// [restored](Self::restore) on a later run.
+	///
+	/// Keys count as finished once [`OnSetupKeyComplete`](crate::OnSetupKeyComplete) has been
+	/// triggered for them, or if they were restored and haven't been invalidated since.
+	pub fn snapshot(&self) -> SetupSnapshot<K> {
+		SetupSnapshot {
+			completed: self
+				.entries
+				.keys()
+				.filter(|key| {
+					!self.invalidated.contains(*key)
+						&& (self.completed.contains_key(*key) || self.restored.contains(*key))
+				})
+				.cloned()
+				.collect(),
+		}
+	}
+
+	/// Marks the keys in `snapshot` as finished without checking their progress, so their
+	/// providers don't run.
+	///
+	/// Only restore a snapshot if whatever the keys represent is still valid, e.g. if a cache
+	/// they filled still exists and matches the current version of the game. Restored keys can be
+	/// [invalidated](Self::invalidate) to run their providers after all. Keys that aren't part of
+	/// the setup graph are ignored.
+	///
+	/// Returns the number of keys restored.
+	pub fn restore(&mut self, snapshot: SetupSnapshot<K>) -> usize {
+		let mut restored = 0;
+		for key in snapshot.completed {
+			if self.entries.contains_key(&key) {
+				self.invalidated.remove(&key);
+				self.failed.remove(&key);
+				self.restored.insert(key);
+				restored += 1;
+			}
+		}
+		restored
+	}
+}
+
+/// Plugin that sends a [`SetupProgressSnapshot<K>`] event every time the progress of the
+/// `SetupTracker<K>` changes.
+pub struct ProgressSnapshotPlugin<K: SetupKey>(PhantomData<K>);
+
+impl<K: SetupKey> Default for ProgressSnapshotPlugin<K> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<K: SetupKey> Plugin for ProgressSnapshotPlugin<K> {
+	fn build(&self, app: &mut App) {
+		app.add_event::<SetupProgressSnapshot<K>>().add_systems(
+			Update,
+			send_progress_snapshots::<K>
+				.after(SetupSet::<K>::Advance)
+				.run_if(resource_exists::<SetupTracker<K>>),
+		);
+	}
+}
+
+fn send_progress_snapshots<K: SetupKey>(
+	tracker: Res<SetupTracker<K>>,
+	mut last: Local<Option<SetupProgressSnapshot<K>>>,
+	mut events: EventWriter<SetupProgressSnapshot<K>>,
+) {
+	let snapshot = tracker.progress_snapshot();
+	if last.as_ref() != Some(&snapshot) {
+		events.write(snapshot.clone());
+		*last = Some(snapshot);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ProviderInfo, advance_setup, test_utils::DoneSetupKey};
+	use std::borrow::Cow;
+
+	#[test]
+	fn test_progress_snapshot() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<DoneSetupKey>::new(world.register_system(|| {}));
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(
+				vec![],
+				vec![DoneSetupKey::A, DoneSetupKey::B],
+				Cow::Borrowed("load"),
+			),
+			&mut world,
+		);
+		tracker
+			.last_key_progress
+			.insert(DoneSetupKey::A, Progress::DONE);
+		tracker
+			.last_key_progress
+			.insert(DoneSetupKey::B, Progress::new(0.5));
+		tracker.last_progress = Progress::new(0.75);
+
+		let snapshot = tracker.progress_snapshot();
+		assert_eq!(snapshot.pending, vec![(1, 0.5)]);
+
+		let json = serde_json::to_string(&snapshot).unwrap();
+		let snapshot = serde_json::from_str::<SetupProgressSnapshot<DoneSetupKey>>(&json).unwrap();
+		let index = serde_json::from_str::<SetupKeyIndex<DoneSetupKey>>(
+			&serde_json::to_string(&tracker.key_index()).unwrap(),
+		)
+		.unwrap();
+		assert_eq!(
+			index.resolve(&snapshot).unwrap(),
+			vec![
+				(&DoneSetupKey::A, Progress::DONE),
+				(&DoneSetupKey::B, Progress::new(0.5)),
+			]
+		);
+		assert_eq!(snapshot.progress(), Progress::new(0.75));
+
+		let outdated = SetupKeyIndex {
+			keys: vec![DoneSetupKey::A],
+		};
+		assert!(outdated.resolve(&snapshot).is_none());
+	}
+
+	#[test]
+	fn test_snapshot_restore() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<DoneSetupKey>::new(world.register_system(|| {}));
+		tracker.register_provider(
+			world.register_system(|| panic!("restored keys shouldn't be provided again")),
+			ProviderInfo::new(
+				vec![],
+				vec![DoneSetupKey::A, DoneSetupKey::B],
+				Cow::Borrowed("load"),
+			),
+			&mut world,
+		);
+		tracker
+			.completed
+			.insert(DoneSetupKey::B, bevy_platform::time::Instant::now());
+		let snapshot = tracker.snapshot();
+		assert_eq!(snapshot.completed, [DoneSetupKey::B]);
+
+		let json = serde_json::to_string(&SetupSnapshot {
+			completed: vec![DoneSetupKey::A, DoneSetupKey::B],
+		})
+		.unwrap();
+		let snapshot = serde_json::from_str::<SetupSnapshot<DoneSetupKey>>(&json).unwrap();
+		assert_eq!(tracker.restore(snapshot), 2);
+		world.insert_resource(tracker);
+		advance_setup::<DoneSetupKey>(&mut world);
+		assert!(
+			world
+				.resource::<SetupTracker<DoneSetupKey>>()
+				.last_progress()
+				.finished()
+		);
+	}
+}
@@ -0,0 +1,351 @@
+//! Progress reported by ordinary systems, instead of checked by [`advance_setup`].
+//!
+//! [`advance_setup`] is an exclusive system, so progress checkers registered as one-shot systems
+//! run one after another while the rest of the schedule waits. For keys whose progress is
+//! expensive to check, return [`ProgressChecker::Reported`](crate::ProgressChecker::Reported)
+//! from [`SetupKey::progress_checker`](crate::SetupKey::progress_checker), and check their
+//! progress in regular systems in [`SetupSet::Report`](crate::SetupSet::Report) instead. Their
+//! reports are buffered like commands and written to the [`SetupProgressStore`] before
+//! [`advance_setup`] runs, so Bevy can run them in parallel with each other and with the rest of
+//! the app, and [`advance_setup`] only has to look up the results:
+//!
+//! ```rust,ignore
+//! fn check_terrain(chunks: Query<&Chunk>, mut progress: ReportProgress<MySetupKey>) {
+//!     let done = chunks.iter().filter(|chunk| chunk.meshed).count();
+//!     progress.report(MySetupKey::Terrain, Progress::from_steps(done, TOTAL_CHUNKS));
+//! }
+//!
+//! app.add_systems(Update, check_terrain.in_set(SetupSet::<MySetupKey>::Report));
+//! ```
+//!
+//! Keys that haven't been reported yet have [`Progress::ZERO`].
+//!
+//! Providers can leave [`advance_setup`] as well: with
+//! [`Provider::in_schedule`](crate::Provider::in_schedule), it only dispatches them, and they run
+//! as regular systems in [`SetupSet::Provide`](crate::SetupSet::Provide).
+//!
+//! [`advance_setup`]: crate::advance_setup
+
+use crate::{Progress, SetupKey};
+use bevy_ecs::system::{Deferred, SystemBuffer, SystemMeta, SystemParam};
+use bevy_ecs::{
+	prelude::*,
+	world::{DeferredWorld, FromWorld},
+};
+use bevy_platform::collections::HashMap;
+
+/// Progress reported for keys checked with
+/// [`ProgressChecker::Reported`](crate::ProgressChecker::Reported). See the [module docs](self).
+///
+/// Inserted by [`SetupTrackingPlugin`](crate::SetupTrackingPlugin).
+#[derive(Resource, Debug)]
+pub struct SetupProgressStore<K: SetupKey> {
+	progress: HashMap<K, Progress>,
+}
+
+impl<K: SetupKey> Default for SetupProgressStore<K> {
+	fn default() -> Self {
+		Self {
+			progress: Default::default(),
+		}
+	}
+}
+
+impl<K: SetupKey> SetupProgressStore<K> {
+	/// Sets the progress of `key`, replacing any previously reported progress.
+	pub fn report(&mut self, key: K, progress: impl Into<Progress>) {
+		self.progress.insert(key, progress.into());
+	}
+
+	/// Returns the last progress reported for `key`, if any.
+	pub fn get(&self, key: &K) -> Option<Progress> {
+		self.progress.get(key).copied()
+	}
+
+	/// Forgets the progress reported for `key`, so it counts as [`Progress::ZERO`] until it's
+	/// reported again.
+	pub fn clear(&mut self, key: &K) -> Option<Progress> {
+		self.progress.remove(key)
+	}
+}
+
+/// A [`SystemParam`] for reporting progress to the [`SetupProgressStore`].
+///
+/// Reports are buffered in the system and written to the store when its deferred changes are
+/// applied, like [`Commands`], so systems reporting progress don't conflict with each other or
+/// with systems reading the store. Bevy applies them before [`SetupSet::Advance`] for systems in
+/// [`SetupSet::Report`].
+///
+/// Reports are dropped if the store doesn't exist, e.g. after the tracker was
+/// [cleaned up](crate::SetupTracker::cleanup).
+///
+/// [`SetupSet::Advance`]: crate::SetupSet::Advance
+/// [`SetupSet::Report`]: crate::SetupSet::Report
+#[derive(SystemParam)]
+pub struct ReportProgress<'s, K: SetupKey> {
+	reports: Deferred<'s, ProgressReports<K>>,
+}
+
+impl<K: SetupKey> ReportProgress<'_, K> {
+	/// Sets the progress of `key` once this system's deferred changes are applied. See
+	/// [`SetupProgressStore::report`].
+	pub fn report(&mut self, key: K, progress: impl Into<Progress>) {
+		self.reports.0.push((key, progress.into()));
+	}
+}
+
+/// The reports buffered by a [`ReportProgress`] param.
+#[doc(hidden)]
+pub struct ProgressReports<K: SetupKey>(Vec<(K, Progress)>);
+
+impl<K: SetupKey> FromWorld for ProgressReports<K> {
+	fn from_world(_world: &mut World) -> Self {
+		Self(Vec::new())
+	}
+}
+
+impl<K: SetupKey> ProgressReports<K> {
+	fn write(reports: impl IntoIterator<Item = (K, Progress)>, world: &mut World) {
+		if let Some(mut store) = world.get_resource_mut::<SetupProgressStore<K>>() {
+			for (key, progress) in reports {
+				store.report(key, progress);
+			}
+		}
+	}
+}
+
+impl<K: SetupKey> SystemBuffer for ProgressReports<K> {
+	fn apply(&mut self, _system_meta: &SystemMeta, world: &mut World) {
+		if !self.0.is_empty() {
+			Self::write(self.0.drain(..), world);
+		}
+	}
+
+	fn queue(&mut self, _system_meta: &SystemMeta, mut world: DeferredWorld) {
+		// One-shot systems, such as providers run by `advance_setup`, queue their buffers instead
+		// of applying them, and the commands are applied right after they run
+		if !self.0.is_empty() {
+			let reports = std::mem::take(&mut self.0);
+			world
+				.commands()
+				.queue(move |world: &mut World| Self::write(reports, world));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		IntoDependencyProvider, ProgressChecker, RegisterProvider, SetupIncomplete, SetupSet,
+		SetupTracker, SetupTrackingPlugin,
+	};
+	use bevy_app::{App, TaskPoolPlugin, Update};
+	use bevy_ecs::system::SystemId;
+	use std::time::Duration;
+
+	#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+	enum TestSetupKey {
+		A,
+		B,
+	}
+
+	impl SetupKey for TestSetupKey {
+		fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+			world.register_system(|| Progress::ZERO)
+		}
+
+		fn progress_checker(&self, _: &mut World) -> ProgressChecker<Self> {
+			ProgressChecker::Reported
+		}
+	}
+
+	#[derive(Resource, Default)]
+	struct Frames(u32);
+
+	#[test]
+	fn test_reported_progress() {
+		let mut app = App::new();
+		app.add_plugins(SetupTrackingPlugin::<TestSetupKey, _, _, _, _>::new(
+			|| true,
+			|| {},
+		))
+		.init_resource::<Frames>()
+		.add_systems(
+			Update,
+			(
+				|mut frames: ResMut<Frames>| frames.0 += 1,
+				|frames: Res<Frames>, mut progress: ReportProgress<TestSetupKey>| {
+					progress.report(TestSetupKey::A, frames.0 >= 2);
+				},
+				|mut progress: ReportProgress<TestSetupKey>| {
+					progress.report(TestSetupKey::B, Progress::DONE);
+				},
+			)
+				.chain()
+				.in_set(SetupSet::<TestSetupKey>::Report),
+		)
+		.register_provider((|| {}).provides([TestSetupKey::A, TestSetupKey::B]));
+
+		app.update();
+		let tracker = app.world().resource::<SetupTracker<TestSetupKey>>();
+		assert_eq!(tracker.last_progress(), Progress::new(0.5));
+
+		app.update();
+		let tracker = app.world().resource::<SetupTracker<TestSetupKey>>();
+		assert_eq!(tracker.last_progress(), Progress::DONE);
+	}
+
+	#[test]
+	fn test_scheduled_provider() {
+		let mut app = App::new();
+		app.add_plugins(SetupTrackingPlugin::<TestSetupKey, _, _, _, _>::new(
+			|| true,
+			|| {},
+		))
+		.init_resource::<Frames>()
+		.register_provider(
+			(|mut frames: ResMut<Frames>, mut progress: ReportProgress<TestSetupKey>| {
+				frames.0 += 1;
+				progress.report(TestSetupKey::A, Progress::DONE);
+			})
+			.provides([TestSetupKey::A])
+			.in_schedule(Update),
+		)
+		.register_provider(
+			(|mut progress: ReportProgress<TestSetupKey>| {
+				progress.report(TestSetupKey::B, Progress::DONE);
+			})
+			.requires([TestSetupKey::A])
+			.provides([TestSetupKey::B])
+			.in_schedule(Update),
+		);
+
+		// Providers run after `advance_setup`, so their reports are seen the next frame
+		app.update();
+		assert_eq!(app.world().resource::<Frames>().0, 1);
+		let tracker = app.world().resource::<SetupTracker<TestSetupKey>>();
+		assert_eq!(tracker.last_progress(), Progress::ZERO);
+
+		app.update();
+		let tracker = app.world().resource::<SetupTracker<TestSetupKey>>();
+		assert_eq!(tracker.last_progress(), Progress::new(0.5));
+
+		app.update();
+		let tracker = app.world().resource::<SetupTracker<TestSetupKey>>();
+		assert_eq!(tracker.last_progress(), Progress::DONE);
+		assert_eq!(app.world().resource::<Frames>().0, 1);
+	}
+
+	#[test]
+	fn test_scheduled_provider_run_stats() {
+		#[derive(Resource)]
+		struct Missing;
+
+		let mut app = App::new();
+		app.add_plugins(SetupTrackingPlugin::<TestSetupKey, _, _, _, _>::new(
+			|| true,
+			|| {},
+		));
+		app.world_mut()
+			.resource_mut::<SetupTracker<TestSetupKey>>()
+			.set_record_trace(true);
+		let slow = (|| std::thread::sleep(Duration::from_millis(10)))
+			.provides([TestSetupKey::A])
+			.in_schedule(Update)
+			.register(app.world_mut());
+		let invalid = (|_: Res<Missing>| {})
+			.provides([TestSetupKey::B])
+			.in_schedule(Update)
+			.register(app.world_mut());
+		app.update();
+
+		// The scheduled systems are timed, not the placeholders that dispatched them
+		let tracker = app.world().resource::<SetupTracker<TestSetupKey>>();
+		let stats = tracker.run_stats(slow).unwrap();
+		assert_eq!(stats.runs(), 1);
+		assert!(stats.last_duration() >= Duration::from_millis(10));
+		assert_eq!(stats.last_error(), None);
+		assert!(tracker.run_stats(invalid).unwrap().last_error().is_some());
+		assert!(
+			tracker
+				.to_chrome_trace()
+				.contains(&format!("\"dur\":{}", stats.last_duration().as_micros()))
+		);
+	}
+
+	#[test]
+	fn test_run_to_completion_needs_schedule() {
+		let mut app = App::new();
+		app.add_plugins((
+			TaskPoolPlugin::default(),
+			SetupTrackingPlugin::<TestSetupKey, _, _, _, _>::new(|| true, || {}),
+		))
+		.register_provider((|| {}).provides([TestSetupKey::A]))
+		.register_provider(
+			(|mut progress: ReportProgress<TestSetupKey>| {
+				progress.report(TestSetupKey::B, Progress::DONE);
+			})
+			.provides([TestSetupKey::B])
+			.in_schedule(Update),
+		);
+
+		// Neither key can finish without running `Update`, so this doesn't wait for the timeout
+		let result = SetupTracker::<TestSetupKey>::run_to_completion(
+			app.world_mut(),
+			Duration::from_secs(10),
+		);
+		let Err(SetupIncomplete::NeedsSchedule { pending, .. }) = result else {
+			panic!("expected setup to need the schedule, got {result:?}");
+		};
+		assert_eq!(pending, [TestSetupKey::A, TestSetupKey::B]);
+
+		app.update();
+		app.world_mut()
+			.resource_mut::<SetupProgressStore<TestSetupKey>>()
+			.report(TestSetupKey::A, Progress::DONE);
+		SetupTracker::<TestSetupKey>::run_to_completion(app.world_mut(), Duration::from_secs(10))
+			.unwrap();
+	}
+
+	#[test]
+	fn test_invalidate_reported_key() {
+		let mut app = App::new();
+		app.add_plugins(SetupTrackingPlugin::<TestSetupKey, _, _, _, _>::new(
+			|| true,
+			|| {},
+		))
+		.init_resource::<Frames>()
+		.register_provider(
+			(|mut frames: ResMut<Frames>, mut progress: ReportProgress<TestSetupKey>| {
+				frames.0 += 1;
+				// Only the first run finishes right away
+				if frames.0 == 1 {
+					progress.report(TestSetupKey::A, Progress::DONE);
+				}
+			})
+			.provides([TestSetupKey::A])
+			.in_schedule(Update),
+		);
+		app.update();
+		app.update();
+		let tracker = app.world().resource::<SetupTracker<TestSetupKey>>();
+		assert_eq!(
+			tracker.last_key_progress(&TestSetupKey::A),
+			Some(Progress::DONE)
+		);
+
+		app.world_mut()
+			.resource_mut::<SetupTracker<TestSetupKey>>()
+			.invalidate(&TestSetupKey::A);
+		app.update();
+		assert_eq!(app.world().resource::<Frames>().0, 2);
+		// The progress reported by the first run is gone
+		app.update();
+		let tracker = app.world().resource::<SetupTracker<TestSetupKey>>();
+		assert_eq!(
+			tracker.last_key_progress(&TestSetupKey::A),
+			Some(Progress::ZERO)
+		);
+	}
+}
@@ -0,0 +1,40 @@
+//! Setup keys shared by the unit tests of different modules.
+
+use crate::{Progress, SetupKey};
+use bevy_ecs::{system::SystemId, world::World};
+
+/// Keys whose progress checkers never finish.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+	any(feature = "ron", feature = "serialize"),
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub(crate) enum TestSetupKey {
+	A,
+	B,
+	C,
+}
+
+impl SetupKey for TestSetupKey {
+	fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+		world.register_system(|| Progress::ZERO)
+	}
+}
+
+/// Keys whose progress checkers are always finished.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+	any(feature = "ron", feature = "serialize"),
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub(crate) enum DoneSetupKey {
+	A,
+	B,
+	C,
+}
+
+impl SetupKey for DoneSetupKey {
+	fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+		world.register_system(|| Progress::DONE)
+	}
+}
@@ -0,0 +1,450 @@
+//! Utilities for testing apps that use setup tracking.
+
+use crate::{Progress, ProgressChecker, ProviderInfo, SetupKey, SetupReport, SetupTracker};
+use bevy_app::App;
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemId;
+use bevy_log::error;
+use bevy_platform::collections::HashSet;
+use smallvec::smallvec;
+use std::borrow::Cow;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Extension methods for testing setup in an [`App`].
+pub trait SetupTestExt {
+	/// Updates the app until the `SetupTracker<K>` finishes, or until `max_frames` updates have
+	/// run, and returns a [report](SetupTracker::report) of the tracker's final state.
+	///
+	/// The tracker must still exist once setup finishes, so don't enable
+	/// [`cleanup_on_finish`](crate::SetupTrackingPlugin::cleanup_on_finish) in tests that use this.
+	fn run_until_setup_complete<K: SetupKey + Debug>(
+		&mut self,
+		max_frames: usize,
+	) -> Result<SetupReport<K>, SetupTestError<K>>;
+}
+
+impl SetupTestExt for App {
+	fn run_until_setup_complete<K: SetupKey + Debug>(
+		&mut self,
+		max_frames: usize,
+	) -> Result<SetupReport<K>, SetupTestError<K>> {
+		for _ in 0..max_frames {
+			self.update();
+			let Some(tracker) = self.world().get_resource::<SetupTracker<K>>() else {
+				return Err(SetupTestError::Missing);
+			};
+			if tracker.last_progress().finished() {
+				return Ok(tracker.report());
+			}
+		}
+		let tracker = self
+			.world()
+			.get_resource::<SetupTracker<K>>()
+			.ok_or(SetupTestError::Missing)?;
+		Err(SetupTestError::Timeout {
+			frames: max_frames,
+			report: tracker.report(),
+		})
+	}
+}
+
+/// Error returned by [`SetupTestExt::run_until_setup_complete`].
+#[derive(Debug, Clone)]
+pub enum SetupTestError<K: SetupKey> {
+	/// Setup didn't finish in time.
+	Timeout {
+		/// The number of frames that ran.
+		frames: usize,
+		/// The state of the tracker after the last frame.
+		report: SetupReport<K>,
+	},
+	/// There is no `SetupTracker<K>` resource, or it was removed.
+	Missing,
+}
+
+impl<K: SetupKey + Debug> Display for SetupTestError<K> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Timeout { frames, report } => {
+				write!(
+					f,
+					"setup did not complete within {frames} frames ({:.0}% done)",
+					report.progress * 100.0
+				)?;
+				let pending = report
+					.keys
+					.iter()
+					.filter(|key| key.progress.is_none_or(|progress| progress < 1.0))
+					.map(|key| key.label.as_str())
+					.collect::<Vec<_>>();
+				if !pending.is_empty() {
+					write!(f, "\npending: {}", pending.join(", "))?;
+				}
+				Ok(())
+			}
+			Self::Missing => f.write_str("the setup tracker is missing"),
+		}
+	}
+}
+
+impl<K: SetupKey + Debug> std::error::Error for SetupTestError<K> {}
+
+/// Extension methods for faking the progress of setup keys in tests, without standing up the
+/// real subsystems behind them.
+///
+/// # Panics
+///
+/// Every method panics if there is no `SetupTracker<K>` resource.
+pub trait SetupTrackerTestExt {
+	/// Replaces the progress checker of `key` with `checker`, adding the key to the tracker if it
+	/// wasn't already tracked.
+	///
+	/// Providers registered for the key later keep using the override.
+	fn override_checker<K: SetupKey, M>(
+		&mut self,
+		key: K,
+		checker: impl IntoSystem<(), Progress, M> + 'static,
+	) -> &mut Self;
+
+	/// Makes `key` always report that it's done.
+	fn force_complete<K: SetupKey>(&mut self, key: K) -> &mut Self {
+		self.override_checker(key, || Progress::DONE)
+	}
+
+	/// Makes `key` always report that it failed.
+	fn force_fail<K: SetupKey>(&mut self, key: K) -> &mut Self {
+		self.override_checker(key, || Progress::new(f32::NAN))
+	}
+}
+
+impl SetupTrackerTestExt for World {
+	fn override_checker<K: SetupKey, M>(
+		&mut self,
+		key: K,
+		checker: impl IntoSystem<(), Progress, M> + 'static,
+	) -> &mut Self {
+		let checker = ProgressChecker::Unique(self.register_system(checker));
+		self.resource_scope(|world, mut tracker: Mut<SetupTracker<K>>| {
+			tracker.spawn_key_entity(&key, world);
+			// The overriding checker may read anything
+			tracker.reads.remove(&key);
+			tracker.checked_progress.remove(&key);
+			if let Some(ProgressChecker::Unique(old)) = tracker.entries.insert(key, checker) {
+				if let Err(e) = world.unregister_system(old) {
+					error!("Failed to unregister progress checker: {e}");
+				}
+			}
+		});
+		self
+	}
+}
+
+impl SetupTrackerTestExt for App {
+	fn override_checker<K: SetupKey, M>(
+		&mut self,
+		key: K,
+		checker: impl IntoSystem<(), Progress, M> + 'static,
+	) -> &mut Self {
+		self.world_mut().override_checker(key, checker);
+		self
+	}
+}
+
+/// Generates random dependency graphs for stress tests, fuzzing, and benchmarks.
+///
+/// Provider `i` provides [`GeneratedKey(i)`](GeneratedKey) and requires up to
+/// [`max_requires`](Self::max_requires) keys of earlier providers, so the graph is always valid
+/// unless a [`defect`](Self::defect) is added. The same seed always generates the same graph.
+#[derive(Debug, Clone)]
+pub struct RandomGraph {
+	/// The number of providers, not counting one added by [`GraphDefect::DuplicateProvider`].
+	pub providers: usize,
+	/// The most keys a single provider requires.
+	pub max_requires: usize,
+	/// A deliberate mistake to add to the graph.
+	pub defect: Option<GraphDefect>,
+	/// The seed for the random number generator.
+	pub seed: u64,
+}
+
+/// A deliberate mistake in a [`RandomGraph`], which [`SetupTracker::validate`] should catch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GraphDefect {
+	/// A provider requires a key that nothing provides.
+	UnprovidedKey,
+	/// An extra provider provides a key that is already provided.
+	DuplicateProvider,
+	/// Two providers require each other's keys.
+	///
+	/// Needs at least 2 providers.
+	Cycle,
+}
+
+impl Default for RandomGraph {
+	fn default() -> Self {
+		Self {
+			providers: 100,
+			max_requires: 3,
+			defect: None,
+			seed: 0,
+		}
+	}
+}
+
+impl RandomGraph {
+	/// Creates a generator for valid graphs of `providers` providers.
+	pub fn new(providers: usize) -> Self {
+		Self {
+			providers,
+			..Self::default()
+		}
+	}
+
+	/// Sets the most keys a single provider requires.
+	pub fn max_requires(self, max_requires: usize) -> Self {
+		Self {
+			max_requires,
+			..self
+		}
+	}
+
+	/// Adds a deliberate mistake to the graph.
+	pub fn defect(self, defect: GraphDefect) -> Self {
+		Self {
+			defect: Some(defect),
+			..self
+		}
+	}
+
+	/// Sets the seed for the random number generator.
+	pub fn seed(self, seed: u64) -> Self {
+		Self { seed, ..self }
+	}
+
+	/// Generates the providers of the graph, named `provider_{i}`.
+	pub fn generate(&self) -> Vec<ProviderInfo<GeneratedKey>> {
+		let mut rng = fastrand::Rng::with_seed(self.seed);
+		let mut providers = (0..self.providers)
+			.map(|i| {
+				let count = rng.usize(0..=self.max_requires.min(i));
+				let requires = rng
+					.choose_multiple(0..i as u32, count)
+					.into_iter()
+					.map(GeneratedKey)
+					.collect();
+				ProviderInfo {
+					requires,
+					provides: smallvec![GeneratedKey(i as u32)],
+					name: Cow::Owned(format!("provider_{i}")),
+					..ProviderInfo::empty()
+				}
+			})
+			.collect::<Vec<_>>();
+
+		match self.defect {
+			None => {}
+			Some(_) if providers.is_empty() => {}
+			Some(GraphDefect::UnprovidedKey) => {
+				let i = rng.usize(..providers.len());
+				providers[i]
+					.requires
+					.push(GeneratedKey(self.providers as u32));
+			}
+			Some(GraphDefect::DuplicateProvider) => {
+				let key = GeneratedKey(rng.u32(..self.providers as u32));
+				providers.push(ProviderInfo {
+					provides: smallvec![key],
+					name: Cow::Owned(format!("provider_{}", self.providers)),
+					..ProviderInfo::empty()
+				});
+			}
+			Some(GraphDefect::Cycle) if providers.len() >= 2 => {
+				let later = rng.usize(1..providers.len());
+				let earlier = rng.usize(..later);
+				for (from, to) in [(later, earlier), (earlier, later)] {
+					let key = GeneratedKey(to as u32);
+					if !providers[from].requires.contains(&key) {
+						providers[from].requires.push(key);
+					}
+				}
+			}
+			Some(GraphDefect::Cycle) => {}
+		}
+		providers
+	}
+
+	/// Generates the graph and registers its providers with the world's
+	/// `SetupTracker<GeneratedKey>`.
+	///
+	/// Each provider inserts its keys into [`GeneratedKeysDone`] when it runs.
+	///
+	/// # Panics
+	///
+	/// Panics if there is no `SetupTracker<GeneratedKey>` resource.
+	pub fn register(&self, world: &mut World) -> Vec<SystemId> {
+		world.init_resource::<GeneratedKeysDone>();
+		self.generate()
+			.into_iter()
+			.map(|info| {
+				let provides = info.provides().iter().map(|key| key.0).collect::<Vec<_>>();
+				let system = world.register_system(move |mut done: ResMut<GeneratedKeysDone>| {
+					done.0.extend(provides.iter().copied());
+				});
+				world.resource_scope(|world, mut tracker: Mut<SetupTracker<GeneratedKey>>| {
+					tracker.register_provider(system, info, world);
+				});
+				system
+			})
+			.collect()
+	}
+}
+
+/// A setup key in a [`RandomGraph`].
+///
+/// Every key shares a single progress checker, which checks [`GeneratedKeysDone`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GeneratedKey(pub u32);
+
+/// The [`GeneratedKey`]s whose providers have run.
+#[derive(Resource, Debug, Default)]
+pub struct GeneratedKeysDone(pub HashSet<u32>);
+
+impl SetupKey for GeneratedKey {
+	fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+		let key = self.0;
+		world.register_system(move |done: Option<Res<GeneratedKeysDone>>| {
+			done.is_some_and(|done| done.0.contains(&key)).into()
+		})
+	}
+
+	fn progress_checker(&self, world: &mut World) -> ProgressChecker<Self> {
+		ProgressChecker::Keyed(world.register_system_cached(
+			|In(key): In<GeneratedKey>, done: Option<Res<GeneratedKeysDone>>| {
+				done.is_some_and(|done| done.0.contains(&key.0)).into()
+			},
+		))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{IntoDependencyProvider, RegisterProvider, SetupGraphError, SetupTrackingPlugin};
+	use serde::Serialize;
+
+	#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+	enum TestSetupKey {
+		A,
+		B,
+	}
+
+	#[derive(Resource, Default)]
+	struct Loaded(bool);
+
+	impl SetupKey for TestSetupKey {
+		fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+			match self {
+				TestSetupKey::A => world.register_system(|loaded: Res<Loaded>| loaded.0.into()),
+				TestSetupKey::B => world.register_system(|| Progress::ZERO),
+			}
+		}
+	}
+
+	#[test]
+	fn test_run_until_setup_complete() {
+		let mut app = App::new();
+		app.init_resource::<Loaded>()
+			.add_plugins(SetupTrackingPlugin::<TestSetupKey>::builder().build())
+			.register_provider(
+				(|mut loaded: ResMut<Loaded>| loaded.0 = true).provides([TestSetupKey::A]),
+			);
+		let report = app.run_until_setup_complete::<TestSetupKey>(10).unwrap();
+		assert!(report.finished);
+		assert_eq!(report.providers[0].runs, 1);
+
+		app.register_provider((|| {}).provides([TestSetupKey::B]));
+		let err = app.run_until_setup_complete::<TestSetupKey>(3).unwrap_err();
+		assert!(matches!(err, SetupTestError::Timeout { frames: 3, .. }));
+		assert_eq!(
+			err.to_string(),
+			"setup did not complete within 3 frames (50% done)\npending: B"
+		);
+	}
+
+	#[test]
+	fn test_override_checker() {
+		let mut app = App::new();
+		app.add_plugins(SetupTrackingPlugin::<TestSetupKey>::builder().build())
+			.register_provider((|| {}).provides([TestSetupKey::A]))
+			.register_provider(
+				(|| {})
+					.requires([TestSetupKey::A])
+					.provides([TestSetupKey::B]),
+			)
+			// `Loaded` doesn't exist, so the real checker for `A` would fail to run
+			.force_complete(TestSetupKey::A)
+			.override_checker(TestSetupKey::B, || Progress::new(0.5));
+		let err = app.run_until_setup_complete::<TestSetupKey>(3).unwrap_err();
+		let SetupTestError::Timeout { report, .. } = err else {
+			panic!("expected a timeout");
+		};
+		assert_eq!(report.progress, 0.75);
+		// Providers only run once unless they repeat until provided
+		assert_eq!(report.providers[1].runs, 1);
+
+		app.force_fail(TestSetupKey::B);
+		app.update();
+		let tracker = app.world().resource::<SetupTracker<TestSetupKey>>();
+		assert!(tracker.is_failed(&TestSetupKey::B));
+	}
+
+	#[test]
+	fn test_random_graph() {
+		let graph = RandomGraph::new(200).seed(7);
+		let providers = graph.generate();
+		assert_eq!(providers.len(), 200);
+		assert_eq!(
+			graph
+				.generate()
+				.iter()
+				.map(|info| info.requires().to_vec())
+				.collect::<Vec<_>>(),
+			providers
+				.iter()
+				.map(|info| info.requires().to_vec())
+				.collect::<Vec<_>>(),
+		);
+
+		let mut app = App::new();
+		app.add_plugins(SetupTrackingPlugin::<GeneratedKey>::builder().build());
+		graph.register(app.world_mut());
+		let tracker = app.world().resource::<SetupTracker<GeneratedKey>>();
+		assert!(tracker.validation_errors().is_empty());
+		assert_eq!(tracker.stages().iter().map(Vec::len).sum::<usize>(), 200);
+		let report = app.run_until_setup_complete::<GeneratedKey>(200).unwrap();
+		assert!(report.providers.iter().all(|provider| provider.runs == 1));
+
+		for (defect, is_expected) in [
+			(
+				GraphDefect::UnprovidedKey,
+				(|e| matches!(e, SetupGraphError::UnprovidedKey { .. }))
+					as fn(&SetupGraphError<GeneratedKey>) -> bool,
+			),
+			(GraphDefect::DuplicateProvider, |e| {
+				matches!(e, SetupGraphError::DuplicateProviders { .. })
+			}),
+			(GraphDefect::Cycle, |e| {
+				matches!(e, SetupGraphError::Cycle(_))
+			}),
+		] {
+			let mut world = World::new();
+			let on_finished = world.register_system(|| {});
+			world.insert_resource(SetupTracker::<GeneratedKey>::new(on_finished));
+			RandomGraph::new(50).defect(defect).register(&mut world);
+			let errors = world
+				.resource::<SetupTracker<GeneratedKey>>()
+				.validation_errors();
+			assert!(errors.iter().any(is_expected), "{defect:?}: {errors:?}");
+		}
+	}
+}
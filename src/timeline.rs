@@ -0,0 +1,251 @@
+//! Setup timelines, either predicted for designing the setup graph without running it, or
+//! measured to see how much providers actually overlap.
+
+use crate::{SetupKey, SetupTracker};
+use bevy_ecs::system::SystemId;
+use bevy_platform::time::Instant;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+/// How long setup is predicted to take, stage by stage. See [`SetupTracker::predict_timeline`].
+///
+/// The `Display` implementation prints one line per stage, followed by its providers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PredictedTimeline {
+	/// Predictions for each of the tracker's [stages](SetupTracker::stages), in order.
+	pub stages: Vec<PredictedStage>,
+	/// When the last stage is predicted to finish.
+	pub total: Duration,
+}
+
+/// A single stage of a [`PredictedTimeline`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PredictedStage {
+	/// When the stage is predicted to start, relative to the start of setup.
+	pub start: Duration,
+	/// How long the stage is predicted to take, i.e. as long as its slowest provider.
+	pub duration: Duration,
+	/// The stage's providers, with how long each is predicted to take.
+	pub providers: Vec<PredictedProvider>,
+}
+
+/// A single provider of a [`PredictedStage`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PredictedProvider {
+	/// The provider's system.
+	pub system: SystemId,
+	/// The provider's [name](crate::ProviderInfo::name).
+	pub name: String,
+	/// The sum of the [weights](SetupTracker::key_weight) of the keys it provides, scaled by the
+	/// timeline's unit.
+	pub duration: Duration,
+}
+
+/// When a provider actually ran. See [`SetupTracker::measured_timeline`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeasuredSpan {
+	/// The provider's system.
+	pub system: SystemId,
+	/// The provider's [name](crate::ProviderInfo::name).
+	pub name: String,
+	/// The provider's [stage](SetupTracker::stages).
+	pub stage: usize,
+	/// When the provider was first dispatched, relative to the first provider being dispatched.
+	pub start: Duration,
+	/// When the last of the provider's keys finished, relative to the first provider being
+	/// dispatched, or `None` if some are still unfinished.
+	pub end: Option<Duration>,
+}
+
+impl<K: SetupKey> SetupTracker<K> {
+	/// Returns when each provider that has been dispatched so far started and finished, ordered
+	/// by stage and then by start time, e.g. for drawing a Gantt chart.
+	///
+	/// A provider that doesn't provide any keys finishes as soon as it starts.
+	pub fn measured_timeline(&self) -> Vec<MeasuredSpan> {
+		let Some(&origin) = self.started.values().min() else {
+			return Vec::new();
+		};
+		let since_origin = |instant: Instant| instant.saturating_duration_since(origin);
+		let mut spans = self
+			.stages()
+			.iter()
+			.enumerate()
+			.flat_map(|(stage, systems)| systems.iter().map(move |system| (stage, *system)))
+			.filter_map(|(stage, system)| {
+				let started = *self.started.get(&system)?;
+				let info = &self.providers[&system];
+				let end = info
+					.provides()
+					.iter()
+					.map(|key| self.completed.get(key).copied())
+					.try_fold(started, |end, finished| Some(end.max(finished?)));
+				Some(MeasuredSpan {
+					system,
+					name: info.name().to_owned(),
+					stage,
+					start: since_origin(started),
+					end: end.map(since_origin),
+				})
+			})
+			.collect::<Vec<_>>();
+		spans.sort_by_key(|span| (span.stage, span.start));
+		spans
+	}
+
+	/// Predicts how long setup will take from the [stages](Self::stages) and
+	/// [key weights](Self::key_weight), without running any providers.
+	///
+	/// Providers in the same stage are assumed to run in parallel, so each stage takes as long as
+	/// its slowest provider. Since weights are relative, `unit` gives the time a key of weight
+	/// `1.0` takes.
+	pub fn predict_timeline(&self, unit: Duration) -> PredictedTimeline {
+		let mut total = Duration::ZERO;
+		let stages = self
+			.stages()
+			.iter()
+			.map(|stage| {
+				let providers = stage
+					.iter()
+					.map(|&system| {
+						let info = &self.providers[&system];
+						let weight = info
+							.provides()
+							.iter()
+							.map(|key| self.key_weight(key))
+							.sum::<f32>();
+						PredictedProvider {
+							system,
+							name: info.name().to_owned(),
+							duration: unit.mul_f32(weight.max(0.0)),
+						}
+					})
+					.collect::<Vec<_>>();
+				let duration = providers
+					.iter()
+					.map(|provider| provider.duration)
+					.max()
+					.unwrap_or_default();
+				let start = total;
+				total += duration;
+				PredictedStage {
+					start,
+					duration,
+					providers,
+				}
+			})
+			.collect();
+		PredictedTimeline { stages, total }
+	}
+}
+
+impl Display for PredictedTimeline {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		for (i, stage) in self.stages.iter().enumerate() {
+			writeln!(
+				f,
+				"stage {i}: {:.2?} - {:.2?} ({:.2?})",
+				stage.start,
+				stage.start + stage.duration,
+				stage.duration
+			)?;
+			for provider in &stage.providers {
+				writeln!(f, "  {}: {:.2?}", provider.name, provider.duration)?;
+			}
+		}
+		write!(f, "total: {:.2?}", self.total)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Progress, ProviderInfo};
+	use bevy_ecs::world::World;
+	use std::borrow::Cow;
+
+	#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+	enum TestSetupKey {
+		A,
+		B,
+		C,
+	}
+
+	impl SetupKey for TestSetupKey {
+		fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+			world.register_system(|| Progress::ZERO)
+		}
+
+		fn relative_time_estimate(&self) -> f32 {
+			match self {
+				TestSetupKey::A => 1.0,
+				TestSetupKey::B => 3.0,
+				TestSetupKey::C => 2.0,
+			}
+		}
+	}
+
+	#[test]
+	fn test_measured_timeline() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		let a = world.register_system(|| {});
+		let c = world.register_system(|| {});
+		tracker.register_provider(
+			a,
+			ProviderInfo::new(vec![], vec![TestSetupKey::A], Cow::Borrowed("a")),
+			&mut world,
+		);
+		tracker.register_provider(
+			c,
+			ProviderInfo::new(
+				vec![TestSetupKey::A],
+				vec![TestSetupKey::C],
+				Cow::Borrowed("c"),
+			),
+			&mut world,
+		);
+		assert!(tracker.measured_timeline().is_empty());
+
+		let origin = Instant::now();
+		let second = Duration::from_secs(1);
+		tracker.started.insert(a, origin);
+		tracker.completed.insert(TestSetupKey::A, origin + second);
+		tracker.started.insert(c, origin + second);
+
+		let timeline = tracker.measured_timeline();
+		assert_eq!(timeline.len(), 2);
+		assert_eq!((timeline[0].stage, timeline[0].start), (0, Duration::ZERO));
+		assert_eq!(timeline[0].end, Some(second));
+		assert_eq!((timeline[1].stage, timeline[1].start), (1, second));
+		assert_eq!(timeline[1].end, None);
+	}
+
+	#[test]
+	fn test_predict_timeline() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		let providers = [
+			(vec![], vec![TestSetupKey::A], "a"),
+			(vec![], vec![TestSetupKey::B], "b"),
+			(vec![TestSetupKey::A], vec![TestSetupKey::C], "c"),
+		];
+		for (requires, provides, name) in providers {
+			let system = world.register_system(|| {});
+			let info = ProviderInfo::new(requires, provides, Cow::Borrowed(name));
+			tracker.register_provider(system, info, &mut world);
+		}
+
+		let timeline = tracker.predict_timeline(Duration::from_secs(1));
+		assert_eq!(timeline.stages.len(), 2);
+		assert_eq!(timeline.stages[0].duration, Duration::from_secs(3));
+		assert_eq!(timeline.stages[1].start, Duration::from_secs(3));
+		assert_eq!(timeline.stages[1].duration, Duration::from_secs(2));
+		assert_eq!(timeline.total, Duration::from_secs(5));
+		assert_eq!(
+			timeline.to_string(),
+			"stage 0: 0.00ns - 3.00s (3.00s)\n  a: 1.00s\n  b: 3.00s\n\
+			stage 1: 3.00s - 5.00s (2.00s)\n  c: 2.00s\ntotal: 5.00s"
+		);
+	}
+}
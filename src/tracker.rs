@@ -1,20 +1,106 @@
-use crate::{Progress, ProgressCheckerId, ProviderInfo, SetupKey};
-use bevy_ecs::{prelude::*, system::SystemId};
-use bevy_log::error;
+use crate::{
+	CancellationToken, CheckerReads, CurrentSetupInstance, DependencyCycle, GraphTooLarge,
+	InvalidSetupGraph, Progress, ProgressChecker, ProviderInfo, RegressionPolicy, ReportProgress,
+	SetupGraphError, SetupGraphWarning, SetupIncomplete, SetupKey, SetupProgressStore, Throttle,
+};
+use bevy_ecs::{component::Tick, prelude::*, system::SystemId};
+use bevy_log::{error, info_span, warn};
 use bevy_platform::collections::{HashMap, HashSet};
-use std::fmt::{Debug, Formatter};
+use bevy_platform::time::Instant;
+use indexmap::IndexMap;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// How many samples [`SetupTracker::progress_history`] keeps.
+const PROGRESS_HISTORY_LEN: usize = 300;
+
+/// The minimum time between samples of [`SetupTracker::progress_history`].
+const PROGRESS_SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
 
 /// The main resource that tracks setup progress and manages provider systems.
 ///
 /// This resource maintains the state of all setup entries, their progress checkers,
 /// and the provider systems that contribute to setup completion.
+///
+/// Entries and providers are kept in registration order, so that stages, validation output, and
+/// visualization layout are reproducible between runs.
+///
+/// Each key and provider is also mirrored as an entity with [`SetupKeyState`](crate::SetupKeyState)
+/// or [`SetupProvider`](crate::SetupProvider) components, so setup state can be queried like any
+/// other ECS data. See [`key_entity`](Self::key_entity) and
+/// [`provider_entity`](Self::provider_entity).
 // TODO: A Schedule would ideally be better than manually running systems
 #[derive(Resource, Debug)]
 pub struct SetupTracker<K: SetupKey> {
-	pub(crate) entries: HashMap<K, ProgressCheckerId>,
-	pub(crate) providers: HashMap<SystemId, ProviderInfo<K>>,
+	pub(crate) entries: IndexMap<K, ProgressChecker<K>>,
+	/// See `SetupKey::checker_reads`
+	pub(crate) reads: HashMap<K, CheckerReads>,
+	/// What each key's checker returned the last time it ran, for keys with `reads`
+	pub(crate) checked_progress: HashMap<K, Progress>,
+	/// The change tick when progress checkers last ran
+	pub(crate) last_check: Option<Tick>,
+	pub(crate) providers: IndexMap<SystemId, ProviderInfo<K>>,
+	/// Reverse index of `providers`: key -> (provider, index in `provides`)
+	pub(crate) provider_index: HashMap<K, Vec<(SystemId, usize)>>,
+	/// Reverse index of `providers`: key -> (provider, index in `requires`)
+	pub(crate) dependant_index: HashMap<K, Vec<(SystemId, usize)>>,
 	pub(crate) on_finished: SystemId,
+	pub(crate) completion_listeners: Vec<SystemId>,
+	/// Sorted by threshold
+	pub(crate) milestones: Vec<Milestone>,
 	pub(crate) last_progress: Progress,
+	pub(crate) last_deferred_progress: Progress,
+	/// Overall progress sampled every `PROGRESS_SAMPLE_INTERVAL`, oldest first
+	pub(crate) progress_history: VecDeque<(Instant, Progress)>,
+	pub(crate) last_key_progress: HashMap<K, Progress>,
+	pub(crate) limits: GraphLimits,
+	pub(crate) terminal: HashSet<K>,
+	pub(crate) deferred_keys: HashSet<K>,
+	/// Overrides of `SetupKey::relative_time_estimate`
+	pub(crate) weights: HashMap<K, f32>,
+	pub(crate) failed: HashSet<K>,
+	/// Keys that [`OnSetupKeyComplete`](crate::OnSetupKeyComplete) has been triggered for
+	/// Values are when completion was first observed
+	pub(crate) completed: HashMap<K, Instant>,
+	/// Keys that count as unfinished until one of their providers runs again
+	pub(crate) invalidated: HashSet<K>,
+	/// Keys that count as finished without checking, e.g. restored from a previous run
+	pub(crate) restored: HashSet<K>,
+	pub(crate) started: HashMap<SystemId, Instant>,
+	pub(crate) run_stats: HashMap<SystemId, ProviderRunStats>,
+	/// Scheduled providers that were dispatched but whose systems haven't run since
+	pub(crate) awaiting_schedule: HashSet<SystemId>,
+	pub(crate) trace: Option<Vec<TraceRun>>,
+	pub(crate) last_frame_diff: FrameDiff<K>,
+	pub(crate) log_frame_diffs: bool,
+	pub(crate) regression_policy: RegressionPolicy,
+	pub(crate) frame_budget: Option<Duration>,
+	pub(crate) check_interval: Option<Duration>,
+	/// When `advance_setup` last checked progress, for `check_interval`
+	pub(crate) last_checked_at: Option<Instant>,
+	pub(crate) deferred: HashSet<SystemId>,
+	pub(crate) throttle: Throttle,
+	pub(crate) last_stage: Option<usize>,
+	pub(crate) strict_stages: bool,
+	pub(crate) cleanup_on_finish: bool,
+	pub(crate) finish_at: Option<Progress>,
+	pub(crate) finish_when: Vec<K>,
+	/// Whether the finish condition was reached the last time `advance_setup` ran
+	pub(crate) finish_was_reached: bool,
+	pub(crate) cancelled: bool,
+	pub(crate) cancellation: CancellationToken,
+	pub(crate) name_formatter: fn(&str) -> String,
+	pub(crate) key_labeler: KeyLabeler<K>,
+	/// Names keys in logs when `K: Debug` isn't known, set by `use_display_names`
+	pub(crate) display_name: Option<fn(&K) -> Cow<'static, str>>,
+	pub(crate) current_stage: usize,
+	pub(crate) key_entities: HashMap<K, Entity>,
+	pub(crate) provider_entities: HashMap<SystemId, Entity>,
+	pub(crate) status_entity: Option<Entity>,
+	stage_cache: OnceLock<Vec<Vec<SystemId>>>,
 }
 
 impl<K: SetupKey> SetupTracker<K> {
@@ -22,108 +108,1397 @@ impl<K: SetupKey> SetupTracker<K> {
 	pub fn new(on_finished: SystemId) -> Self {
 		Self {
 			entries: Default::default(),
+			reads: Default::default(),
+			checked_progress: Default::default(),
+			last_check: None,
 			providers: Default::default(),
+			provider_index: Default::default(),
+			dependant_index: Default::default(),
 			on_finished,
+			completion_listeners: Vec::new(),
+			milestones: Vec::new(),
 			last_progress: Default::default(),
+			last_deferred_progress: Default::default(),
+			progress_history: VecDeque::with_capacity(PROGRESS_HISTORY_LEN),
+			last_key_progress: Default::default(),
+			limits: Default::default(),
+			terminal: Default::default(),
+			deferred_keys: Default::default(),
+			weights: Default::default(),
+			failed: Default::default(),
+			completed: Default::default(),
+			invalidated: Default::default(),
+			restored: Default::default(),
+			started: Default::default(),
+			run_stats: Default::default(),
+			awaiting_schedule: Default::default(),
+			trace: None,
+			last_frame_diff: Default::default(),
+			log_frame_diffs: false,
+			regression_policy: Default::default(),
+			frame_budget: None,
+			check_interval: None,
+			last_checked_at: None,
+			deferred: Default::default(),
+			throttle: Default::default(),
+			last_stage: None,
+			strict_stages: false,
+			cleanup_on_finish: false,
+			finish_at: None,
+			finish_when: Vec::new(),
+			finish_was_reached: false,
+			cancelled: false,
+			cancellation: Default::default(),
+			name_formatter: default_name_formatter,
+			key_labeler: KeyLabeler(None),
+			display_name: None,
+			current_stage: 0,
+			key_entities: Default::default(),
+			provider_entities: Default::default(),
+			status_entity: None,
+			stage_cache: OnceLock::new(),
+		}
+	}
+
+	/// Adds a system to run whenever setup is finished, in addition to the `on_finished` callback.
+	///
+	/// Listeners run in the order they were added, right after `on_finished`. This lets several
+	/// independent plugins hook the end of setup without sharing the single plugin callback. See
+	/// also [`AddCompletionListener`](crate::AddCompletionListener).
+	pub fn add_completion_listener(&mut self, system: SystemId) {
+		self.completion_listeners.push(system);
+	}
+
+	/// Removes a completion listener added with
+	/// [`add_completion_listener`](Self::add_completion_listener).
+	///
+	/// The system itself is not unregistered from the world. Returns `true` if it was a listener.
+	pub fn remove_completion_listener(&mut self, system: SystemId) -> bool {
+		let len = self.completion_listeners.len();
+		self.completion_listeners.retain(|id| *id != system);
+		self.completion_listeners.len() != len
+	}
+
+	/// Returns the systems added with [`add_completion_listener`](Self::add_completion_listener).
+	pub fn completion_listeners(&self) -> &[SystemId] {
+		&self.completion_listeners
+	}
+
+	/// Adds a system to run once overall progress reaches `threshold`.
+	///
+	/// Each milestone fires exactly once, the first time [`advance_setup`](crate::advance_setup)
+	/// sees progress at or above its threshold, even if progress later drops again. Milestones
+	/// reached in the same frame fire in order of increasing threshold, before the completion
+	/// callbacks.
+	pub fn add_milestone(&mut self, threshold: Progress, system: SystemId) {
+		let i = self
+			.milestones
+			.partition_point(|milestone| milestone.threshold <= threshold);
+		self.milestones.insert(
+			i,
+			Milestone {
+				threshold,
+				system,
+				fired: false,
+			},
+		);
+	}
+
+	/// Runs milestones that have been reached by `progress` and haven't fired yet.
+	pub(crate) fn fire_milestones(&mut self, progress: Progress, world: &mut World) {
+		for milestone in self.milestones.iter_mut() {
+			if milestone.fired || !milestone.reached(progress) {
+				continue;
+			}
+			milestone.fired = true;
+			if let Err(e) = world.run_system(milestone.system) {
+				error!(
+					"Failed to run setup milestone at {}: {e}",
+					milestone.threshold
+				);
+			}
+		}
+	}
+
+	/// Returns the size limits applied when validating or visualizing this setup graph.
+	pub fn limits(&self) -> GraphLimits {
+		self.limits
+	}
+
+	/// Sets the size limits applied when validating or visualizing this setup graph.
+	pub fn set_limits(&mut self, limits: GraphLimits) {
+		self.limits = limits;
+	}
+
+	/// Returns the maximum time [`advance_setup`](crate::advance_setup) may spend per frame before
+	/// it stops dispatching providers, if any.
+	pub fn frame_budget(&self) -> Option<Duration> {
+		self.frame_budget
+	}
+
+	/// Sets the maximum time [`advance_setup`](crate::advance_setup) may spend per frame before it
+	/// stops dispatching providers. Providers that didn't get to run are dispatched first on the
+	/// next frame.
+	///
+	/// At least one provider is always dispatched per frame, so setup can't stall entirely.
+	/// Providers can split their own work across frames to stay within the budget, such as with a
+	/// [`ChunkedTask`](crate::ChunkedTask).
+	pub fn set_frame_budget(&mut self, budget: Option<Duration>) {
+		self.frame_budget = budget;
+	}
+
+	/// Returns the minimum time between progress checks, if any.
+	pub fn check_interval(&self) -> Option<Duration> {
+		self.check_interval
+	}
+
+	/// Sets the minimum time between runs of [`advance_setup`](crate::advance_setup) that actually
+	/// check progress and dispatch providers. Runs in between return immediately, so progress and
+	/// events only update once per interval.
+	pub fn set_check_interval(&mut self, interval: Option<Duration>) {
+		self.check_interval = interval;
+	}
+
+	/// Returns how many providers [`advance_setup`](crate::advance_setup) may dispatch per frame.
+	pub fn throttle(&self) -> Throttle {
+		self.throttle
+	}
+
+	/// Sets how many providers [`advance_setup`](crate::advance_setup) may dispatch per frame.
+	/// Providers that didn't get to run are dispatched first on the next frame.
+	pub fn set_throttle(&mut self, throttle: Throttle) {
+		self.throttle = throttle;
+	}
+
+	/// Returns `true` if the tracker will be [cleaned up](Self::cleanup) once setup finishes.
+	pub fn cleanup_on_finish(&self) -> bool {
+		self.cleanup_on_finish
+	}
+
+	/// Sets whether [`advance_setup`](crate::advance_setup) should [clean up](Self::cleanup) the
+	/// tracker after running the completion callbacks, so long-running apps don't keep
+	/// loading-time bookkeeping around.
+	pub fn set_cleanup_on_finish(&mut self, cleanup: bool) {
+		self.cleanup_on_finish = cleanup;
+	}
+
+	/// Returns the progress at which setup counts as finished, if set with
+	/// [`set_finish_at`](Self::set_finish_at).
+	pub fn finish_at(&self) -> Option<Progress> {
+		self.finish_at
+	}
+
+	/// Sets a progress threshold at which setup counts as finished, instead of only when every key
+	/// is finished.
+	///
+	/// Once overall progress reaches the threshold, the completion callbacks run and
+	/// [`OnSetupComplete`](crate::OnSetupComplete) is triggered, while the remaining providers keep
+	/// being dispatched. The tracker is still only [cleaned up on
+	/// finish](Self::set_cleanup_on_finish) once every key is finished. If
+	/// [`set_finish_when`](Self::set_finish_when) is also used, reaching either condition finishes
+	/// setup.
+	pub fn set_finish_at(&mut self, threshold: Option<Progress>) {
+		self.finish_at = threshold;
+	}
+
+	/// Returns the keys that finish setup once they are all finished, set with
+	/// [`set_finish_when`](Self::set_finish_when).
+	pub fn finish_when(&self) -> &[K] {
+		&self.finish_when
+	}
+
+	/// Sets keys that finish setup once they are all finished, like
+	/// [`set_finish_at`](Self::set_finish_at). An empty list disables this condition.
+	pub fn set_finish_when(&mut self, keys: impl IntoIterator<Item = K>) {
+		self.finish_when = keys.into_iter().collect();
+	}
+
+	/// Whether setup counts as finished with the given overall progress, according to the
+	/// [finish conditions](Self::set_finish_at). Key progress must already be up to date.
+	pub(crate) fn finish_reached(&self, progress: Progress) -> bool {
+		progress.finished()
+			|| self
+				.finish_at
+				.is_some_and(|threshold| progress.is_finite() && progress >= threshold)
+			|| (!self.finish_when.is_empty()
+				&& self.finish_when.iter().all(|key| {
+					self.last_key_progress(key)
+						.is_some_and(|progress| progress.finished())
+				}))
+	}
+
+	/// Returns `true` if setup was stopped with [`cancel_setup`](crate::cancel_setup).
+	pub fn is_cancelled(&self) -> bool {
+		self.cancelled
+	}
+
+	/// Returns `true` if every provider run is being recorded for
+	/// [`to_chrome_trace`](Self::to_chrome_trace).
+	pub fn is_recording_trace(&self) -> bool {
+		self.trace.is_some()
+	}
+
+	/// Sets whether [`advance_setup`](crate::advance_setup) records the start time and duration
+	/// of every provider run, for [`to_chrome_trace`](Self::to_chrome_trace).
+	///
+	/// Recording is off by default, since every run is kept until the tracker is dropped. Turning
+	/// it off discards what was recorded so far.
+	pub fn set_record_trace(&mut self, record: bool) {
+		if !record {
+			self.trace = None;
+		} else if self.trace.is_none() {
+			self.trace = Some(Vec::new());
+		}
+	}
+
+	/// Returns how many systems the tracker has registered and will unregister when it's
+	/// [cleaned up](Self::cleanup), i.e. providers, cancellation handlers, completion callbacks,
+	/// milestones, and [unique](ProgressChecker::Unique) progress checkers.
+	///
+	/// Useful for checking that adding and removing keys at runtime doesn't leak systems. Shared
+	/// checkers aren't counted, since they're owned by whoever registered them.
+	pub fn registered_systems(&self) -> usize {
+		let unique_checkers = self
+			.entries
+			.values()
+			.filter(|checker| matches!(checker, ProgressChecker::Unique(_)))
+			.count();
+		let on_cancel = self
+			.providers
+			.values()
+			.filter_map(ProviderInfo::on_cancel)
+			.count();
+		self.providers.len()
+			+ on_cancel
+			+ self.completion_listeners.len()
+			+ self.milestones.len()
+			+ 1 // on_finished
+			+ unique_checkers
+	}
+
+	/// Removes the `SetupTracker<K>` resource from the world, unregistering every progress
+	/// checker, provider, and completion callback it holds, and despawning its
+	/// [entities](Self::key_entity). The [`SetupProgressStore`] is removed as well, unless this is
+	/// a [`SetupInstance`](crate::SetupInstance)'s tracker. Its
+	/// [cancellation token](Self::cancellation_token) is cancelled, stopping background work that
+	/// checks it.
+	///
+	/// [`advance_setup`](crate::advance_setup) does nothing once the tracker is removed. Any other
+	/// systems that access the tracker, such as the visualization systems, should be stopped as
+	/// well, e.g. with a `resource_exists` run condition.
+	///
+	/// Returns `false` if the resource didn't exist.
+	pub fn cleanup(world: &mut World) -> bool {
+		let Some(tracker) = world.remove_resource::<Self>() else {
+			return false;
+		};
+		tracker.cancellation.cancel();
+		if !world.contains_resource::<CurrentSetupInstance>() {
+			// Instances share the store
+			world.remove_resource::<SetupProgressStore<K>>();
+		}
+		let systems = tracker
+			.providers
+			.keys()
+			.copied()
+			.chain(
+				tracker
+					.providers
+					.values()
+					.filter_map(ProviderInfo::on_cancel),
+			)
+			.chain(tracker.completion_listeners)
+			.chain(tracker.milestones.iter().map(|milestone| milestone.system))
+			.chain([tracker.on_finished]);
+		for system in systems {
+			if let Err(e) = world.unregister_system(system) {
+				error!("Failed to unregister setup system: {e}");
+			}
+		}
+		for checker in tracker.entries.into_values() {
+			let ProgressChecker::Unique(checker) = checker else {
+				// Shared checkers are owned by whoever registered them
+				continue;
+			};
+			if let Err(e) = world.unregister_system(checker) {
+				error!("Failed to unregister progress checker: {e}");
+			}
+		}
+		let entities = tracker
+			.key_entities
+			.into_values()
+			.chain(tracker.provider_entities.into_values())
+			.chain(tracker.status_entity);
+		for entity in entities {
+			world.try_despawn(entity).ok();
+		}
+		true
+	}
+
+	/// Runs [`advance_setup`](crate::advance_setup) in a loop until setup finishes, for headless
+	/// tools, asset bakers, and tests that don't need to keep rendering frames while loading.
+	///
+	/// Setup counts as finished once the [finish condition](Self::set_finish_at) is reached, or
+	/// the tracker was [cleaned up](Self::cleanup) after finishing. Work in background tasks keeps
+	/// running on the task pools' threads in the meantime, and tasks on the main thread are ticked
+	/// between passes, but anything that needs the app to update, such as asset loading, won't
+	/// make progress.
+	///
+	/// While passes change nothing, the thread parks for increasingly long intervals between them,
+	/// up to a few milliseconds, instead of spinning.
+	///
+	/// On wasm32, tasks only make progress once control returns to the browser, so instead of
+	/// blocking until `timeout`, this returns as soon as a pass changes nothing. Setup then
+	/// continues over frames as usual.
+	///
+	/// This only runs [`advance_setup`](crate::advance_setup), never the app's schedules, so
+	/// [scheduled providers](crate::Provider::in_schedule) are dispatched but don't run, and
+	/// [reported](ProgressChecker::Reported) keys only make progress if a provider that
+	/// [repeats until provided](crate::Provider::repeat_until_provided) reports them. Once a pass
+	/// changes nothing while setup waits for either, this returns instead of waiting for
+	/// `timeout`.
+	///
+	/// # Errors
+	///
+	/// Returns [`SetupIncomplete`] if setup hasn't finished after `timeout`, needs the app's
+	/// schedules to run, or was cancelled.
+	pub fn run_to_completion(
+		world: &mut World,
+		timeout: Duration,
+	) -> Result<(), SetupIncomplete<K>> {
+		let start = Instant::now();
+		let mut idle = Duration::ZERO;
+		loop {
+			crate::advance_setup::<K>(world);
+			let Some(tracker) = world.get_resource::<Self>() else {
+				return Ok(());
+			};
+			if tracker.cancelled {
+				return Err(SetupIncomplete::Cancelled);
+			}
+			if tracker.finish_was_reached {
+				return Ok(());
+			}
+			let pending = || {
+				tracker
+					.entries
+					.keys()
+					.filter(|key| {
+						!tracker
+							.last_key_progress(key)
+							.is_some_and(|progress| progress.finished())
+					})
+					.cloned()
+					.collect::<Vec<_>>()
+			};
+			if tracker.last_frame_diff.is_empty() {
+				let pending = pending();
+				if tracker.needs_schedule(&pending) {
+					return Err(SetupIncomplete::NeedsSchedule {
+						progress: tracker.last_progress,
+						pending,
+					});
+				}
+			}
+			// Waiting for tasks would block the browser's event loop, which runs them
+			let stalled = cfg!(target_arch = "wasm32") && tracker.last_frame_diff.is_empty();
+			if start.elapsed() >= timeout || stalled {
+				return Err(SetupIncomplete::TimedOut {
+					progress: tracker.last_progress,
+					pending: pending(),
+				});
+			}
+			idle = if tracker.last_frame_diff.is_empty() {
+				(idle * 2).clamp(MIN_IDLE, MAX_IDLE)
+			} else {
+				Duration::ZERO
+			};
+			yield_to_tasks(idle.min(timeout.saturating_sub(start.elapsed())));
+		}
+	}
+
+	/// Returns whether setup is waiting for work that only happens when the app updates: scheduled
+	/// providers that were dispatched but haven't run yet, or `pending` keys that are
+	/// [reported](ProgressChecker::Reported) by systems, not by a repeating provider run in
+	/// [`advance_setup`](crate::advance_setup).
+	fn needs_schedule(&self, pending: &[K]) -> bool {
+		!self.awaiting_schedule.is_empty()
+			|| pending.iter().any(|key| {
+				matches!(self.entries[key], ProgressChecker::Reported)
+					&& !self
+						.provider_index
+						.get(key)
+						.into_iter()
+						.flatten()
+						.any(|(system, _)| {
+							let info = &self.providers[system];
+							info.repeats_until_provided() && !info.runs_in_schedule()
+						})
+			})
+	}
+
+	/// Sets the function used to derive a provider's name from its system's type name, for
+	/// providers that aren't given one with [`Provider::named`](crate::Provider::named).
+	///
+	/// The default strips module paths, e.g. `my_game::setup::load_assets` becomes
+	/// `load_assets`. This only affects providers registered after it is set.
+	pub fn set_name_formatter(&mut self, formatter: fn(&str) -> String) {
+		self.name_formatter = formatter;
+	}
+
+	/// Derives a provider name from a system's type name using the
+	/// [name formatter](Self::set_name_formatter).
+	pub fn format_name(&self, type_name: &str) -> String {
+		(self.name_formatter)(type_name)
+	}
+
+	/// Sets a callback that resolves key labels at display time, e.g. through a localization
+	/// source, for the visualization and loading screens.
+	///
+	/// The callback is called every time a label is displayed, so it can follow runtime locale
+	/// changes, e.g. by reading from a shared `Arc<RwLock<_>>`. Returning `None` falls back to
+	/// [`SetupKey::display_name`].
+	pub fn set_key_labeler(
+		&mut self,
+		labeler: impl Fn(&K) -> Option<Cow<'static, str>> + Send + Sync + 'static,
+	) {
+		self.key_labeler = KeyLabeler(Some(Box::new(labeler)));
+	}
+
+	/// Returns the label to display for `key`, resolved by the
+	/// [key labeler](Self::set_key_labeler) if there is one, or [`SetupKey::display_name`]
+	/// otherwise.
+	pub fn key_label(&self, key: &K) -> Cow<'static, str>
+	where
+		K: Debug,
+	{
+		self.key_labeler
+			.0
+			.as_ref()
+			.and_then(|labeler| labeler(key))
+			.unwrap_or_else(|| key.display_name())
+	}
+
+	/// Makes logs and profiling spans, which can't require `K: Debug`, name keys by their
+	/// [label](Self::key_label) as well. [`SetupTrackingPlugin`](crate::SetupTrackingPlugin)
+	/// enables this for the tracker it inserts.
+	pub fn use_display_names(&mut self)
+	where
+		K: Debug,
+	{
+		self.display_name = Some(|key| key.display_name());
+	}
+
+	/// Returns a name for `key` in logs and profiling spans, which doesn't require `K: Debug`.
+	///
+	/// Uses the [key labeler](Self::set_key_labeler) if there is one, then
+	/// [`SetupKey::display_name`] if [enabled](Self::use_display_names), or the key type and the
+	/// key's index in [`entries`](Self::entries) otherwise.
+	pub(crate) fn key_span_name(&self, key: &K) -> Cow<'static, str> {
+		self.key_labeler
+			.0
+			.as_ref()
+			.and_then(|labeler| labeler(key))
+			.or_else(|| self.display_name.map(|display_name| display_name(key)))
+			.unwrap_or_else(|| {
+				let index = self.entries.get_index_of(key).unwrap_or(usize::MAX);
+				format!("{}[{index}]", disqualified::ShortName::of::<K>()).into()
+			})
+	}
+
+	/// Returns how long `key` has been in progress, measured from when the first of its providers
+	/// started running until it finished, or until now if it hasn't finished yet.
+	///
+	/// Returns `None` if none of its providers have run yet.
+	pub fn key_elapsed(&self, key: &K) -> Option<Duration> {
+		let start = self
+			.providers_of(key)
+			.filter_map(|(system, _)| self.started.get(&system))
+			.min()?;
+		let end = self
+			.completed
+			.get(key)
+			.copied()
+			.unwrap_or_else(Instant::now);
+		Some(end.saturating_duration_since(*start))
+	}
+
+	/// Returns `true` if strict stage mode is enabled.
+	pub fn strict_stages(&self) -> bool {
+		self.strict_stages
+	}
+
+	/// Enables or disables strict stage mode.
+	///
+	/// In strict stage mode, [`advance_setup`](crate::advance_setup) only dispatches providers
+	/// belonging to the current [stage](Self::stages), and doesn't move on to the next stage until
+	/// every key provided by the current stage is finished, even if the requirements of providers
+	/// in later stages happen to be satisfied already. This makes execution order deterministic,
+	/// which can help with debugging.
+	pub fn set_strict_stages(&mut self, strict: bool) {
+		self.strict_stages = strict;
+	}
+
+	/// Marks keys as intended final outputs of the setup graph.
+	///
+	/// Once any key has been marked terminal, [`lint`](Self::lint) warns about keys that are
+	/// provided but never required and are not terminal. Until then, that check is skipped. With
+	/// the plugin, terminal keys can be set with
+	/// [`SetupTrackingPlugin::terminal`](crate::SetupTrackingPlugin::terminal).
+	pub fn mark_terminal(&mut self, keys: impl IntoIterator<Item = K>) {
+		self.terminal.extend(keys);
+	}
+
+	/// Returns the keys that have been marked as final outputs.
+	pub fn terminal_keys(&self) -> &HashSet<K> {
+		&self.terminal
+	}
+
+	/// Marks keys as non-essential, so setup can finish without them, e.g. high-resolution
+	/// textures that can stream in while the game is already playable.
+	///
+	/// Deferred keys are left out of [`progress`](Self::progress), so the barrier's completion
+	/// callbacks and [`OnSetupComplete`](crate::OnSetupComplete) fire once every other key is
+	/// finished. Their providers keep being dispatched afterwards, and their own progress is
+	/// reported by [`deferred_progress`](Self::deferred_progress), with
+	/// [`OnDeferredSetupComplete`](crate::OnDeferredSetupComplete) triggered once they all finish.
+	/// The tracker is only [cleaned up on finish](Self::set_cleanup_on_finish) after that.
+	///
+	/// Essential keys that require a deferred key will still wait for it.
+	pub fn mark_deferred(&mut self, keys: impl IntoIterator<Item = K>) {
+		self.deferred_keys.extend(keys);
+	}
+
+	/// Returns the keys that have been [marked as deferred](Self::mark_deferred).
+	pub fn deferred_keys(&self) -> &HashSet<K> {
+		&self.deferred_keys
+	}
+
+	/// Marks a key as failed, so that its [fallback providers](crate::IntoDependencyProvider::fallback_for)
+	/// run instead of its primary providers.
+	pub fn mark_failed(&mut self, key: K) {
+		self.failed.insert(key);
+	}
+
+	/// Returns `true` if the key has been marked as failed.
+	pub fn is_failed(&self, key: &K) -> bool {
+		self.failed.contains(key)
+	}
+
+	/// Returns the keys that have been marked as failed.
+	pub fn failed_keys(&self) -> &HashSet<K> {
+		&self.failed
+	}
+
+	/// Returns the total number of edges in the setup graph, i.e. the sum of the lengths of every
+	/// provider's `requires` and `provides` lists.
+	pub fn edge_count(&self) -> usize {
+		self.providers
+			.values()
+			.map(|info| info.requires().len() + info.provides().len())
+			.sum()
+	}
+
+	/// Checks whether this setup graph fits within its configured [`GraphLimits`].
+	pub fn check_limits(&self) -> Result<(), GraphTooLarge> {
+		let keys = self.entries.len();
+		let edges = self.edge_count();
+		if keys > self.limits.max_keys || edges > self.limits.max_edges {
+			Err(GraphTooLarge {
+				keys,
+				edges,
+				limits: self.limits,
+			})
+		} else {
+			Ok(())
 		}
 	}
 
-	/// Registers a provider system with its dependency information.
+	/// Registers a provider system with its dependency information.
+	///
+	/// This method automatically registers progress checkers for any setup keys
+	/// that haven't been seen before. The [intrinsic requirements](SetupKey::requires) of the keys
+	/// the provider provides are added to its `requires`.
+	pub fn register_provider(
+		&mut self,
+		system: SystemId,
+		mut provider: ProviderInfo<K>,
+		world: &mut World,
+	) {
+		for key in provider.provides.clone() {
+			for required in key.requires() {
+				if !provider.requires.contains(&required) && !provider.provides.contains(&required)
+				{
+					provider.requires.push(required);
+				}
+			}
+		}
+		for key in provider.requires().iter().chain(provider.provides()) {
+			if !self.entries.contains_key(key) {
+				self.entries
+					.insert(key.clone(), key.progress_checker(world));
+				if let Some(reads) = key.checker_reads() {
+					self.reads.insert(key.clone(), reads);
+				}
+				self.spawn_key_entity(key, world);
+			}
+		}
+		if provider.reports_progress() {
+			world.init_resource::<SetupProgressStore<K>>();
+			for key in provider.provides() {
+				self.reads.remove(key);
+				self.checked_progress.remove(key);
+				if let Some(ProgressChecker::Unique(old)) =
+					self.entries.insert(key.clone(), ProgressChecker::Reported)
+				{
+					if let Err(e) = world.unregister_system(old) {
+						error!("Failed to unregister progress checker: {e}");
+					}
+				}
+			}
+		}
+		if let Some(weight) = provider.weight() {
+			for prov in provider.provides() {
+				self.weights.insert(prov.clone(), weight);
+			}
+		}
+		if self.providers.contains_key(&system) {
+			self.unindex_provider(system);
+		}
+		self.providers.insert(system, provider);
+		self.index_provider(system);
+		self.invalidate_stages();
+		self.sync_provider_entity(system, world);
+	}
+
+	/// Adds a provider to the `provider_index` and `dependant_index`.
+	fn index_provider(&mut self, system: SystemId) {
+		let info = &self.providers[&system];
+		// Only the first occurrence of each key is indexed
+		for (i, key) in info.provides().iter().enumerate() {
+			let entries = self.provider_index.entry(key.clone()).or_default();
+			if !entries.iter().any(|(id, _)| *id == system) {
+				entries.push((system, i));
+			}
+		}
+		for (i, key) in info.requires().iter().enumerate() {
+			let entries = self.dependant_index.entry(key.clone()).or_default();
+			if !entries.iter().any(|(id, _)| *id == system) {
+				entries.push((system, i));
+			}
+		}
+	}
+
+	/// Removes a provider from the `provider_index` and `dependant_index`.
+	fn unindex_provider(&mut self, system: SystemId) {
+		fn unindex<K: SetupKey>(
+			index: &mut HashMap<K, Vec<(SystemId, usize)>>,
+			keys: &[K],
+			system: SystemId,
+		) {
+			for key in keys {
+				if let Some(entries) = index.get_mut(key) {
+					entries.retain(|(id, _)| *id != system);
+					if entries.is_empty() {
+						index.remove(key);
+					}
+				}
+			}
+		}
+
+		let info = &self.providers[&system];
+		unindex(&mut self.provider_index, info.provides(), system);
+		unindex(&mut self.dependant_index, info.requires(), system);
+	}
+
+	/// Rebuilds the `provider_index` and `dependant_index` from scratch.
+	fn rebuild_index(&mut self) {
+		self.provider_index.clear();
+		self.dependant_index.clear();
+		for i in 0..self.providers.len() {
+			let system = *self.providers.get_index(i).unwrap().0;
+			self.index_provider(system);
+		}
+	}
+
+	/// Unregisters a provider system and removes it from the setup graph.
+	///
+	/// The provider's system is unregistered from the world. Any setup keys that are no longer
+	/// required or provided by any remaining provider are pruned as well, along with their cached
+	/// progress checkers.
+	///
+	/// Returns the removed provider's info, or `None` if no provider was registered with the given
+	/// `SystemId`.
+	pub fn remove_provider(
+		&mut self,
+		system: SystemId,
+		world: &mut World,
+	) -> Option<ProviderInfo<K>> {
+		if !self.providers.contains_key(&system) {
+			return None;
+		}
+		self.unindex_provider(system);
+		let info = self.providers.shift_remove(&system)?;
+		self.invalidate_stages();
+		self.started.remove(&system);
+		self.run_stats.remove(&system);
+		self.awaiting_schedule.remove(&system);
+		self.deferred.remove(&system);
+		self.despawn_provider_entity(system, world);
+		for system in [system].into_iter().chain(info.on_cancel()) {
+			if let Err(e) = world.unregister_system(system) {
+				error!(
+					"Failed to unregister provider system `{}`: {e}",
+					info.name()
+				);
+			}
+		}
+		if info.weight().is_some() {
+			// Don't let the removed provider's weight apply to later providers of its keys
+			for key in info.provides() {
+				let weight = self
+					.providers_of(key)
+					.find_map(|(other, _)| self.providers[&other].weight());
+				match weight {
+					Some(weight) => self.weights.insert(key.clone(), weight),
+					None => self.weights.remove(key),
+				};
+			}
+		}
+		for key in info.requires().iter().chain(info.provides()) {
+			let still_used =
+				self.providers_of(key).next().is_some() || self.dependants_of(key).next().is_some();
+			if !still_used {
+				// Optional requirements are kept so they take effect again if the key is
+				// provided later
+				self.remove_entry(key, world);
+			}
+		}
+		Some(info)
+	}
+
+	/// Replaces the keys a provider requires, e.g. to try out a different ordering at runtime.
+	///
+	/// New keys get progress checkers as if the provider had been registered with them, and keys
+	/// that are no longer required or provided by any provider are pruned, as in
+	/// [`remove_provider`](Self::remove_provider).
+	///
+	/// The [intrinsic requirements](SetupKey::requires) of the keys the provider provides can't be
+	/// removed. They are kept, with a warning, if `requires` leaves them out.
+	///
+	/// Returns `false` if no provider was registered with the given `SystemId`.
+	pub fn set_requires(&mut self, system: SystemId, requires: Vec<K>, world: &mut World) -> bool {
+		let Some(info) = self.providers.get(&system) else {
+			return false;
+		};
+		let kept = info
+			.provides()
+			.iter()
+			.flat_map(SetupKey::requires)
+			.filter(|key| !requires.contains(key))
+			.collect::<Vec<_>>();
+		if !kept.is_empty() {
+			warn!(
+				"Keeping intrinsic requirements [{}] of `{}`",
+				kept.iter()
+					.map(|key| self.key_span_name(key))
+					.collect::<Vec<_>>()
+					.join(", "),
+				info.name()
+			);
+		}
+		let mut info = info.clone();
+		let old = std::mem::replace(&mut info.requires, requires.into());
+		self.register_provider(system, info, world);
+		for key in &old {
+			let still_used =
+				self.providers_of(key).next().is_some() || self.dependants_of(key).next().is_some();
+			if !still_used {
+				self.remove_entry(key, world);
+			}
+		}
+		true
+	}
+
+	/// Replaces the keys a provider provides, like [`set_requires`](Self::set_requires) does for
+	/// the keys it requires.
+	///
+	/// Keys that are no longer provided are also dropped from the provider's
+	/// [`provides_any`](ProviderInfo::provides_any) and
+	/// [`fallback_for`](ProviderInfo::fallback_for) lists. Their weights fall back to another
+	/// provider's, as in [`remove_provider`](Self::remove_provider), and keys that are no longer
+	/// required or provided by any provider are pruned.
+	///
+	/// Returns `false` if no provider was registered with the given `SystemId`.
+	pub fn set_provides(&mut self, system: SystemId, provides: Vec<K>, world: &mut World) -> bool {
+		let Some(info) = self.providers.get(&system) else {
+			return false;
+		};
+		let mut info = info.clone();
+		let old = std::mem::replace(&mut info.provides, provides.into());
+		let removed = old
+			.into_iter()
+			.filter(|key| !info.provides.contains(key))
+			.collect::<Vec<_>>();
+		info.provides_any.retain(|key| !removed.contains(key));
+		info.fallback_for.retain(|key| !removed.contains(key));
+		self.register_provider(system, info, world);
+		for key in &removed {
+			if self.providers_of(key).next().is_none() && self.dependants_of(key).next().is_none() {
+				self.remove_entry(key, world);
+				continue;
+			}
+			let weight = self
+				.providers_of(key)
+				.find_map(|(other, _)| self.providers[&other].weight());
+			match weight {
+				Some(weight) => self.weights.insert(key.clone(), weight),
+				None => self.weights.remove(key),
+			};
+		}
+		true
+	}
+
+	/// Defines `alias` as a virtual key that finishes once every one of `keys` has finished, so
+	/// downstream providers can require one umbrella key instead of listing each of them.
+	///
+	/// The alias is provided by a provider that requires `keys`, and its progress is
+	/// [reported](ProgressChecker::Reported) by that provider instead of checked, so
+	/// [`SetupKey::progress_checker`] isn't called for it. Its progress stays at [`Progress::ZERO`]
+	/// until the provider runs, and its [weight](Self::key_weight) is zero, so it doesn't count
+	/// twice towards overall progress. The provider is named after the alias's
+	/// [label](Self::key_label), e.g. "alias GameReady".
+	///
+	/// Returns the `SystemId` of the alias's provider.
+	pub fn define_alias(
+		&mut self,
+		alias: K,
+		keys: impl IntoIterator<Item = K>,
+		world: &mut World,
+	) -> SystemId
+	where
+		K: Debug,
+	{
+		if !self.entries.contains_key(&alias) {
+			self.entries
+				.insert(alias.clone(), ProgressChecker::Reported);
+			self.spawn_key_entity(&alias, world);
+		}
+		let reported = alias.clone();
+		let system = world.register_system(move |mut progress: ReportProgress<K>| {
+			progress.report(reported.clone(), Progress::DONE);
+		});
+		let info = ProviderInfo {
+			requires: keys.into_iter().collect(),
+			provides: [alias.clone()].into_iter().collect(),
+			weight: Some(0.0),
+			name: format!("alias {}", self.key_label(&alias)).into(),
+			reports_progress: true,
+			..ProviderInfo::empty()
+		};
+		self.register_provider(system, info, world);
+		system
+	}
+
+	/// Removes a setup key from the setup graph.
+	///
+	/// The key's cached progress checker is unregistered from the world, and the key is removed
+	/// from the `requires` and `provides` lists of every provider. Providers themselves are kept,
+	/// even if they no longer provide anything.
+	///
+	/// Returns `true` if the key was present.
+	pub fn remove_key(&mut self, key: &K, world: &mut World) -> bool {
+		for info in self.providers.values_mut() {
+			info.remove_key(key);
+		}
+		// Indices into `provides` and `requires` may have shifted
+		self.rebuild_index();
+		self.invalidate_stages();
+		for i in 0..self.providers.len() {
+			let system = *self.providers.get_index(i).unwrap().0;
+			self.sync_provider_entity(system, world);
+		}
+		self.remove_entry(key, world)
+	}
+
+	/// Removes a key's entry and unregisters its progress checker, without touching providers.
+	fn remove_entry(&mut self, key: &K, world: &mut World) -> bool {
+		self.terminal.remove(key);
+		self.deferred_keys.remove(key);
+		self.weights.remove(key);
+		self.failed.remove(key);
+		self.completed.remove(key);
+		self.invalidated.remove(key);
+		self.restored.remove(key);
+		self.reads.remove(key);
+		self.checked_progress.remove(key);
+		self.last_key_progress.remove(key);
+		self.despawn_key_entity(key, world);
+		let Some(checker) = self.entries.shift_remove(key) else {
+			return false;
+		};
+		if let ProgressChecker::Unique(checker) = checker {
+			if let Err(e) = world.unregister_system(checker) {
+				error!("Failed to unregister progress checker: {e}");
+			}
+		}
+		true
+	}
+
+	/// Validates the setup graph for common configuration errors.
+	///
+	/// This method checks for:
+	/// - Graphs exceeding the configured [`GraphLimits`] (no further checks are performed in this
+	///   case, to avoid grinding through enormous graphs)
+	/// - Unprovided setup keys (keys that are required but never provided)
+	/// - Duplicate providers (multiple providers for the same key, unless all of them use
+	///   [`provides_any`](crate::IntoDependencyProvider::provides_any)). Fallback providers are
+	///   not counted.
+	/// - Cyclic dependencies (circular dependency chains)
+	///
+	/// A provider that requires a key it also provides (see [`SetupGraphWarning::SelfDependency`])
+	/// can never run, so its provision of that key is ignored by these checks.
+	///
+	/// This can only be used with keys that implement `Debug`, because [`InvalidSetupGraph`]
+	/// requires `K: Debug` for its `Display` implementation.
+	pub fn validate(world: &mut World) -> Result<(), InvalidSetupGraph<K>>
+	where
+		K: Debug,
+	{
+		let errors = world.resource::<SetupTracker<K>>().validation_errors();
+		if errors.is_empty() {
+			Ok(())
+		} else {
+			Err(InvalidSetupGraph { errors })
+		}
+	}
+
+	/// Returns every error [`validate`](Self::validate) would report, without requiring access to
+	/// the `World` or `K: Debug`.
+	///
+	/// Returns an empty list if the graph is valid.
+	pub fn validation_errors(&self) -> Vec<SetupGraphError<K>> {
+		if let Err(too_large) = self.check_limits() {
+			return vec![SetupGraphError::TooLarge(too_large)];
+		}
+
+		let mut unprovided = self.entries.keys().cloned().collect::<HashSet<_>>();
+		let mut providers = IndexMap::<K, Vec<String>>::new();
+		// Keys that some provider provides without opting in to alternatives
+		let mut exclusive = HashSet::<K>::new();
+
+		for info in self.providers.values() {
+			for provision in info.provides() {
+				if info.requires().contains(provision) {
+					continue;
+				}
+				if info.fallback_for().contains(provision) {
+					unprovided.remove(provision);
+					continue;
+				}
+				if !info.provides_any().contains(provision) {
+					exclusive.insert(provision.clone());
+				}
+				providers
+					.entry(provision.clone())
+					.or_default()
+					.push(info.name().to_owned());
+				unprovided.remove(provision);
+			}
+		}
+
+		let mut errors = Vec::new();
+
+		// Iterate entries rather than the set, to keep the output in a stable order
+		for key in self.entries.keys().filter(|key| unprovided.contains(*key)) {
+			let key = key.clone();
+			let mut required_by = self
+				.dependants_of(&key)
+				.map(|(id, _)| self.providers[&id].name().to_owned())
+				.collect::<Vec<_>>();
+			required_by.sort();
+			errors.push(SetupGraphError::UnprovidedKey { key, required_by });
+		}
+
+		for (key, mut providers) in providers {
+			if providers.len() > 1 && exclusive.contains(&key) {
+				providers.sort();
+				errors.push(SetupGraphError::DuplicateProviders { key, providers });
+			}
+		}
+
+		errors.extend(
+			Self::detect_cycles(self)
+				.into_iter()
+				.map(SetupGraphError::Cycle),
+		);
+
+		errors
+	}
+
+	/// Checks the setup graph for suspicious, but not necessarily invalid, configurations.
+	///
+	/// This method checks for:
+	/// - Unused provisions (keys that are provided but never required, and are not marked as
+	///   [terminal](Self::mark_terminal)). Only checked once at least one key has been marked
+	///   terminal, since otherwise every final output would be reported.
+	/// - Self-dependencies (providers that require a key they also provide)
+	///
+	/// Graphs exceeding the configured [`GraphLimits`] are not checked.
+	pub fn lint(&self) -> Vec<SetupGraphWarning<K>> {
+		let mut warnings = Vec::new();
+		if self.check_limits().is_err() {
+			return warnings;
+		}
+
+		if !self.terminal.is_empty() {
+			for key in self.entries.keys() {
+				if self.terminal.contains(key) || self.dependants_of(key).next().is_some() {
+					continue;
+				}
+				let mut provided_by = self
+					.providers_of(key)
+					.map(|(id, _)| self.providers[&id].name().to_owned())
+					.collect::<Vec<_>>();
+				provided_by.sort();
+				warnings.push(SetupGraphWarning::UnusedProvision {
+					key: key.clone(),
+					provided_by,
+				});
+			}
+		}
+
+		for info in self.providers.values() {
+			let keys = info
+				.provides()
+				.iter()
+				.filter(|key| info.requires().contains(key))
+				.cloned()
+				.collect::<Vec<_>>();
+			if !keys.is_empty() {
+				warnings.push(SetupGraphWarning::SelfDependency {
+					provider: info.name().to_owned(),
+					keys,
+				});
+			}
+		}
+
+		warnings
+	}
+
+	/// Calculates the overall progress of the setup process.
+	///
+	/// Progress is calculated as a weighted average based on each setup key's
+	/// relative time estimate and current progress. [Deferred](Self::mark_deferred) keys are not
+	/// included, see [`deferred_progress`](Self::deferred_progress).
+	pub fn progress(&self, world: &mut World) -> Progress {
+		let progress = self.weighted_progress(world, |key| !self.deferred_keys.contains(key));
+		if progress.is_nan() && !self.deferred_keys.is_empty() {
+			// Every key is deferred
+			Progress::DONE
+		} else {
+			progress
+		}
+	}
+
+	/// Calculates the progress of the [deferred](Self::mark_deferred) keys, like
+	/// [`progress`](Self::progress). Returns [`Progress::DONE`] if there are none.
+	pub fn deferred_progress(&self, world: &mut World) -> Progress {
+		if self.deferred_keys.is_empty() {
+			return Progress::DONE;
+		}
+		self.weighted_progress(world, |key| self.deferred_keys.contains(key))
+	}
+
+	fn weighted_progress(&self, world: &mut World, include: impl Fn(&K) -> bool) -> Progress {
+		let entries = self.entries.iter().filter(|(key, _)| include(key));
+		let total: f32 = entries.clone().map(|(key, _)| self.key_weight(key)).sum();
+		let sum: f32 = entries
+			.map(|(key, checker)| *self.check_key(key, checker, world) * self.key_weight(key))
+			.sum();
+		Progress::new(sum / total)
+	}
+
+	/// Checks the current progress of `key`, which uses `checker`.
+	pub(crate) fn check_key(
+		&self,
+		key: &K,
+		checker: &ProgressChecker<K>,
+		world: &mut World,
+	) -> Progress {
+		if self.invalidated.contains(key) {
+			return Progress::ZERO;
+		}
+		if self.restored.contains(key) {
+			return Progress::DONE;
+		}
+		let this_check = world.read_change_tick();
+		if let Some(progress) = self.unchanged_progress(key, self.last_check, this_check, world) {
+			return progress;
+		}
+		let _span = info_span!("setup_progress_check", key = %self.key_span_name(key)).entered();
+		checker.run(key, world)
+	}
+
+	/// The progress `key`'s checker returned the last time it ran, if it declared
+	/// [`checker_reads`](SetupKey::checker_reads) and none of them changed since `last_check`.
+	pub(crate) fn unchanged_progress(
+		&self,
+		key: &K,
+		last_check: Option<Tick>,
+		this_check: Tick,
+		world: &World,
+	) -> Option<Progress> {
+		let reads = self.reads.get(key)?;
+		let progress = self.checked_progress.get(key)?;
+		(!reads.changed(world, last_check?, this_check)).then_some(*progress)
+	}
+
+	/// Returns the last calculated progress value.
+	pub fn last_progress(&self) -> Progress {
+		self.last_progress
+	}
+
+	/// Returns recent samples of overall progress, oldest first, e.g. for drawing a sparkline that
+	/// makes stalls and regressions visible.
+	///
+	/// Progress is sampled at most every 100ms while [`advance_setup`](crate::advance_setup) runs,
+	/// and only the last 300 samples are kept.
+	pub fn progress_history(&self) -> impl ExactSizeIterator<Item = (Instant, Progress)> + '_ {
+		self.progress_history.iter().copied()
+	}
+
+	/// Records a provider's run in its [run stats](Self::run_stats) and the
+	/// [trace](Self::set_record_trace), letting fallbacks take over its provisions if it failed.
+	/// The run count is kept by [`advance_setup`](crate::advance_setup), which dispatches it.
+	pub(crate) fn record_run(
+		&mut self,
+		system: SystemId,
+		start: Instant,
+		duration: Duration,
+		error: Option<String>,
+	) {
+		self.awaiting_schedule.remove(&system);
+		// The provider may have been removed while it was running in a schedule
+		let Some(info) = self.providers.get(&system) else {
+			return;
+		};
+		if let Some(e) = &error {
+			error!("Failed to run setup system `{}`: {e}", info.name());
+			let has_fallback = |key: &K| {
+				self.provider_index.get(key).is_some_and(|providers| {
+					providers
+						.iter()
+						.any(|(provider, _)| self.providers[provider].fallback_for().contains(key))
+				})
+			};
+			let failed = info
+				.provides()
+				.iter()
+				.filter(|key| !info.fallback_for().contains(key) && has_fallback(key))
+				.cloned()
+				.collect::<Vec<_>>();
+			self.failed.extend(failed);
+		}
+		let stats = self.run_stats.entry(system).or_default();
+		stats.last_duration = duration;
+		stats.last_error = error;
+		if let Some(trace) = &mut self.trace {
+			trace.push(TraceRun {
+				system,
+				start,
+				duration,
+			});
+		}
+	}
+
+	/// Records a sample of overall progress, unless the last one is too recent.
+	pub(crate) fn sample_progress(&mut self, now: Instant, progress: Progress) {
+		if self
+			.progress_history
+			.back()
+			.is_some_and(|(last, _)| now.duration_since(*last) < PROGRESS_SAMPLE_INTERVAL)
+		{
+			return;
+		}
+		if self.progress_history.len() == PROGRESS_HISTORY_LEN {
+			self.progress_history.pop_front();
+		}
+		self.progress_history.push_back((now, progress));
+	}
+
+	/// Returns the weight of `key` in overall progress: the weight set with
+	/// [`set_key_weight`](Self::set_key_weight) or [`Provider::weight`](crate::Provider::weight),
+	/// or [`SetupKey::relative_time_estimate`] otherwise.
+	pub fn key_weight(&self, key: &K) -> f32 {
+		self.weights
+			.get(key)
+			.copied()
+			.unwrap_or_else(|| key.relative_time_estimate())
+	}
+
+	/// Overrides the weight of `key` in overall progress, or resets it to
+	/// [`SetupKey::relative_time_estimate`] if `weight` is `None`.
+	pub fn set_key_weight(&mut self, key: K, weight: Option<f32>) {
+		match weight {
+			Some(weight) => self.weights.insert(key, weight),
+			None => self.weights.remove(&key),
+		};
+	}
+
+	/// Returns the progress of the keys in a [group](SetupKey::group), weighted like
+	/// [`progress`](Self::progress), as of the last time [`advance_setup`](crate::advance_setup)
+	/// ran. Keys that haven't been checked yet count as having no progress.
+	///
+	/// Returns `None` if no key belongs to the group.
+	pub fn group_progress(&self, group: &str) -> Option<Progress> {
+		let (sum, total) = self
+			.entries
+			.keys()
+			.filter(|key| key.group().is_some_and(|key_group| key_group == group))
+			.fold((0.0, 0.0), |(sum, total), key| {
+				let weight = self.key_weight(key);
+				let progress = self.last_key_progress(key).unwrap_or(Progress::ZERO);
+				(sum + *progress * weight, total + weight)
+			});
+		(total > 0.0).then(|| Progress::new(sum / total))
+	}
+
+	/// Returns the [groups](SetupKey::group) of the tracker's keys, in the order they first
+	/// appear.
+	pub fn groups(&self) -> Vec<Cow<'static, str>> {
+		let mut groups = Vec::new();
+		for group in self.entries.keys().filter_map(SetupKey::group) {
+			if !groups.contains(&group) {
+				groups.push(group);
+			}
+		}
+		groups
+	}
+
+	/// Returns the last calculated progress of the [deferred](Self::mark_deferred) keys.
+	pub fn last_deferred_progress(&self) -> Progress {
+		self.last_deferred_progress
+	}
+
+	/// Returns the progress of `key` as of the last time [`advance_setup`](crate::advance_setup)
+	/// ran its progress checker, without running it again.
+	pub fn last_key_progress(&self, key: &K) -> Option<Progress> {
+		self.last_key_progress.get(key).copied()
+	}
+
+	/// Marks `key` as unfinished, so its providers run again even if its progress checker reports
+	/// that it is finished.
 	///
-	/// This method automatically registers progress checkers for any setup keys
-	/// that haven't been seen before.
-	pub fn register_provider(
-		&mut self,
-		system: SystemId,
-		provider: ProviderInfo<K>,
-		world: &mut World,
-	) {
-		for req in provider.requires() {
-			if !self.entries.contains_key(req) {
-				self.entries
-					.insert(req.clone(), req.register_progress_checker(world));
-			}
+	/// The key counts as having no progress until one of its providers has been dispatched again,
+	/// after which its progress checker is used as normal. [`OnSetupKeyComplete`] is triggered
+	/// again when it finishes, as is [`OnSetupComplete`] if setup had already finished. The key's
+	/// failure state and its providers' [timeouts](crate::Provider::timeout) are reset. If its
+	/// progress is [reported](ProgressChecker::Reported), the reported progress is cleared when a
+	/// provider is dispatched again, so it isn't finished again before the provider reports it.
+	///
+	/// Keys that depend on `key` are unaffected, see
+	/// [`invalidate_downstream`](Self::invalidate_downstream) to re-run them as well.
+	///
+	/// Returns `false` if the key isn't part of the setup graph.
+	///
+	/// [`OnSetupKeyComplete`]: crate::OnSetupKeyComplete
+	/// [`OnSetupComplete`]: crate::OnSetupComplete
+	pub fn invalidate(&mut self, key: &K) -> bool {
+		if !self.entries.contains_key(key) {
+			return false;
 		}
-		for prov in provider.provides() {
-			if !self.entries.contains_key(prov) {
-				self.entries
-					.insert(prov.clone(), prov.register_progress_checker(world));
-			}
+		self.invalidated.insert(key.clone());
+		self.restored.remove(key);
+		self.completed.remove(key);
+		self.failed.remove(key);
+		self.last_key_progress.insert(key.clone(), Progress::ZERO);
+		for (system, _) in self.provider_index.get(key).into_iter().flatten() {
+			self.started.remove(system);
 		}
-		self.providers.insert(system, provider);
+		true
 	}
 
-	/// Validates the setup graph for common configuration errors.
-	///
-	/// This method checks for:
-	/// - Unprovided setup keys (keys that are required but never provided)
-	/// - Duplicate providers (multiple providers for the same key)
-	/// - Cyclic dependencies (circular dependency chains)
+	/// [Invalidates](Self::invalidate) `key` and every key provided by a provider that requires
+	/// it, transitively, so everything downstream of `key` runs again, e.g. after a settings change.
 	///
-	/// This can only be used with keys that implement `Debug`, because [`InvalidSetupGraph`]
-	/// requires `K: Debug` for its `Display` implementation.
-	pub fn validate(world: &mut World) -> Result<(), InvalidSetupGraph<K>>
-	where
-		K: Debug,
-	{
-		world.resource_scope::<SetupTracker<K>, _>(|_, tracker| {
-			let mut unprovided = tracker.entries.keys().cloned().collect::<HashSet<_>>();
-			let mut providers = HashMap::<K, Vec<SystemId>>::new();
-
-			for (system, info) in tracker.providers.iter() {
-				for provision in info.provides() {
-					providers
-						.entry(provision.clone())
-						.or_insert_with(Vec::new)
-						.push(*system);
-					unprovided.remove(provision);
+	/// Returns the invalidated keys, starting with `key`, or an empty list if `key` isn't part of
+	/// the setup graph.
+	pub fn invalidate_downstream(&mut self, key: &K) -> Vec<K> {
+		if !self.entries.contains_key(key) {
+			return Vec::new();
+		}
+		let mut invalidated = vec![key.clone()];
+		let mut seen = [key.clone()].into_iter().collect::<HashSet<_>>();
+		let mut i = 0;
+		while let Some(key) = invalidated.get(i).cloned() {
+			for (system, _) in self.dependants_of(&key) {
+				for provided in self.providers[&system].provides() {
+					if seen.insert(provided.clone()) {
+						invalidated.push(provided.clone());
+					}
 				}
 			}
+			i += 1;
+		}
+		for key in &invalidated {
+			self.invalidate(key);
+		}
+		invalidated
+	}
 
-			providers.retain(|_, providers| providers.len() > 1);
+	/// Returns `true` if `key` has been [invalidated](Self::invalidate) and none of its providers
+	/// have run since.
+	pub fn is_invalidated(&self, key: &K) -> bool {
+		self.invalidated.contains(key)
+	}
 
-			let cyclic_dependencies = Self::detect_cycles(&tracker);
+	/// Returns `true` if the provider registered with `system` has been run at least once.
+	pub fn has_started(&self, system: SystemId) -> bool {
+		self.started.contains_key(&system)
+	}
 
-			if !unprovided.is_empty() || !providers.is_empty() || !cyclic_dependencies.is_empty() {
-				Err(InvalidSetupGraph {
-					unprovided,
-					duplicate_providers: providers,
-					cyclic_dependencies,
-				})
-			} else {
-				Ok(())
-			}
-		})
+	/// Returns statistics about the runs of the provider registered with `system`, or `None` if it
+	/// hasn't run yet.
+	pub fn run_stats(&self, system: SystemId) -> Option<&ProviderRunStats> {
+		self.run_stats.get(&system)
 	}
 
-	/// Calculates the overall progress of the setup process.
-	///
-	/// Progress is calculated as a weighted average based on each setup key's
-	/// relative time estimate and current progress.
-	pub fn progress(&self, world: &mut World) -> Progress {
-		let total: f32 = self.entries.keys().map(K::relative_time_estimate).sum();
-		let sum: f32 = self
-			.entries
-			.iter()
-			.map(|(key, checker)| {
-				*world.run_system(*checker).unwrap() * key.relative_time_estimate()
-			})
-			.sum();
-		Progress::new(sum / total)
+	/// Returns which keys changed progress and which providers ran the last time
+	/// [`advance_setup`](crate::advance_setup) checked progress, e.g. to find out why a provider
+	/// ran more than once.
+	pub fn last_frame_diff(&self) -> &FrameDiff<K> {
+		&self.last_frame_diff
 	}
 
-	/// Returns the last calculated progress value.
-	pub fn last_progress(&self) -> Progress {
-		self.last_progress
+	/// Returns whether [`last_frame_diff`](Self::last_frame_diff) is logged whenever it isn't
+	/// empty.
+	pub fn log_frame_diffs(&self) -> bool {
+		self.log_frame_diffs
+	}
+
+	/// Sets whether [`last_frame_diff`](Self::last_frame_diff) is logged whenever it isn't empty.
+	pub fn set_log_frame_diffs(&mut self, log: bool) {
+		self.log_frame_diffs = log;
+	}
+
+	/// Returns what happens when a key's progress decreases between checks.
+	pub fn regression_policy(&self) -> RegressionPolicy {
+		self.regression_policy
+	}
+
+	/// Sets what happens when a key's progress decreases between checks.
+	pub fn set_regression_policy(&mut self, policy: RegressionPolicy) {
+		self.regression_policy = policy;
 	}
 
 	/// Returns a reference to the setup entries map.
-	pub fn entries(&self) -> &HashMap<K, ProgressCheckerId> {
+	pub fn entries(&self) -> &IndexMap<K, ProgressChecker<K>> {
 		&self.entries
 	}
 
 	/// Returns a reference to the providers map.
-	pub fn providers(&self) -> &HashMap<SystemId, ProviderInfo<K>> {
+	pub fn providers(&self) -> &IndexMap<SystemId, ProviderInfo<K>> {
 		&self.providers
 	}
 
@@ -132,13 +1507,7 @@ impl<K: SetupKey> SetupTracker<K> {
 		&'a self,
 		key: &'b K,
 	) -> impl Iterator<Item = (SystemId, usize)> + use<'a, 'b, K> {
-		self.providers.iter().filter_map(|(id, info)| {
-			info.provides()
-				.iter()
-				.enumerate()
-				.find(|(_, item)| **item == *key)
-				.map(|(i, _)| (*id, i))
-		})
+		self.provider_index.get(key).into_iter().flatten().copied()
 	}
 
 	/// Returns an iterator over all providers that depend on the given key.
@@ -146,39 +1515,41 @@ impl<K: SetupKey> SetupTracker<K> {
 		&'a self,
 		key: &'b K,
 	) -> impl Iterator<Item = (SystemId, usize)> + use<'a, 'b, K> {
-		self.providers.iter().filter_map(|(id, info)| {
-			info.requires()
-				.iter()
-				.enumerate()
-				.find(|(_, item)| **item == *key)
-				.map(|(i, _)| (*id, i))
-		})
+		self.dependant_index.get(key).into_iter().flatten().copied()
 	}
 
 	/// Detects cycles in the dependency graph using depth-first search.
 	///
-	/// Returns a set of setup keys that are part of dependency cycles.
-	fn detect_cycles(tracker: &SetupTracker<K>) -> HashSet<K> {
+	/// Returns the concrete path of each cycle found. Every back edge found during the search
+	/// produces one cycle, so a key may appear in more than one cycle.
+	fn detect_cycles(tracker: &SetupTracker<K>) -> Vec<DependencyCycle<K>> {
 		let mut visited = HashSet::new();
-		let mut rec_stack = HashSet::new();
-		let mut cycles = HashSet::new();
+		let mut cycles = Vec::new();
 
-		// Build a dependency graph: key -> keys it depends on
-		let mut dependencies = HashMap::<K, Vec<K>>::new();
+		// Build a dependency graph: key -> (key it depends on, name of the provider of that edge)
+		let mut dependencies = HashMap::<K, Vec<(K, String)>>::new();
 
 		// Initialize all keys
 		for key in tracker.entries.keys() {
 			dependencies.entry(key.clone()).or_default();
 		}
 
-		// Populate dependencies from provider requirements
+		// Populate dependencies from provider requirements. Self-dependencies are reported by
+		// `lint` instead.
 		for (_, info) in tracker.providers.iter() {
 			for provided in info.provides() {
-				for required in info.requires() {
+				let optional = info
+					.requires_optional()
+					.iter()
+					.filter(|key| tracker.entries.contains_key(*key));
+				for required in info.requires().iter().chain(optional) {
+					if required == provided {
+						continue;
+					}
 					dependencies
 						.entry(provided.clone())
 						.or_default()
-						.push(required.clone());
+						.push((required.clone(), info.name().to_owned()));
 				}
 			}
 		}
@@ -186,13 +1557,7 @@ impl<K: SetupKey> SetupTracker<K> {
 		// Perform DFS for each unvisited node
 		for key in tracker.entries.keys() {
 			if !visited.contains(key) {
-				Self::dfs_cycle_detection(
-					key,
-					&dependencies,
-					&mut visited,
-					&mut rec_stack,
-					&mut cycles,
-				);
+				Self::dfs_cycle_detection(key, &dependencies, &mut visited, &mut cycles);
 			}
 		}
 
@@ -200,55 +1565,199 @@ impl<K: SetupKey> SetupTracker<K> {
 	}
 
 	/// Depth-first search helper for cycle detection.
+	///
+	/// Uses an explicit stack rather than recursion so that long dependency chains can't overflow
+	/// the call stack.
 	fn dfs_cycle_detection(
-		key: &K,
-		dependencies: &HashMap<K, Vec<K>>,
+		start: &K,
+		dependencies: &HashMap<K, Vec<(K, String)>>,
 		visited: &mut HashSet<K>,
-		rec_stack: &mut HashSet<K>,
-		cycles: &mut HashSet<K>,
+		cycles: &mut Vec<DependencyCycle<K>>,
 	) {
-		visited.insert(key.clone());
-		rec_stack.insert(key.clone());
-
-		if let Some(deps) = dependencies.get(key) {
-			for dep in deps {
-				if !visited.contains(dep) {
-					Self::dfs_cycle_detection(dep, dependencies, visited, rec_stack, cycles);
-				} else if rec_stack.contains(dep) {
-					// Found a cycle - mark all nodes in the current recursion stack as cyclic
-					// This includes all nodes from the current path back to the cycle start
-					for node in rec_stack.iter() {
-						cycles.insert(node.clone());
-					}
-					cycles.insert(dep.clone()); // Also mark the target of the back edge
+		// Current path, with the index of the next dependency to visit for each key
+		let mut stack = vec![(start.clone(), 0usize)];
+		// Position of each key in `stack`
+		let mut on_stack = HashMap::<K, usize>::new();
+		visited.insert(start.clone());
+		on_stack.insert(start.clone(), 0);
+
+		while let Some((key, next)) = stack.last_mut() {
+			let deps = dependencies.get(key).map(Vec::as_slice).unwrap_or_default();
+			let Some((dep, _)) = deps.get(*next) else {
+				on_stack.remove(key);
+				stack.pop();
+				continue;
+			};
+			*next += 1;
+
+			if let Some(&pos) = on_stack.get(dep) {
+				// Found a back edge - the cycle is the path from `dep` to the top of the stack
+				let (path, via) = stack[pos..]
+					.iter()
+					.map(|(key, next)| (key.clone(), dependencies[key][*next - 1].1.clone()))
+					.unzip();
+				cycles.push(DependencyCycle { path, via });
+			} else if !visited.contains(dep) {
+				let dep = dep.clone();
+				visited.insert(dep.clone());
+				on_stack.insert(dep.clone(), stack.len());
+				stack.push((dep, 0));
+			}
+		}
+	}
+
+	/// Returns the index of the stage each provider belongs to in [`stages`](Self::stages).
+	pub(crate) fn stage_indices(&self) -> HashMap<SystemId, usize> {
+		self.stages()
+			.iter()
+			.enumerate()
+			.flat_map(|(i, stage)| stage.iter().map(move |id| (*id, i)))
+			.collect()
+	}
+
+	/// Returns the index of the earliest [stage](Self::stages) that provides the given key.
+	///
+	/// Returns `None` if the key isn't provided by any provider.
+	pub fn stage_of_key(&self, key: &K) -> Option<usize> {
+		let stages = self.stage_indices();
+		self.providers_of(key)
+			.filter_map(|(id, _)| stages.get(&id).copied())
+			.min()
+	}
+
+	/// Calculates the progress of each [stage](Self::stages).
+	///
+	/// Each key counts towards the earliest stage that provides it (see
+	/// [`stage_of_key`](Self::stage_of_key)), weighted by its relative time estimate. Keys are
+	/// checked the same way as for [`progress`](Self::progress), so invalidated and restored keys
+	/// and unchanged [`checker_reads`](SetupKey::checker_reads) agree with it. Stages that don't
+	/// provide any keys are reported as [`Progress::DONE`].
+	pub fn progress_by_stage(&self, world: &mut World) -> Vec<Progress> {
+		let stages = self.stage_indices();
+		let stage_count = stages.values().max().map_or(0, |max| max + 1);
+		let mut totals = vec![(0.0, 0.0); stage_count];
+		for (key, checker) in self.entries.iter() {
+			let Some(stage) = self
+				.providers_of(key)
+				.filter_map(|(id, _)| stages.get(&id).copied())
+				.min()
+			else {
+				continue;
+			};
+			let weight = self.key_weight(key);
+			let (sum, total) = &mut totals[stage];
+			*sum += *self.check_key(key, checker, world) * weight;
+			*total += weight;
+		}
+		totals
+			.into_iter()
+			.map(|(sum, total)| {
+				if total == 0.0 {
+					Progress::DONE
+				} else {
+					Progress::new(sum / total)
+				}
+			})
+			.collect()
+	}
+
+	/// Returns the stage currently being dispatched in
+	/// [strict stage mode](Self::set_strict_stages).
+	pub fn current_stage(&self) -> usize {
+		self.current_stage
+	}
+
+	/// Returns the critical path through the setup graph: the chain of providers, each requiring a
+	/// key provided by the one before it, with the largest total weight.
+	///
+	/// This is the chain that bounds how fast setup can finish, no matter how much of the rest of
+	/// the graph runs in parallel. Providers that can never run, e.g. because of a cycle, are
+	/// ignored.
+	pub fn critical_path(&self, weight: PathWeight) -> Vec<SystemId> {
+		// The heaviest chain ending at each provider, and the provider before it in that chain
+		let mut best = HashMap::<SystemId, (f32, Option<SystemId>)>::new();
+		let mut end = None::<(SystemId, f32)>;
+		for &system in self.stages().iter().flatten() {
+			let info = &self.providers[&system];
+			let own = match weight {
+				PathWeight::Estimated => {
+					info.provides().iter().map(|key| self.key_weight(key)).sum()
 				}
+				PathWeight::Measured => info
+					.provides()
+					.iter()
+					.filter_map(|key| self.key_elapsed(key))
+					.max()
+					.map_or(0.0, |elapsed| elapsed.as_secs_f32()),
+			};
+			let previous = info
+				.requires()
+				.iter()
+				.chain(info.requires_optional())
+				.flat_map(|key| self.providers_of(key))
+				.filter_map(|(id, _)| Some((id, best.get(&id)?.0)))
+				.max_by(|a, b| a.1.total_cmp(&b.1));
+			let total = own + previous.map_or(0.0, |(_, total)| total);
+			best.insert(system, (total, previous.map(|(id, _)| id)));
+			if end.is_none_or(|(_, longest)| total > longest) {
+				end = Some((system, total));
 			}
 		}
 
-		rec_stack.remove(key);
+		let mut path = Vec::new();
+		let mut next = end.map(|(id, _)| id);
+		while let Some(id) = next {
+			path.push(id);
+			next = best[&id].1;
+		}
+		path.reverse();
+		path
 	}
 
 	/// Returns the setup stages in dependency order.
 	///
 	/// Each stage contains provider systems that can run in parallel,
 	/// with later stages depending on earlier stages.
-	pub fn stages(&self) -> Vec<Vec<SystemId>> {
-		let mut provided_so_far = HashSet::new();
+	///
+	/// The stages are cached until a provider is registered or removed, or a key is removed.
+	pub fn stages(&self) -> &[Vec<SystemId>] {
+		self.stage_cache.get_or_init(|| self.compute_stages())
+	}
+
+	/// Clears the cached [`stages`](Self::stages), after the setup graph has changed.
+	fn invalidate_stages(&mut self) {
+		self.stage_cache.take();
+	}
+
+	fn compute_stages(&self) -> Vec<Vec<SystemId>> {
+		let mut provided_so_far = HashSet::<&K>::new();
 		let mut stages: Vec<Vec<SystemId>> = Vec::new();
-		let mut providers = self.providers.clone();
+		// Indices into `self.providers` of providers that haven't been staged yet
+		let mut remaining = (0..self.providers.len()).collect::<Vec<_>>();
+		let all_provided = self
+			.providers
+			.values()
+			.flat_map(|info| info.provides())
+			.collect::<HashSet<_>>();
 
-		while !providers.is_empty() {
+		while !remaining.is_empty() {
 			let mut stage = Vec::new();
 			let mut provided_this_stage = Vec::new();
 
-			providers.retain(|id, info| {
-				for req in info.requires().iter() {
+			remaining.retain(|&i| {
+				let (id, info) = self.providers.get_index(i).unwrap();
+				for req in info.requires() {
 					if !provided_so_far.contains(req) {
 						return true;
 					}
 				}
+				for req in info.requires_optional() {
+					if all_provided.contains(req) && !provided_so_far.contains(req) {
+						return true;
+					}
+				}
 				stage.push(*id);
-				provided_this_stage.extend_from_slice(info.provides());
+				provided_this_stage.extend(info.provides());
 				false
 			});
 
@@ -265,21 +1774,154 @@ impl<K: SetupKey> SetupTracker<K> {
 	}
 }
 
-/// Error type for invalid setup graph configurations.
-#[derive(Debug, Clone)]
-pub struct InvalidSetupGraph<K: SetupKey> {
-	pub unprovided: HashSet<K>,
-	pub duplicate_providers: HashMap<K, Vec<SystemId>>,
-	pub cyclic_dependencies: HashSet<K>,
+/// How to weigh each provider when finding the [critical path](SetupTracker::critical_path).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PathWeight {
+	/// The sum of the [weights](SetupTracker::key_weight) of the keys the provider provides.
+	#[default]
+	Estimated,
+	/// How long the provider's keys took to finish so far, in seconds (see
+	/// [`SetupTracker::key_elapsed`]). Providers that haven't started weigh nothing.
+	Measured,
+}
+
+/// Callback set by [`SetupTracker::set_key_labeler`].
+type KeyLabelerFn<K> = dyn Fn(&K) -> Option<Cow<'static, str>> + Send + Sync;
+
+/// Wrapper for the key labeler callback, so the tracker can still implement `Debug`.
+pub(crate) struct KeyLabeler<K>(Option<Box<KeyLabelerFn<K>>>);
+
+impl<K> Debug for KeyLabeler<K> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_tuple("KeyLabeler")
+			.field(&self.0.as_ref().map(|_| ".."))
+			.finish()
+	}
+}
+
+/// How long [`SetupTracker::run_to_completion`] parks after the first pass that changes nothing.
+const MIN_IDLE: Duration = Duration::from_millis(1);
+
+/// The longest [`SetupTracker::run_to_completion`] parks between passes.
+const MAX_IDLE: Duration = Duration::from_millis(16);
+
+/// Lets tasks make progress between the passes of [`SetupTracker::run_to_completion`], parking
+/// the thread for `idle` if it's not zero. Tasks spawned without the `multi_threaded` feature of
+/// `bevy_tasks` run on the main thread, and are only polled when ticked.
+fn yield_to_tasks(idle: Duration) {
+	#[cfg(not(target_arch = "wasm32"))]
+	{
+		bevy_tasks::tick_global_task_pools_on_main_thread();
+		if idle.is_zero() {
+			std::thread::yield_now();
+		} else {
+			std::thread::park_timeout(idle);
+		}
+	}
+	#[cfg(target_arch = "wasm32")]
+	let _ = idle;
+}
+
+/// The default [name formatter](SetupTracker::set_name_formatter), which strips module paths,
+/// including inside generic arguments.
+pub fn default_name_formatter(type_name: &str) -> String {
+	disqualified::ShortName(type_name).to_string()
+}
+
+/// Statistics about the runs of a single provider. See [`SetupTracker::run_stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProviderRunStats {
+	pub(crate) runs: u32,
+	pub(crate) last_duration: Duration,
+	pub(crate) last_error: Option<String>,
+}
+
+impl ProviderRunStats {
+	/// Returns the number of times the provider has run.
+	pub fn runs(&self) -> u32 {
+		self.runs
+	}
+
+	/// Returns how long the provider's most recent run took.
+	pub fn last_duration(&self) -> Duration {
+		self.last_duration
+	}
+
+	/// Returns the error from the provider's most recent run, if it failed.
+	pub fn last_error(&self) -> Option<&str> {
+		self.last_error.as_deref()
+	}
+}
+
+/// What changed during a single run of [`advance_setup`](crate::advance_setup). See
+/// [`SetupTracker::last_frame_diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameDiff<K: SetupKey> {
+	/// Keys whose progress changed, with their previous and new progress, in registration order.
+	/// Keys checked for the first time count as changed from [`Progress::ZERO`].
+	pub progress: Vec<(K, Progress, Progress)>,
+	/// Providers that ran, in the order they ran.
+	pub ran: Vec<SystemId>,
+}
+
+impl<K: SetupKey> Default for FrameDiff<K> {
+	fn default() -> Self {
+		Self {
+			progress: Vec::new(),
+			ran: Vec::new(),
+		}
+	}
+}
+
+impl<K: SetupKey> FrameDiff<K> {
+	/// Whether no key changed progress and no provider ran.
+	pub fn is_empty(&self) -> bool {
+		self.progress.is_empty() && self.ran.is_empty()
+	}
+}
+
+/// A single provider run, recorded if [`SetupTracker::set_record_trace`] is enabled.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TraceRun {
+	pub(crate) system: SystemId,
+	pub(crate) start: Instant,
+	pub(crate) duration: Duration,
+}
+
+/// A system to run once progress reaches a threshold. See [`SetupTracker::add_milestone`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Milestone {
+	threshold: Progress,
+	system: SystemId,
+	fired: bool,
 }
 
-impl<K: SetupKey + Debug> std::fmt::Display for InvalidSetupGraph<K> {
-	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-		<Self as Debug>::fmt(self, f)
+impl Milestone {
+	fn reached(&self, progress: Progress) -> bool {
+		progress.finished() || (progress.is_finite() && progress >= self.threshold)
 	}
 }
 
-impl<K: SetupKey + Debug> std::error::Error for InvalidSetupGraph<K> {}
+/// Size limits for a setup graph.
+///
+/// Graphs exceeding these limits are rejected by [`SetupTracker::validate`] and summarized instead
+/// of drawn by the visualization, rather than grinding the app to a halt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphLimits {
+	/// Maximum number of distinct setup keys.
+	pub max_keys: usize,
+	/// Maximum number of edges (the sum of every provider's requirements and provisions).
+	pub max_edges: usize,
+}
+
+impl Default for GraphLimits {
+	fn default() -> Self {
+		Self {
+			max_keys: 10_000,
+			max_edges: 100_000,
+		}
+	}
+}
 
 /// System to validate the setup graph at startup.
 ///
@@ -310,26 +1952,130 @@ mod tests {
 		fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
 			world.register_system(|| Progress::DONE)
 		}
+
+		fn group(&self) -> Option<Cow<'static, str>> {
+			match self {
+				TestSetupKey::A | TestSetupKey::B => Some(Cow::Borrowed("first")),
+				TestSetupKey::C => Some(Cow::Borrowed("second")),
+				TestSetupKey::D => None,
+			}
+		}
 	}
 
 	#[test]
-	fn test_cycle_detection() {
+	fn test_key_weight() {
 		let mut world = World::new();
 		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo {
+				weight: Some(3.0),
+				..ProviderInfo::new(vec![], vec![TestSetupKey::A], Cow::Borrowed("a"))
+			},
+			&mut world,
+		);
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(vec![], vec![TestSetupKey::B], Cow::Borrowed("b")),
+			&mut world,
+		);
+		assert_eq!(tracker.key_weight(&TestSetupKey::A), 3.0);
+		assert_eq!(tracker.key_weight(&TestSetupKey::B), 1.0);
 
-		// Add entries for all keys
-		tracker
-			.entries
-			.insert(TestSetupKey::A, world.register_system(|| Progress::DONE));
 		tracker
-			.entries
-			.insert(TestSetupKey::B, world.register_system(|| Progress::DONE));
-		tracker
-			.entries
-			.insert(TestSetupKey::C, world.register_system(|| Progress::DONE));
+			.last_key_progress
+			.insert(TestSetupKey::A, Progress::DONE);
+		assert_eq!(tracker.group_progress("first"), Some(Progress::new(0.75)));
+
+		tracker.set_key_weight(TestSetupKey::A, None);
+		assert_eq!(tracker.key_weight(&TestSetupKey::A), 1.0);
+	}
+
+	#[test]
+	fn test_remove_provider_weight() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		let weighted = world.register_system(|| {});
+		tracker.register_provider(
+			weighted,
+			ProviderInfo {
+				weight: Some(3.0),
+				..ProviderInfo::new(vec![], vec![TestSetupKey::A], Cow::Borrowed("a"))
+			},
+			&mut world,
+		);
+		// Keeps the key around after the weighted provider is removed
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(
+				vec![TestSetupKey::A],
+				vec![TestSetupKey::B],
+				Cow::Borrowed("b"),
+			),
+			&mut world,
+		);
+		assert_eq!(tracker.key_weight(&TestSetupKey::A), 3.0);
+
+		tracker.remove_provider(weighted, &mut world);
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(vec![], vec![TestSetupKey::A], Cow::Borrowed("a2")),
+			&mut world,
+		);
+		assert_eq!(tracker.key_weight(&TestSetupKey::A), 1.0);
+	}
+
+	#[test]
+	fn test_group_progress() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(
+				vec![],
+				vec![
+					TestSetupKey::A,
+					TestSetupKey::B,
+					TestSetupKey::C,
+					TestSetupKey::D,
+				],
+				Cow::Borrowed("provider"),
+			),
+			&mut world,
+		);
+		assert_eq!(tracker.groups(), ["first", "second"]);
+		assert_eq!(tracker.group_progress("first"), Some(Progress::ZERO));
+
 		tracker
-			.entries
-			.insert(TestSetupKey::D, world.register_system(|| Progress::DONE));
+			.last_key_progress
+			.insert(TestSetupKey::A, Progress::DONE);
+		assert_eq!(tracker.group_progress("first"), Some(Progress::new(0.5)));
+		assert_eq!(tracker.group_progress("second"), Some(Progress::ZERO));
+		assert_eq!(tracker.group_progress("third"), None);
+	}
+
+	#[test]
+	fn test_cycle_detection() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+
+		// Add entries for all keys
+		tracker.entries.insert(
+			TestSetupKey::A,
+			world.register_system(|| Progress::DONE).into(),
+		);
+		tracker.entries.insert(
+			TestSetupKey::B,
+			world.register_system(|| Progress::DONE).into(),
+		);
+		tracker.entries.insert(
+			TestSetupKey::C,
+			world.register_system(|| Progress::DONE).into(),
+		);
+		tracker.entries.insert(
+			TestSetupKey::D,
+			world.register_system(|| Progress::DONE).into(),
+		);
 
 		// Create a cycle: A -> B -> C -> A
 		let provider_a = ProviderInfo::new(
@@ -365,13 +2111,29 @@ mod tests {
 			.insert(world.register_system(|| {}), provider_d);
 
 		let cycles = SetupTracker::detect_cycles(&tracker);
+		assert_eq!(cycles.len(), 1);
+		let cycle = &cycles[0];
 
 		// A, B, C should be detected as part of the cycle
-		assert!(cycles.contains(&TestSetupKey::A));
-		assert!(cycles.contains(&TestSetupKey::B));
-		assert!(cycles.contains(&TestSetupKey::C));
+		assert_eq!(cycle.path.len(), 3);
+		assert!(cycle.path.contains(&TestSetupKey::A));
+		assert!(cycle.path.contains(&TestSetupKey::B));
+		assert!(cycle.path.contains(&TestSetupKey::C));
 		// D should not be part of the cycle
-		assert!(!cycles.contains(&TestSetupKey::D));
+		assert!(!cycle.path.contains(&TestSetupKey::D));
+
+		// Each key depends on the next one, via the provider that provides it
+		let len = cycle.path.len();
+		for i in 0..len {
+			let (key, next) = (&cycle.path[i], &cycle.path[(i + 1) % len]);
+			let expected = match (key, next) {
+				(TestSetupKey::A, TestSetupKey::C) => "provider_a",
+				(TestSetupKey::B, TestSetupKey::A) => "provider_b",
+				(TestSetupKey::C, TestSetupKey::B) => "provider_c",
+				other => panic!("unexpected edge {other:?}"),
+			};
+			assert_eq!(cycle.via[i], expected);
+		}
 	}
 
 	#[test]
@@ -380,15 +2142,18 @@ mod tests {
 		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
 
 		// Add entries for all keys
-		tracker
-			.entries
-			.insert(TestSetupKey::A, world.register_system(|| Progress::DONE));
-		tracker
-			.entries
-			.insert(TestSetupKey::B, world.register_system(|| Progress::DONE));
-		tracker
-			.entries
-			.insert(TestSetupKey::C, world.register_system(|| Progress::DONE));
+		tracker.entries.insert(
+			TestSetupKey::A,
+			world.register_system(|| Progress::DONE).into(),
+		);
+		tracker.entries.insert(
+			TestSetupKey::B,
+			world.register_system(|| Progress::DONE).into(),
+		);
+		tracker.entries.insert(
+			TestSetupKey::C,
+			world.register_system(|| Progress::DONE).into(),
+		);
 
 		// Create a linear dependency chain: A -> B -> C (no cycle)
 		let provider_a =
@@ -428,15 +2193,15 @@ mod tests {
 		// Add progress checkers that return different values
 		tracker.entries.insert(
 			TestSetupKey::A,
-			world.register_system(|| Progress::new(0.5)), // 50% complete
+			world.register_system(|| Progress::new(0.5)).into(), // 50% complete
 		);
 		tracker.entries.insert(
 			TestSetupKey::B,
-			world.register_system(|| Progress::new(1.0)), // 100% complete
+			world.register_system(|| Progress::new(1.0)).into(), // 100% complete
 		);
 		tracker.entries.insert(
 			TestSetupKey::C,
-			world.register_system(|| Progress::new(0.0)), // 0% complete
+			world.register_system(|| Progress::new(0.0)).into(), // 0% complete
 		);
 
 		let progress = tracker.progress(&mut world);
@@ -453,15 +2218,15 @@ mod tests {
 
 		tracker.entries.insert(
 			TestSetupKey::A,
-			world.register_system(|| Progress::new(1.0)), // Complete, weight 1.0
+			world.register_system(|| Progress::new(1.0)).into(), // Complete, weight 1.0
 		);
 		tracker.entries.insert(
 			TestSetupKey::B,
-			world.register_system(|| Progress::new(0.5)), // Half done, weight 2.0
+			world.register_system(|| Progress::new(0.5)).into(), // Half done, weight 2.0
 		);
 		tracker.entries.insert(
 			TestSetupKey::C,
-			world.register_system(|| Progress::new(0.0)), // Not started, weight 1.0
+			world.register_system(|| Progress::new(0.0)).into(), // Not started, weight 1.0
 		);
 
 		let progress = tracker.progress(&mut world);
@@ -475,15 +2240,18 @@ mod tests {
 		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
 
 		// Add progress checkers
-		tracker
-			.entries
-			.insert(TestSetupKey::A, world.register_system(|| Progress::DONE));
-		tracker
-			.entries
-			.insert(TestSetupKey::B, world.register_system(|| Progress::ZERO));
-		tracker
-			.entries
-			.insert(TestSetupKey::C, world.register_system(|| Progress::ZERO));
+		tracker.entries.insert(
+			TestSetupKey::A,
+			world.register_system(|| Progress::DONE).into(),
+		);
+		tracker.entries.insert(
+			TestSetupKey::B,
+			world.register_system(|| Progress::ZERO).into(),
+		);
+		tracker.entries.insert(
+			TestSetupKey::C,
+			world.register_system(|| Progress::ZERO).into(),
+		);
 
 		// Provider that requires A (complete) and provides B (incomplete)
 		let provider = ProviderInfo::new(
@@ -512,18 +2280,22 @@ mod tests {
 		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
 
 		// Add entries
-		tracker
-			.entries
-			.insert(TestSetupKey::A, world.register_system(|| Progress::DONE));
-		tracker
-			.entries
-			.insert(TestSetupKey::B, world.register_system(|| Progress::DONE));
-		tracker
-			.entries
-			.insert(TestSetupKey::C, world.register_system(|| Progress::DONE));
-		tracker
-			.entries
-			.insert(TestSetupKey::D, world.register_system(|| Progress::DONE));
+		tracker.entries.insert(
+			TestSetupKey::A,
+			world.register_system(|| Progress::DONE).into(),
+		);
+		tracker.entries.insert(
+			TestSetupKey::B,
+			world.register_system(|| Progress::DONE).into(),
+		);
+		tracker.entries.insert(
+			TestSetupKey::C,
+			world.register_system(|| Progress::DONE).into(),
+		);
+		tracker.entries.insert(
+			TestSetupKey::D,
+			world.register_system(|| Progress::DONE).into(),
+		);
 
 		// Create dependency chain: A -> B -> C, D (independent)
 		let provider_a =
@@ -551,19 +2323,50 @@ mod tests {
 		tracker.providers.insert(system_c, provider_c);
 		tracker.providers.insert(system_d, provider_d);
 
-		let stages = tracker.stages();
+		let stages = tracker.stages();
+
+		// Should have 3 stages: [A, D], [B], [C]
+		assert_eq!(stages.len(), 3);
+		assert_eq!(stages[0].len(), 2); // A and D can run in parallel
+		assert_eq!(stages[1].len(), 1); // B depends on A
+		assert_eq!(stages[2].len(), 1); // C depends on B
+
+		// Check that A and D are in the first stage, in registration order
+		assert_eq!(stages[0], [system_a, system_d]);
+		assert_eq!(stages[1], [system_b]);
+		assert_eq!(stages[2], [system_c]);
+	}
+
+	#[test]
+	fn test_stages_cache_invalidation() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+
+		let system_a = world.register_system(|| {});
+		let system_b = world.register_system(|| {});
+		tracker.register_provider(
+			system_a,
+			ProviderInfo::new(vec![], vec![TestSetupKey::A], Cow::Borrowed("provider_a")),
+			&mut world,
+		);
+		assert_eq!(tracker.stages(), [vec![system_a]]);
+
+		tracker.register_provider(
+			system_b,
+			ProviderInfo::new(
+				vec![TestSetupKey::A],
+				vec![TestSetupKey::B],
+				Cow::Borrowed("provider_b"),
+			),
+			&mut world,
+		);
+		assert_eq!(tracker.stages(), [vec![system_a], vec![system_b]]);
 
-		// Should have 3 stages: [A, D], [B], [C]
-		assert_eq!(stages.len(), 3);
-		assert_eq!(stages[0].len(), 2); // A and D can run in parallel
-		assert_eq!(stages[1].len(), 1); // B depends on A
-		assert_eq!(stages[2].len(), 1); // C depends on B
+		tracker.remove_key(&TestSetupKey::A, &mut world);
+		assert_eq!(tracker.stages(), [vec![system_a, system_b]]);
 
-		// Check that A and D are in the first stage
-		assert!(stages[0].contains(&system_a));
-		assert!(stages[0].contains(&system_d));
-		assert!(stages[1].contains(&system_b));
-		assert!(stages[2].contains(&system_c));
+		tracker.remove_provider(system_a, &mut world);
+		assert_eq!(tracker.stages(), [vec![system_b]]);
 	}
 
 	#[test]
@@ -574,9 +2377,10 @@ mod tests {
 
 		// Add an entry that's never provided
 		world.resource_scope::<SetupTracker<TestSetupKey>, _>(|world, mut tracker| {
-			tracker
-				.entries
-				.insert(TestSetupKey::A, world.register_system(|| Progress::DONE));
+			tracker.entries.insert(
+				TestSetupKey::A,
+				world.register_system(|| Progress::DONE).into(),
+			);
 
 			// Add a provider that requires A but doesn't provide it
 			let provider = ProviderInfo::new(
@@ -584,17 +2388,19 @@ mod tests {
 				vec![TestSetupKey::B],
 				Cow::Borrowed("provider"),
 			);
-			tracker
-				.providers
-				.insert(world.register_system(|| {}), provider);
+			tracker.register_provider(world.register_system(|| {}), provider, world);
 		});
 
 		let result = SetupTracker::<TestSetupKey>::validate(&mut world);
 		assert!(result.is_err());
 
 		let error = result.unwrap_err();
-		assert!(error.unprovided.contains(&TestSetupKey::A));
-		assert!(!error.unprovided.contains(&TestSetupKey::B));
+		assert_eq!(error.errors.len(), 1);
+		let SetupGraphError::UnprovidedKey { key, required_by } = &error.errors[0] else {
+			panic!("expected unprovided key error, got {:?}", error.errors[0]);
+		};
+		assert_eq!(*key, TestSetupKey::A);
+		assert_eq!(required_by, &["provider"]);
 	}
 
 	#[test]
@@ -604,9 +2410,10 @@ mod tests {
 		world.insert_resource(SetupTracker::<TestSetupKey>::new(system_id));
 
 		world.resource_scope::<SetupTracker<TestSetupKey>, _>(|world, mut tracker| {
-			tracker
-				.entries
-				.insert(TestSetupKey::A, world.register_system(|| Progress::DONE));
+			tracker.entries.insert(
+				TestSetupKey::A,
+				world.register_system(|| Progress::DONE).into(),
+			);
 
 			// Add two providers for the same key
 			let provider1 =
@@ -626,8 +2433,15 @@ mod tests {
 		assert!(result.is_err());
 
 		let error = result.unwrap_err();
-		assert!(error.duplicate_providers.contains_key(&TestSetupKey::A));
-		assert_eq!(error.duplicate_providers[&TestSetupKey::A].len(), 2);
+		assert_eq!(error.errors.len(), 1);
+		let SetupGraphError::DuplicateProviders { key, providers } = &error.errors[0] else {
+			panic!(
+				"expected duplicate providers error, got {:?}",
+				error.errors[0]
+			);
+		};
+		assert_eq!(*key, TestSetupKey::A);
+		assert_eq!(providers, &["provider1", "provider2"]);
 	}
 
 	#[test]
@@ -649,8 +2463,8 @@ mod tests {
 			Cow::Borrowed("provider_b"),
 		);
 
-		tracker.providers.insert(system_a, provider_a);
-		tracker.providers.insert(system_b, provider_b);
+		tracker.register_provider(system_a, provider_a, &mut world);
+		tracker.register_provider(system_b, provider_b, &mut world);
 
 		// Test providers_of
 		let providers_of_a: Vec<_> = tracker.providers_of(&TestSetupKey::A).collect();
@@ -664,4 +2478,681 @@ mod tests {
 		assert_eq!(dependants_of_a[0].0, system_b);
 		assert_eq!(dependants_of_a[0].1, 0); // First (and only) requirement
 	}
+
+	#[test]
+	fn test_critical_path() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+
+		// A -> B -> C, with D depending only on A
+		let providers = [
+			(vec![], vec![TestSetupKey::A]),
+			(vec![TestSetupKey::A], vec![TestSetupKey::B]),
+			(vec![TestSetupKey::B], vec![TestSetupKey::C]),
+			(vec![TestSetupKey::A], vec![TestSetupKey::D]),
+		];
+		let mut systems = Vec::new();
+		for (requires, provides) in providers {
+			let system = world.register_system(|| {});
+			tracker.register_provider(
+				system,
+				ProviderInfo::new(requires, provides, Cow::Borrowed("provider")),
+				&mut world,
+			);
+			systems.push(system);
+		}
+
+		assert_eq!(
+			tracker.critical_path(PathWeight::Estimated),
+			[systems[0], systems[1], systems[2]]
+		);
+
+		// Nothing has run, so every chain weighs nothing and the first provider is enough
+		assert_eq!(tracker.critical_path(PathWeight::Measured), [systems[0]]);
+	}
+
+	#[test]
+	fn test_remove_provider_prunes_unused_keys() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+
+		let system_a = world.register_system(|| {});
+		let system_b = world.register_system(|| {});
+
+		tracker.register_provider(
+			system_a,
+			ProviderInfo::new(vec![], vec![TestSetupKey::A], Cow::Borrowed("provider_a")),
+			&mut world,
+		);
+		tracker.register_provider(
+			system_b,
+			ProviderInfo::new(
+				vec![TestSetupKey::A],
+				vec![TestSetupKey::B],
+				Cow::Borrowed("provider_b"),
+			),
+			&mut world,
+		);
+		let ProgressChecker::Unique(checker_b) = tracker.entries[&TestSetupKey::B] else {
+			unreachable!()
+		};
+
+		let removed = tracker.remove_provider(system_b, &mut world).unwrap();
+		assert_eq!(removed.name(), "provider_b");
+		assert!(!tracker.providers.contains_key(&system_b));
+
+		// B is no longer referenced, but A is still provided by provider_a
+		assert!(!tracker.entries.contains_key(&TestSetupKey::B));
+		assert!(tracker.entries.contains_key(&TestSetupKey::A));
+
+		// Both the provider system and the pruned key's checker are unregistered
+		assert!(world.run_system(system_b).is_err());
+		assert!(world.run_system(checker_b).is_err());
+
+		assert!(tracker.remove_provider(system_b, &mut world).is_none());
+	}
+
+	#[test]
+	fn test_intrinsic_requirements() {
+		#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+		enum Key {
+			LoadAssets,
+			BuildScene,
+		}
+
+		impl SetupKey for Key {
+			fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+				world.register_system(|| Progress::ZERO)
+			}
+
+			fn requires(&self) -> Vec<Self> {
+				match self {
+					Key::LoadAssets => vec![],
+					Key::BuildScene => vec![Key::LoadAssets],
+				}
+			}
+		}
+
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<Key>::new(world.register_system(|| {}));
+		let build = world.register_system(|| {});
+		tracker.register_provider(
+			build,
+			ProviderInfo::new(vec![], vec![Key::BuildScene], Cow::Borrowed("build")),
+			&mut world,
+		);
+		assert_eq!(tracker.providers()[&build].requires(), [Key::LoadAssets]);
+		assert_eq!(tracker.validation_errors().len(), 1);
+
+		let load = world.register_system(|| {});
+		tracker.register_provider(
+			load,
+			ProviderInfo::new(vec![], vec![Key::LoadAssets], Cow::Borrowed("load")),
+			&mut world,
+		);
+		assert!(tracker.validation_errors().is_empty());
+		assert_eq!(tracker.stages(), [vec![load], vec![build]]);
+
+		// Intrinsic requirements can't be edited away
+		tracker.set_requires(build, vec![], &mut world);
+		assert_eq!(tracker.providers()[&build].requires(), [Key::LoadAssets]);
+	}
+
+	#[test]
+	fn test_define_alias() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		for key in [TestSetupKey::A, TestSetupKey::B] {
+			tracker.register_provider(
+				world.register_system(|| {}),
+				ProviderInfo::new(vec![], vec![key], Cow::Borrowed("provider")),
+				&mut world,
+			);
+		}
+		let alias = tracker.define_alias(
+			TestSetupKey::C,
+			[TestSetupKey::A, TestSetupKey::B],
+			&mut world,
+		);
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(
+				vec![TestSetupKey::C],
+				vec![TestSetupKey::D],
+				Cow::Borrowed("provider_d"),
+			),
+			&mut world,
+		);
+		assert!(tracker.validation_errors().is_empty());
+		assert_eq!(tracker.stages().len(), 3);
+		assert_eq!(tracker.stages()[1], [alias]);
+		assert_eq!(tracker.providers()[&alias].name(), "alias C");
+		assert!(matches!(
+			tracker.entries()[&TestSetupKey::C],
+			ProgressChecker::Reported
+		));
+		assert_eq!(tracker.key_weight(&TestSetupKey::C), 0.0);
+
+		world.insert_resource(tracker);
+		for _ in 0..3 {
+			crate::advance_setup::<TestSetupKey>(&mut world);
+		}
+		let tracker = world.resource::<SetupTracker<TestSetupKey>>();
+		assert_eq!(
+			tracker.last_key_progress(&TestSetupKey::C),
+			Some(Progress::DONE)
+		);
+		assert_eq!(tracker.last_progress(), Progress::DONE);
+	}
+
+	#[test]
+	fn test_set_requires() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+
+		let system_a = world.register_system(|| {});
+		let system_b = world.register_system(|| {});
+		let system_c = world.register_system(|| {});
+		tracker.register_provider(
+			system_a,
+			ProviderInfo::new(vec![], vec![TestSetupKey::A], Cow::Borrowed("provider_a")),
+			&mut world,
+		);
+		tracker.register_provider(
+			system_b,
+			ProviderInfo::new(vec![], vec![TestSetupKey::B], Cow::Borrowed("provider_b")),
+			&mut world,
+		);
+		tracker.register_provider(
+			system_c,
+			ProviderInfo::new(
+				vec![TestSetupKey::D],
+				vec![TestSetupKey::C],
+				Cow::Borrowed("provider_c"),
+			),
+			&mut world,
+		);
+		assert_eq!(tracker.validation_errors().len(), 1);
+		assert_eq!(tracker.stages().len(), 1);
+
+		assert!(tracker.set_requires(system_c, vec![TestSetupKey::A, TestSetupKey::B], &mut world));
+		assert_eq!(
+			tracker.providers[&system_c].requires(),
+			[TestSetupKey::A, TestSetupKey::B]
+		);
+		assert!(tracker.validation_errors().is_empty());
+		assert_eq!(tracker.stages(), [vec![system_a, system_b], vec![system_c]]);
+		assert_eq!(
+			tracker.dependants_of(&TestSetupKey::B).collect::<Vec<_>>(),
+			[(system_c, 1)]
+		);
+		// Nothing uses D anymore
+		assert!(!tracker.entries.contains_key(&TestSetupKey::D));
+
+		assert!(!tracker.set_requires(world.register_system(|| {}), vec![], &mut world));
+	}
+
+	#[test]
+	fn test_set_provides() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+
+		let system_a = world.register_system(|| {});
+		let system_b = world.register_system(|| {});
+		tracker.register_provider(
+			system_a,
+			ProviderInfo {
+				weight: Some(2.0),
+				..ProviderInfo::new(
+					vec![],
+					vec![TestSetupKey::A, TestSetupKey::D],
+					Cow::Borrowed("provider_a"),
+				)
+			},
+			&mut world,
+		);
+		tracker.register_provider(
+			system_b,
+			ProviderInfo::new(
+				vec![TestSetupKey::B],
+				vec![TestSetupKey::C],
+				Cow::Borrowed("provider_b"),
+			),
+			&mut world,
+		);
+		assert_eq!(tracker.validation_errors().len(), 1);
+
+		assert!(tracker.set_provides(system_a, vec![TestSetupKey::A, TestSetupKey::B], &mut world));
+		assert_eq!(
+			tracker.providers[&system_a].provides(),
+			[TestSetupKey::A, TestSetupKey::B]
+		);
+		assert!(tracker.validation_errors().is_empty());
+		assert_eq!(tracker.stages(), [vec![system_a], vec![system_b]]);
+		assert_eq!(
+			tracker.providers_of(&TestSetupKey::B).collect::<Vec<_>>(),
+			[(system_a, 1)]
+		);
+		assert_eq!(tracker.key_weight(&TestSetupKey::B), 2.0);
+		// Nothing uses D anymore
+		assert!(!tracker.entries.contains_key(&TestSetupKey::D));
+
+		assert!(!tracker.set_provides(world.register_system(|| {}), vec![], &mut world));
+	}
+
+	#[test]
+	fn test_remove_key() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+
+		let system_a = world.register_system(|| {});
+		let system_b = world.register_system(|| {});
+
+		tracker.register_provider(
+			system_a,
+			ProviderInfo::new(vec![], vec![TestSetupKey::A], Cow::Borrowed("provider_a")),
+			&mut world,
+		);
+		tracker.register_provider(
+			system_b,
+			ProviderInfo::new(
+				vec![TestSetupKey::A],
+				vec![TestSetupKey::B],
+				Cow::Borrowed("provider_b"),
+			),
+			&mut world,
+		);
+		let ProgressChecker::Unique(checker_a) = tracker.entries[&TestSetupKey::A] else {
+			unreachable!()
+		};
+
+		assert!(tracker.remove_key(&TestSetupKey::A, &mut world));
+		assert!(!tracker.entries.contains_key(&TestSetupKey::A));
+		assert!(world.run_system(checker_a).is_err());
+
+		// Providers are kept, but no longer reference the removed key
+		assert!(tracker.providers[&system_a].provides().is_empty());
+		assert!(tracker.providers[&system_b].requires().is_empty());
+		assert_eq!(tracker.dependants_of(&TestSetupKey::A).count(), 0);
+
+		assert!(!tracker.remove_key(&TestSetupKey::A, &mut world));
+	}
+
+	#[test]
+	fn test_validation_graph_too_large() {
+		let mut world = World::new();
+		let system_id = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<TestSetupKey>::new(system_id));
+
+		world.resource_scope::<SetupTracker<TestSetupKey>, _>(|world, mut tracker| {
+			tracker.set_limits(GraphLimits {
+				max_keys: 2,
+				max_edges: 100,
+			});
+			let provider = ProviderInfo::new(
+				vec![TestSetupKey::A, TestSetupKey::B],
+				vec![TestSetupKey::C],
+				Cow::Borrowed("provider"),
+			);
+			tracker.register_provider(world.register_system(|| {}), provider, world);
+		});
+
+		let error = SetupTracker::<TestSetupKey>::validate(&mut world).unwrap_err();
+		// The rest of validation is skipped
+		assert_eq!(error.errors.len(), 1);
+		let SetupGraphError::TooLarge(too_large) = &error.errors[0] else {
+			panic!("expected too large error, got {:?}", error.errors[0]);
+		};
+		assert_eq!(too_large.keys, 3);
+		assert_eq!(too_large.edges, 3);
+	}
+
+	#[test]
+	fn test_lint_unused_provisions() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(
+				vec![],
+				vec![TestSetupKey::A, TestSetupKey::B],
+				Cow::Borrowed("provider_a"),
+			),
+			&mut world,
+		);
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(
+				vec![TestSetupKey::A],
+				vec![TestSetupKey::C],
+				Cow::Borrowed("provider_c"),
+			),
+			&mut world,
+		);
+
+		// Nothing is reported until terminal keys are declared
+		assert!(tracker.lint().is_empty());
+
+		tracker.mark_terminal([TestSetupKey::C]);
+		let warnings = tracker.lint();
+		assert_eq!(warnings.len(), 1);
+		let SetupGraphWarning::UnusedProvision { key, provided_by } = &warnings[0] else {
+			panic!("expected unused provision warning, got {:?}", warnings[0]);
+		};
+		assert_eq!(*key, TestSetupKey::B);
+		assert_eq!(provided_by, &["provider_a"]);
+	}
+
+	#[test]
+	fn test_lint_self_dependency() {
+		let mut world = World::new();
+		let system_id = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<TestSetupKey>::new(system_id));
+
+		world.resource_scope::<SetupTracker<TestSetupKey>, _>(|world, mut tracker| {
+			tracker.register_provider(
+				world.register_system(|| {}),
+				ProviderInfo::new(
+					vec![TestSetupKey::A],
+					vec![TestSetupKey::A, TestSetupKey::B],
+					Cow::Borrowed("provider"),
+				),
+				world,
+			);
+
+			let warnings = tracker.lint();
+			assert_eq!(warnings.len(), 1);
+			let SetupGraphWarning::SelfDependency { provider, keys } = &warnings[0] else {
+				panic!("expected self-dependency warning, got {:?}", warnings[0]);
+			};
+			assert_eq!(provider, "provider");
+			assert_eq!(keys, &[TestSetupKey::A]);
+		});
+
+		// The self-dependency is not a cycle, but A is effectively never provided
+		let error = SetupTracker::<TestSetupKey>::validate(&mut world).unwrap_err();
+		assert_eq!(error.errors.len(), 1);
+		assert!(matches!(
+			error.errors[0],
+			SetupGraphError::UnprovidedKey {
+				key: TestSetupKey::A,
+				..
+			}
+		));
+	}
+
+	#[test]
+	fn test_validation_provides_any() {
+		use crate::{IntoDependencyProvider, RegisterProvider};
+
+		let mut world = World::new();
+		let system_id = world.register_system(|| {});
+		world.insert_resource(SetupTracker::<TestSetupKey>::new(system_id));
+
+		// Two alternative providers for the same key are allowed
+		world
+			.register_provider((|| {}).provides_any([TestSetupKey::A]))
+			.register_provider((|| {}).provides_any([TestSetupKey::A]));
+		assert!(SetupTracker::<TestSetupKey>::validate(&mut world).is_ok());
+
+		// But not if another provider claims the key exclusively
+		world.register_provider((|| {}).provides([TestSetupKey::A]));
+		let error = SetupTracker::<TestSetupKey>::validate(&mut world).unwrap_err();
+		assert_eq!(error.errors.len(), 1);
+		let SetupGraphError::DuplicateProviders { key, providers } = &error.errors[0] else {
+			panic!(
+				"expected duplicate providers error, got {:?}",
+				error.errors[0]
+			);
+		};
+		assert_eq!(*key, TestSetupKey::A);
+		assert_eq!(providers.len(), 3);
+	}
+
+	#[test]
+	fn test_adjacency_index_after_removal() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+
+		let system_a = world.register_system(|| {});
+		let system_c = world.register_system(|| {});
+		tracker.register_provider(
+			system_a,
+			ProviderInfo::new(
+				vec![],
+				vec![TestSetupKey::A, TestSetupKey::B],
+				Cow::Borrowed("provider_a"),
+			),
+			&mut world,
+		);
+		tracker.register_provider(
+			system_c,
+			ProviderInfo::new(
+				vec![TestSetupKey::A, TestSetupKey::B],
+				vec![TestSetupKey::C],
+				Cow::Borrowed("provider_c"),
+			),
+			&mut world,
+		);
+		assert_eq!(
+			tracker.dependants_of(&TestSetupKey::B).collect::<Vec<_>>(),
+			[(system_c, 1)]
+		);
+
+		// Pin indices shift after removing a key
+		tracker.remove_key(&TestSetupKey::A, &mut world);
+		assert_eq!(
+			tracker.dependants_of(&TestSetupKey::B).collect::<Vec<_>>(),
+			[(system_c, 0)]
+		);
+		assert_eq!(
+			tracker.providers_of(&TestSetupKey::B).collect::<Vec<_>>(),
+			[(system_a, 0)]
+		);
+
+		tracker.remove_provider(system_c, &mut world);
+		assert_eq!(tracker.dependants_of(&TestSetupKey::B).count(), 0);
+	}
+
+	#[test]
+	fn test_stage_of_key_and_progress_by_stage() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(
+				vec![],
+				vec![TestSetupKey::A, TestSetupKey::B],
+				Cow::Borrowed("provider_a"),
+			),
+			&mut world,
+		);
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(
+				vec![TestSetupKey::A],
+				vec![TestSetupKey::C],
+				Cow::Borrowed("provider_c"),
+			),
+			&mut world,
+		);
+		tracker.register_provider(
+			world.register_system(|| {}),
+			ProviderInfo::new(vec![TestSetupKey::D], vec![], Cow::Borrowed("consumer")),
+			&mut world,
+		);
+		tracker.entries.insert(
+			TestSetupKey::A,
+			world.register_system(|| Progress::DONE).into(),
+		);
+		tracker.entries.insert(
+			TestSetupKey::B,
+			world.register_system(|| Progress::ZERO).into(),
+		);
+		tracker.entries.insert(
+			TestSetupKey::C,
+			world.register_system(|| Progress::new(0.4)).into(),
+		);
+
+		assert_eq!(tracker.stage_of_key(&TestSetupKey::A), Some(0));
+		assert_eq!(tracker.stage_of_key(&TestSetupKey::B), Some(0));
+		assert_eq!(tracker.stage_of_key(&TestSetupKey::C), Some(1));
+		// D is never provided
+		assert_eq!(tracker.stage_of_key(&TestSetupKey::D), None);
+
+		let progress = tracker.progress_by_stage(&mut world);
+		assert_eq!(progress, [Progress::new(0.5), Progress::new(0.4)]);
+
+		// Invalidated keys count as having no progress, like in `progress`
+		tracker.invalidate(&TestSetupKey::A);
+		let progress = tracker.progress_by_stage(&mut world);
+		assert_eq!(progress, [Progress::ZERO, Progress::new(0.4)]);
+	}
+
+	#[test]
+	fn test_keyed_progress_checker() {
+		#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+		struct DataKey(u32);
+
+		#[derive(Resource, Default)]
+		struct Loaded(HashSet<u32>);
+
+		impl SetupKey for DataKey {
+			fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+				let key = self.0;
+				world.register_system(move |loaded: Res<Loaded>| loaded.0.contains(&key).into())
+			}
+
+			fn progress_checker(&self, world: &mut World) -> ProgressChecker<Self> {
+				ProgressChecker::Keyed(world.register_system_cached(
+					|In(key): In<DataKey>, loaded: Res<Loaded>| loaded.0.contains(&key.0).into(),
+				))
+			}
+		}
+
+		let mut world = World::new();
+		world.init_resource::<Loaded>();
+		let mut tracker = SetupTracker::<DataKey>::new(world.register_system(|| {}));
+		let system = world.register_system(|| {});
+		tracker.register_provider(
+			system,
+			ProviderInfo::new(vec![], (0..100).map(DataKey).collect(), "load".into()),
+			&mut world,
+		);
+
+		// Every key shares one checker
+		let checkers = tracker
+			.entries()
+			.values()
+			.map(|checker| match checker {
+				ProgressChecker::Keyed(id) => *id,
+				_ => unreachable!(),
+			})
+			.collect::<HashSet<_>>();
+		assert_eq!(checkers.len(), 1);
+
+		world.resource_mut::<Loaded>().0.extend(0..50);
+		assert_eq!(tracker.progress(&mut world), Progress::new(0.5));
+
+		// Shared checkers stay registered when keys are removed
+		tracker.remove_key(&DataKey(0), &mut world);
+		let checker = *checkers.iter().next().unwrap();
+		assert!(world.run_system_with(checker, DataKey(1)).is_ok());
+	}
+
+	#[test]
+	fn test_progress_history() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		let start = Instant::now();
+		tracker.sample_progress(start, Progress::ZERO);
+		// Too soon after the last sample
+		tracker.sample_progress(start + Duration::from_millis(50), Progress::new(0.1));
+		for i in 1..=PROGRESS_HISTORY_LEN {
+			let progress = Progress::new(i as f32 / PROGRESS_HISTORY_LEN as f32);
+			tracker.sample_progress(start + PROGRESS_SAMPLE_INTERVAL * i as u32, progress);
+		}
+
+		let history = tracker.progress_history().collect::<Vec<_>>();
+		assert_eq!(history.len(), PROGRESS_HISTORY_LEN);
+		assert_eq!(
+			history[0],
+			(start + PROGRESS_SAMPLE_INTERVAL, Progress::new(1.0 / 300.0))
+		);
+		assert_eq!(history.last().unwrap().1, Progress::DONE);
+	}
+
+	#[test]
+	fn test_registered_systems() {
+		#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+		enum DynamicKey {
+			Unique(u32),
+			Cached(u32),
+		}
+
+		impl SetupKey for DynamicKey {
+			fn register_progress_checker(&self, world: &mut World) -> SystemId<(), Progress> {
+				world.register_system(|| Progress::ZERO)
+			}
+
+			fn progress_checker(&self, world: &mut World) -> ProgressChecker<Self> {
+				match self {
+					DynamicKey::Unique(_) => {
+						ProgressChecker::Unique(world.register_system(|| Progress::ZERO))
+					}
+					DynamicKey::Cached(_) => ProgressChecker::cached(world, || Progress::DONE),
+				}
+			}
+		}
+
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<DynamicKey>::new(world.register_system(|| {}));
+		assert_eq!(tracker.registered_systems(), 1);
+
+		let system = world.register_system(|| {});
+		let keys = (0..10).flat_map(|i| [DynamicKey::Unique(i), DynamicKey::Cached(i)]);
+		tracker.register_provider(
+			system,
+			ProviderInfo::new(vec![], keys.collect(), "load".into()),
+			&mut world,
+		);
+		// on_finished, the provider, and one checker per unique key
+		assert_eq!(tracker.registered_systems(), 12);
+		assert_eq!(tracker.progress(&mut world), Progress::new(0.5));
+
+		for i in 0..10 {
+			tracker.remove_key(&DynamicKey::Unique(i), &mut world);
+			tracker.remove_key(&DynamicKey::Cached(i), &mut world);
+		}
+		assert_eq!(tracker.registered_systems(), 2);
+	}
+
+	#[test]
+	fn test_key_labeler() {
+		let mut world = World::new();
+		let mut tracker = SetupTracker::<TestSetupKey>::new(world.register_system(|| {}));
+		assert_eq!(tracker.key_label(&TestSetupKey::A), "A");
+
+		let locale = std::sync::Arc::new(std::sync::RwLock::new("en"));
+		let current = locale.clone();
+		tracker.set_key_labeler(move |key| match (key, *current.read().unwrap()) {
+			(TestSetupKey::A, "fr") => Some("Chargement…".into()),
+			(TestSetupKey::A, _) => Some("Loading…".into()),
+			_ => None,
+		});
+		assert_eq!(tracker.key_label(&TestSetupKey::A), "Loading…");
+		*locale.write().unwrap() = "fr";
+		assert_eq!(tracker.key_label(&TestSetupKey::A), "Chargement…");
+		assert_eq!(tracker.key_label(&TestSetupKey::B), "B");
+
+		// Span names fall back to the key's index, since they can't rely on `Debug`
+		tracker.entries.insert(
+			TestSetupKey::B,
+			world.register_system(|| Progress::DONE).into(),
+		);
+		assert_eq!(tracker.key_span_name(&TestSetupKey::A), "Chargement…");
+		assert_eq!(tracker.key_span_name(&TestSetupKey::B), "TestSetupKey[0]");
+	}
 }
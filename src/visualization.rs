@@ -6,9 +6,19 @@
 //! # Features
 //!
 //! - Interactive node-based graph visualization
-//! - Color-coded pins showing different setup keys
+//! - Pins colored by each key's current progress, and per-node progress bars
 //! - Real-time updates as setup progresses
-//! - Automatic layout based on dependency stages
+//! - Providers and keys involved in validation errors are highlighted in red
+//! - Searching by provider or key name, hiding finished providers, and collapsing stages
+//! - Optional highlighting of the [critical path](SetupTracker::critical_path)
+//! - Clicking a node shows its requirements, provisions, and run statistics in a side panel
+//! - Automatic layout based on dependency stages, with moved nodes remembered in a
+//!   [`SetupGraphLayout`]
+//! - A per-key progress panel for loading overlays (see [`draw_setup_progress_panel`])
+//! - A sparkline of recent overall progress, to spot stalls and regressions (see
+//!   [`draw_progress_sparkline`])
+//! - A timeline view plotting when each provider actually ran, grouped by stage (see
+//!   [`draw_setup_timeline`])
 //!
 //! # Usage
 //!
@@ -86,19 +96,34 @@
 //!     }
 //! }
 //! ```
+//!
+//! ### Option 3: One Window for Every Key Type
+//!
+//! Add [`SetupGraphTabsPlugin`] and run [`toggle_setup_graph_tabs`] to show every tracker with a
+//! [`SetupGraphVisualizationPlugin`] in a single window, with one tab per key type.
 
-use crate::{SetupKey, SetupTracker};
+use crate::{
+	MeasuredSpan, PathWeight, Progress, ProviderRunStats, SetupGraphError, SetupKey, SetupKeyState,
+	SetupTracker,
+};
 use bevy_app::{App, Plugin, PreUpdate};
 use bevy_ecs::prelude::*;
-use bevy_egui::{EguiContexts, EguiPrimaryContextPass};
-use bevy_platform::collections::HashMap;
+use bevy_egui::{EguiContext, EguiContexts, EguiPrimaryContextPass, PrimaryEguiContext};
+use bevy_platform::collections::{HashMap, HashSet};
+use std::any::TypeId;
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::ops::Deref;
 
-use bevy_egui::egui::{Color32, Ui};
+use bevy_egui::egui::{
+	Align2, Color32, FontId, Frame, Grid, Pos2, ProgressBar, Rect, ScrollArea, Sense, Shape,
+	Stroke, Ui, pos2, vec2,
+};
 use bevy_log::{error, info, trace};
-use egui_snarl::ui::{NodeLayout, PinInfo, SnarlPin, SnarlStyle, SnarlViewer, WireStyle};
+use egui_snarl::ui::{
+	NodeLayout, PinInfo, SnarlPin, SnarlStyle, SnarlViewer, SnarlWidget, WireStyle,
+};
 use egui_snarl::{InPin, InPinId, NodeId, OutPin, OutPinId, Snarl};
 
 /// Plugin that adds graph visualization capabilities for setup dependencies.
@@ -128,22 +153,50 @@ impl<K: SetupKey> Default for SetupGraphVisualizationPlugin<K> {
 
 impl<K: SetupKey + Debug + Send + Sync + 'static> Plugin for SetupGraphVisualizationPlugin<K> {
 	fn build(&self, app: &mut App) {
+		app.world_mut()
+			.get_resource_or_init::<SetupGraphTabs>()
+			.register::<K>();
+		app.init_resource::<SetupGraphLayout<K>>()
+			.add_systems(
+				PreUpdate,
+				(apply_graph_edits::<K>, sync_snarl::<K>)
+					.chain()
+					.run_if(resource_exists::<SetupGraphVisState<K>>),
+			)
+			.add_systems(EguiPrimaryContextPass, draw_setup_graph_window::<K>);
+		#[cfg(feature = "debug")]
 		app.add_systems(
-			PreUpdate,
-			sync_snarl::<K>.run_if(resource_exists::<SetupGraphVisState<K>>),
-		)
-		.add_systems(EguiPrimaryContextPass, draw_setup_graph_window::<K>);
+			EguiPrimaryContextPass,
+			draw_setup_stepper_window::<K>.run_if(resource_exists::<crate::SetupStepper<K>>),
+		);
 	}
 }
 
 /// Wrapper around SetupTracker that implements SnarlViewer for graph visualization.
-pub struct SetupGraphViewer<'a, K: SetupKey>(&'a SetupTracker<K>);
+pub struct SetupGraphViewer<'a, K: SetupKey> {
+	tracker: &'a SetupTracker<K>,
+	errors: &'a GraphErrors<K>,
+	critical_path: &'a [bevy_ecs::system::SystemId],
+	matches: &'a HashSet<bevy_ecs::system::SystemId>,
+	/// Where to queue changes to providers, or `None` if editing is disabled.
+	edits: Option<&'a mut Vec<GraphEdit<K>>>,
+}
+
+/// A change to a provider made in [edit mode](SetupGraphVisState::edit_mode), waiting for
+/// [`apply_graph_edits`].
+#[derive(Debug)]
+enum GraphEdit<K> {
+	/// The new keys the provider requires.
+	Requires(bevy_ecs::system::SystemId, Vec<K>),
+	/// The new keys the provider provides.
+	Provides(bevy_ecs::system::SystemId, Vec<K>),
+}
 
 impl<'a, K: SetupKey> Deref for SetupGraphViewer<'a, K> {
 	type Target = SetupTracker<K>;
 
 	fn deref(&self) -> &Self::Target {
-		self.0
+		self.tracker
 	}
 }
 
@@ -170,6 +223,68 @@ impl<'a, K: SetupKey> SetupGraphViewer<'a, K> {
 
 		Some(COLORS[i % COLORS.len()])
 	}
+
+	/// Queues an edit of the keys `system` requires, applied to the tracker by
+	/// [`apply_graph_edits`]. Earlier edits from the same frame are taken into account.
+	fn edit_requires(
+		&mut self,
+		system: bevy_ecs::system::SystemId,
+		edit: impl FnOnce(&mut Vec<K>),
+	) {
+		let Some(edits) = &mut self.edits else {
+			return;
+		};
+		let mut requires = edits
+			.iter()
+			.rev()
+			.find_map(|edit| match edit {
+				GraphEdit::Requires(id, requires) if *id == system => Some(requires.clone()),
+				_ => None,
+			})
+			.unwrap_or_else(|| self.tracker.providers()[&system].requires().to_vec());
+		edit(&mut requires);
+		edits.push(GraphEdit::Requires(system, requires));
+	}
+
+	/// Queues an edit of the keys `system` provides, like [`edit_requires`](Self::edit_requires).
+	fn edit_provides(
+		&mut self,
+		system: bevy_ecs::system::SystemId,
+		edit: impl FnOnce(&mut Vec<K>),
+	) {
+		let Some(edits) = &mut self.edits else {
+			return;
+		};
+		let mut provides = edits
+			.iter()
+			.rev()
+			.find_map(|edit| match edit {
+				GraphEdit::Provides(id, provides) if *id == system => Some(provides.clone()),
+				_ => None,
+			})
+			.unwrap_or_else(|| self.tracker.providers()[&system].provides().to_vec());
+		edit(&mut provides);
+		edits.push(GraphEdit::Provides(system, provides));
+	}
+
+	/// Get a color for a setup key based on its current progress: gray while pending, yellow
+	/// once one of its providers has started, green when finished, and red if it failed or is
+	/// involved in a validation error.
+	pub fn key_status_color(&self, key: &K) -> Color32 {
+		let progress = self.last_key_progress(key).unwrap_or_default();
+		if self.is_failed(key) || !progress.is_finite() || self.errors.keys.contains(key) {
+			Color32::RED
+		} else if progress.finished() {
+			Color32::GREEN
+		} else if self
+			.providers_of(key)
+			.any(|(system, _)| self.has_started(system))
+		{
+			Color32::YELLOW
+		} else {
+			Color32::GRAY
+		}
+	}
 }
 
 /// Color palette for setup keys in the visualization.
@@ -192,12 +307,86 @@ impl<K: SetupKey + Debug> SnarlViewer<bevy_ecs::system::SystemId> for SetupGraph
 		self.providers()[node].name().to_owned()
 	}
 
+	fn node_frame(
+		&mut self,
+		default: Frame,
+		node: NodeId,
+		_inputs: &[InPin],
+		_outputs: &[OutPin],
+		snarl: &Snarl<bevy_ecs::system::SystemId>,
+	) -> Frame {
+		let system = &snarl[node];
+		if self.errors.providers.contains(system) {
+			default.stroke(Stroke::new(2.0, Color32::RED))
+		} else if self.critical_path.contains(system) {
+			default.stroke(Stroke::new(2.0, Color32::GOLD))
+		} else if self.matches.contains(system) {
+			default.stroke(Stroke::new(2.0, Color32::from_rgb(0, 255, 255)))
+		} else {
+			default
+		}
+	}
+
+	fn has_on_hover_popup(&mut self, node: &bevy_ecs::system::SystemId) -> bool {
+		let info = &self.providers()[node];
+		info.description().is_some() || !info.tags().is_empty()
+	}
+
+	fn show_on_hover_popup(
+		&mut self,
+		node: NodeId,
+		_inputs: &[InPin],
+		_outputs: &[OutPin],
+		ui: &mut Ui,
+		snarl: &mut Snarl<bevy_ecs::system::SystemId>,
+	) {
+		let info = &self.providers()[&snarl[node]];
+		if let Some(description) = info.description() {
+			ui.label(description);
+		}
+		if !info.tags().is_empty() {
+			ui.label(format!("Tags: {}", info.tags().join(", ")));
+		}
+	}
+
+	fn has_body(&mut self, node: &bevy_ecs::system::SystemId) -> bool {
+		!self.providers()[node].provides().is_empty()
+	}
+
+	fn show_body(
+		&mut self,
+		node: NodeId,
+		_inputs: &[InPin],
+		_outputs: &[OutPin],
+		ui: &mut Ui,
+		snarl: &mut Snarl<bevy_ecs::system::SystemId>,
+	) {
+		let provides = self.providers()[&snarl[node]].provides();
+		let progress = provides
+			.iter()
+			.map(|key| finite_or_zero(self.last_key_progress(key).unwrap_or_default()))
+			.sum::<f32>()
+			/ provides.len() as f32;
+		if Progress::new(progress).finished() {
+			ui.colored_label(Color32::GREEN, "✔");
+		} else {
+			ui.add(
+				ProgressBar::new(progress)
+					.desired_width(120.0)
+					.text(format!("{:.0}", Progress::new(progress))),
+			);
+		}
+	}
+
 	fn outputs(&mut self, node: &bevy_ecs::system::SystemId) -> usize {
-		self.providers()[node].provides().len()
+		// In edit mode, an extra pin to connect to the requirements of other providers to
+		// provide them too
+		self.providers()[node].provides().len() + self.edits.is_some() as usize
 	}
 
 	fn inputs(&mut self, node: &bevy_ecs::system::SystemId) -> usize {
-		self.providers()[node].requires().len()
+		// In edit mode, an extra pin to connect new requirements to
+		self.providers()[node].requires().len() + self.edits.is_some() as usize
 	}
 
 	fn show_input(
@@ -206,11 +395,18 @@ impl<K: SetupKey + Debug> SnarlViewer<bevy_ecs::system::SystemId> for SetupGraph
 		ui: &mut Ui,
 		snarl: &mut Snarl<bevy_ecs::system::SystemId>,
 	) -> impl SnarlPin + 'static {
-		let key = &self.providers()[&snarl[pin.id.node]].requires()[pin.id.input];
-		let fill = self.key_color(key);
-		ui.label(format!("{key:?}"));
+		let Some(key) = self.providers()[&snarl[pin.id.node]]
+			.requires()
+			.get(pin.id.input)
+		else {
+			ui.weak("+");
+			return PinInfo::default();
+		};
+		let fill = Some(self.key_status_color(key));
+		ui.label(self.key_label(key));
 		PinInfo {
 			fill,
+			wire_color: self.errors.keys.contains(key).then_some(Color32::RED),
 			..Default::default()
 		}
 	}
@@ -221,36 +417,326 @@ impl<K: SetupKey + Debug> SnarlViewer<bevy_ecs::system::SystemId> for SetupGraph
 		ui: &mut Ui,
 		snarl: &mut Snarl<bevy_ecs::system::SystemId>,
 	) -> impl SnarlPin + 'static {
-		let key = &self.providers()[&snarl[pin.id.node]].provides()[pin.id.output];
-		let fill = self.key_color(key);
-		ui.label(format!("{key:?}"));
+		let Some(key) = self.providers()[&snarl[pin.id.node]]
+			.provides()
+			.get(pin.id.output)
+		else {
+			ui.weak("+");
+			return PinInfo::default();
+		};
+		let fill = Some(self.key_status_color(key));
+		ui.label(self.key_label(key));
 		PinInfo {
 			fill,
+			wire_color: self.errors.keys.contains(key).then_some(Color32::RED),
 			..Default::default()
 		}
 	}
+
+	fn connect(
+		&mut self,
+		from: &OutPin,
+		to: &InPin,
+		snarl: &mut Snarl<bevy_ecs::system::SystemId>,
+	) {
+		if self.edits.is_none() {
+			snarl.connect(from.id, to.id);
+			return;
+		}
+		let Some(key) = self.providers()[&snarl[from.id.node]]
+			.provides()
+			.get(from.id.output)
+			.cloned()
+		else {
+			// From the extra output pin, so provide the key the input pin requires
+			let Some(key) = self.providers()[&snarl[to.id.node]]
+				.requires()
+				.get(to.id.input)
+				.cloned()
+			else {
+				return;
+			};
+			self.edit_provides(snarl[from.id.node], |provides| {
+				if !provides.contains(&key) {
+					provides.push(key);
+				}
+			});
+			return;
+		};
+		let input = to.id.input;
+		self.edit_requires(snarl[to.id.node], |requires| {
+			if requires.contains(&key) {
+				return;
+			}
+			match requires.get_mut(input) {
+				Some(requirement) => *requirement = key,
+				None => requires.push(key),
+			}
+		});
+	}
+
+	fn disconnect(
+		&mut self,
+		from: &OutPin,
+		to: &InPin,
+		snarl: &mut Snarl<bevy_ecs::system::SystemId>,
+	) {
+		if self.edits.is_none() {
+			snarl.disconnect(from.id, to.id);
+			return;
+		}
+		let Some(key) = self.providers()[&snarl[from.id.node]]
+			.provides()
+			.get(from.id.output)
+			.cloned()
+		else {
+			return;
+		};
+		self.edit_requires(snarl[to.id.node], |requires| {
+			requires.retain(|requirement| *requirement != key)
+		});
+	}
+
+	fn drop_inputs(&mut self, pin: &InPin, snarl: &mut Snarl<bevy_ecs::system::SystemId>) {
+		if self.edits.is_none() {
+			snarl.drop_inputs(pin.id);
+			return;
+		}
+		let Some(key) = self.providers()[&snarl[pin.id.node]]
+			.requires()
+			.get(pin.id.input)
+			.cloned()
+		else {
+			return;
+		};
+		self.edit_requires(snarl[pin.id.node], |requires| {
+			requires.retain(|requirement| *requirement != key)
+		});
+	}
+
+	fn drop_outputs(&mut self, pin: &OutPin, snarl: &mut Snarl<bevy_ecs::system::SystemId>) {
+		if self.edits.is_none() {
+			snarl.drop_outputs(pin.id);
+			return;
+		}
+		let Some(key) = self.providers()[&snarl[pin.id.node]]
+			.provides()
+			.get(pin.id.output)
+			.cloned()
+		else {
+			return;
+		};
+		if pin.remotes.is_empty() {
+			// Nothing requires it, so stop providing it
+			self.edit_provides(snarl[pin.id.node], |provides| {
+				provides.retain(|provided| *provided != key)
+			});
+			return;
+		}
+		for remote in &pin.remotes {
+			let key = key.clone();
+			self.edit_requires(snarl[remote.node], |requires| {
+				requires.retain(|requirement| *requirement != key)
+			});
+		}
+	}
 }
 
 /// Resource that holds the snarl graph state for visualization.
 #[derive(Resource, Debug)]
 pub struct SetupGraphVisState<K: SetupKey> {
 	snarl: Snarl<bevy_ecs::system::SystemId>,
-	_marker: PhantomData<K>,
+	errors: GraphErrors<K>,
+	/// Whether to highlight the [critical path](SetupTracker::critical_path).
+	pub show_critical_path: bool,
+	/// How to weigh providers when finding the critical path.
+	pub path_weight: PathWeight,
+	/// Text to search for in provider names and key labels. Matching nodes are highlighted.
+	pub search: String,
+	/// Whether to hide nodes that don't match [`search`](Self::search), if it isn't empty.
+	pub only_matches: bool,
+	/// Whether to hide providers whose keys are all finished.
+	pub hide_finished: bool,
+	/// Stages whose nodes are collapsed to just their headers.
+	pub collapsed_stages: HashSet<usize>,
+	/// Whether to show the node graph or the timeline.
+	pub view: GraphView,
+	/// Whether dragging and deleting wires in the graph changes which keys providers
+	/// [require](SetupTracker::set_requires) and [provide](SetupTracker::set_provides). Edits only
+	/// last until the app exits. Wires for [intrinsic requirements](crate::SetupKey::requires) come
+	/// back after being deleted.
+	///
+	/// Each node gets an extra `+` input and output pin. Connecting a key to the `+` input makes
+	/// the provider require it, and connecting the `+` output to a requirement of another provider
+	/// makes this provider provide that key too. Deleting the wires of an output pin that has none
+	/// makes the provider stop providing its key.
+	pub edit_mode: bool,
+	edits: Vec<GraphEdit<K>>,
+	matches: HashSet<bevy_ecs::system::SystemId>,
+	hidden: HashSet<bevy_ecs::system::SystemId>,
+	stages: HashMap<bevy_ecs::system::SystemId, usize>,
+	stage_count: usize,
 }
 
 impl<K: SetupKey> Default for SetupGraphVisState<K> {
 	fn default() -> Self {
 		Self {
 			snarl: Default::default(),
+			errors: Default::default(),
+			show_critical_path: false,
+			path_weight: Default::default(),
+			search: String::new(),
+			only_matches: false,
+			hide_finished: false,
+			collapsed_stages: Default::default(),
+			view: Default::default(),
+			edit_mode: false,
+			edits: Vec::new(),
+			matches: Default::default(),
+			hidden: Default::default(),
+			stages: Default::default(),
+			stage_count: 0,
+		}
+	}
+}
+
+/// Which view [`draw_setup_graph`] shows.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GraphView {
+	/// The dependency graph, with one node per provider.
+	#[default]
+	Graph,
+	/// When each provider ran. See [`draw_setup_timeline`].
+	Timeline,
+}
+
+/// The tracker's [validation errors](SetupTracker::validation_errors), along with the providers
+/// and keys involved in them, which are highlighted in the graph.
+#[derive(Debug)]
+struct GraphErrors<K: SetupKey> {
+	errors: Vec<SetupGraphError<K>>,
+	providers: HashSet<bevy_ecs::system::SystemId>,
+	keys: HashSet<K>,
+}
+
+impl<K: SetupKey> Default for GraphErrors<K> {
+	fn default() -> Self {
+		Self {
+			errors: Vec::new(),
+			providers: Default::default(),
+			keys: Default::default(),
+		}
+	}
+}
+
+impl<K: SetupKey> GraphErrors<K> {
+	fn new(tracker: &SetupTracker<K>) -> Self {
+		let mut this = Self {
+			errors: tracker.validation_errors(),
+			..Default::default()
+		};
+		for error in &this.errors {
+			match error {
+				SetupGraphError::UnprovidedKey { key, .. } => {
+					this.keys.insert(key.clone());
+					this.providers
+						.extend(tracker.dependants_of(key).map(|(system, _)| system));
+				}
+				SetupGraphError::DuplicateProviders { key, .. } => {
+					this.keys.insert(key.clone());
+					this.providers
+						.extend(tracker.providers_of(key).map(|(system, _)| system));
+				}
+				SetupGraphError::Cycle(cycle) => {
+					this.keys.extend(cycle.path.iter().cloned());
+					// Only the providers along the cycle, not every provider of its keys
+					for (i, key) in cycle.path.iter().enumerate() {
+						let next = &cycle.path[(i + 1) % cycle.path.len()];
+						this.providers.extend(
+							tracker
+								.providers_of(key)
+								.map(|(system, _)| system)
+								.filter(|system| {
+									tracker.providers()[system].requires().contains(next)
+								}),
+						);
+					}
+				}
+				SetupGraphError::TooLarge(_) => {}
+			}
+		}
+		this
+	}
+}
+
+/// Node positions in the setup graph visualization, keyed by provider name.
+///
+/// Unlike [`SetupGraphVisState`], this resource is kept when the window closes, so nodes the user
+/// moved stay where they were when it reopens. [`SetupGraphVisualizationPlugin`] adds it, and
+/// [`sync_snarl`] keeps it up to date. With the `ron` feature, it can also be saved to and loaded
+/// from a file to persist the layout between runs.
+///
+/// Providers with the same name share a position.
+#[derive(Resource, Debug)]
+#[cfg_attr(feature = "ron", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetupGraphLayout<K: SetupKey> {
+	positions: BTreeMap<String, (f32, f32)>,
+	#[cfg_attr(feature = "ron", serde(skip))]
+	_marker: PhantomData<K>,
+}
+
+impl<K: SetupKey> Default for SetupGraphLayout<K> {
+	fn default() -> Self {
+		Self {
+			positions: Default::default(),
 			_marker: PhantomData,
 		}
 	}
 }
 
+impl<K: SetupKey> SetupGraphLayout<K> {
+	/// Returns the saved position of the provider named `name`.
+	pub fn position(&self, name: &str) -> Option<Pos2> {
+		self.positions.get(name).map(|&(x, y)| Pos2::new(x, y))
+	}
+
+	/// Sets the position of the provider named `name`.
+	pub fn set_position(&mut self, name: impl Into<String>, pos: Pos2) {
+		self.positions.insert(name.into(), (pos.x, pos.y));
+	}
+
+	/// Forgets every saved position, so nodes are laid out by stage again.
+	pub fn clear(&mut self) {
+		self.positions.clear();
+	}
+}
+
+#[cfg(feature = "ron")]
+impl<K: SetupKey> SetupGraphLayout<K> {
+	/// Saves the layout to a RON file at `path`.
+	pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+		let ron =
+			ron::ser::to_string_pretty(self, Default::default()).map_err(std::io::Error::other)?;
+		std::fs::write(path, ron)
+	}
+
+	/// Loads a layout previously [saved](Self::save) to `path`.
+	pub fn load(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+		let ron = std::fs::read_to_string(path)?;
+		ron::from_str(&ron).map_err(std::io::Error::other)
+	}
+}
+
 /// System that synchronizes the snarl graph with the current setup tracker state.
-pub fn sync_snarl<K: SetupKey>(
+///
+/// New nodes are placed at their position in the [`SetupGraphLayout`] if it has one, or laid out
+/// by stage otherwise. The positions of all nodes are recorded back into the layout.
+///
+/// Also applies the search and filters in [`SetupGraphVisState`].
+pub fn sync_snarl<K: SetupKey + Debug>(
 	mut snarl: ResMut<SetupGraphVisState<K>>,
 	tracker: Res<SetupTracker<K>>,
+	mut layout: Option<ResMut<SetupGraphLayout<K>>>,
 ) {
 	let mut nodes = snarl
 		.snarl
@@ -258,15 +744,84 @@ pub fn sync_snarl<K: SetupKey>(
 		.map(|(id, node)| (id, node.value))
 		.collect::<HashMap<NodeId, bevy_ecs::system::SystemId>>();
 
-	if tracker.is_changed() || snarl.is_added() {
-		// Add nodes for each provider, arranged by stage
-		for (i, stage) in tracker.stages().into_iter().enumerate() {
+	// Filters can change without the tracker changing
+	let matches = search_matches(&tracker, &snarl.search);
+	let hidden = tracker
+		.providers()
+		.iter()
+		.filter(|(id, info)| {
+			let finished = !info.provides().is_empty()
+				&& info.provides().iter().all(|key| {
+					tracker
+						.last_key_progress(key)
+						.is_some_and(|progress| progress.finished())
+				});
+			(snarl.hide_finished && finished)
+				|| (snarl.only_matches && !snarl.search.is_empty() && !matches.contains(*id))
+		})
+		.map(|(id, _)| *id)
+		.collect::<HashSet<_>>();
+	snarl.matches = matches;
+	let filters_changed = hidden != snarl.hidden;
+	if filters_changed {
+		snarl.hidden = hidden;
+	}
+
+	if tracker.is_changed() || snarl.is_added() || filters_changed {
+		if tracker.check_limits().is_err() {
+			// Too large to draw, so don't waste time building nodes. `draw_setup_graph` shows a
+			// summary instead.
+			for (nid, _) in nodes {
+				snarl.snarl.remove_node(nid);
+			}
+			return;
+		}
+
+		snarl.errors = GraphErrors::new(&tracker);
+
+		// Remove nodes for providers that have been unregistered or hidden
+		nodes.retain(|nid, id| {
+			if tracker.providers().contains_key(id) && !snarl.hidden.contains(id) {
+				true
+			} else {
+				snarl.snarl.remove_node(*nid);
+				false
+			}
+		});
+
+		// Pin indices may have shifted if keys were removed, so rebuild all wires
+		let wires = snarl.snarl.wires().collect::<Vec<_>>();
+		for (out_pin, in_pin) in wires {
+			snarl.snarl.disconnect(out_pin, in_pin);
+		}
+
+		// Add nodes for each provider, arranged by stage. Providers that can never run, e.g.
+		// because of a cycle, go in a column after the last stage.
+		let mut stages = tracker.stages().to_vec();
+		let staged = stages.iter().flatten().copied().collect::<HashSet<_>>();
+		let unstaged = tracker
+			.providers()
+			.keys()
+			.filter(|id| !staged.contains(*id))
+			.copied()
+			.collect::<Vec<_>>();
+		if !unstaged.is_empty() {
+			stages.push(unstaged);
+		}
+		snarl.stage_count = stages.len();
+		snarl.stages.clear();
+		for (i, stage) in stages.into_iter().enumerate() {
 			for (j, id) in stage.into_iter().enumerate() {
+				snarl.stages.insert(id, i);
+				if snarl.hidden.contains(&id) {
+					continue;
+				}
 				if !nodes.iter().any(|(_, node)| *node == id) {
-					let node = snarl.snarl.insert_node(
-						bevy_egui::egui::Pos2::new(i as f32 * 400.0, j as f32 * 96.0),
-						id,
-					);
+					let pos = layout
+						.as_ref()
+						.and_then(|layout| layout.position(tracker.providers()[&id].name()))
+						.unwrap_or(Pos2::new(i as f32 * 400.0, j as f32 * 96.0));
+					let node = snarl.snarl.insert_node(pos, id);
 					nodes.insert(node, id);
 				}
 			}
@@ -278,7 +833,9 @@ pub fn sync_snarl<K: SetupKey>(
 				.iter()
 				.find_map(|(nid, node)| (*node == *id).then_some(*nid))
 			else {
-				bevy_log::error!("Missing Snarl node for provider: {id:?}");
+				if !snarl.hidden.contains(id) {
+					bevy_log::error!("Missing Snarl node for provider: {id:?}");
+				}
 				continue;
 			};
 
@@ -288,7 +845,9 @@ pub fn sync_snarl<K: SetupKey>(
 						.iter()
 						.find_map(|(nid, node)| (*node == dependant).then_some(*nid))
 					else {
-						bevy_log::error!("Missing Snarl node for dependency: {dependant:?}");
+						if !snarl.hidden.contains(&dependant) {
+							bevy_log::error!("Missing Snarl node for dependency: {dependant:?}");
+						}
 						continue;
 					};
 
@@ -306,6 +865,97 @@ pub fn sync_snarl<K: SetupKey>(
 			}
 		}
 	}
+
+	// Collapse nodes in collapsed stages, and reopen the rest
+	let toggled = snarl
+		.snarl
+		.nodes_ids_data()
+		.filter(|(_, node)| {
+			let collapsed = snarl
+				.stages
+				.get(&node.value)
+				.is_some_and(|stage| snarl.collapsed_stages.contains(stage));
+			node.open == collapsed
+		})
+		.map(|(nid, node)| (nid, node.open))
+		.collect::<Vec<_>>();
+	for (nid, open) in toggled {
+		snarl.snarl.open_node(nid, !open);
+	}
+
+	// Record where the user has moved nodes to
+	if let Some(layout) = &mut layout {
+		for (_, node) in snarl.snarl.nodes_ids_data() {
+			let Some(info) = tracker.providers().get(&node.value) else {
+				continue;
+			};
+			// Only write on change, to keep change detection quiet
+			if layout.position(info.name()) != Some(node.pos) {
+				layout.set_position(info.name(), node.pos);
+			}
+		}
+	}
+}
+
+/// System that applies the edits made to the graph in [edit mode](SetupGraphVisState::edit_mode)
+/// to the tracker, and logs each provider's new requirements and provided keys.
+///
+/// The graph is rebuilt and re-validated by [`sync_snarl`] once the tracker has changed.
+pub fn apply_graph_edits<K: SetupKey + Debug>(world: &mut World) {
+	let Some(mut state) = world.get_resource_mut::<SetupGraphVisState<K>>() else {
+		return;
+	};
+	if state.edits.is_empty() {
+		return;
+	}
+	let edits = std::mem::take(&mut state.edits);
+	if !world.contains_resource::<SetupTracker<K>>() {
+		// The edits are dropped along with the providers they were made to
+		return;
+	}
+	world.resource_scope(|world, mut tracker: Mut<SetupTracker<K>>| {
+		for edit in edits {
+			match edit {
+				GraphEdit::Requires(system, requires) => {
+					if let Some(info) = tracker.providers().get(&system) {
+						info!("`{}` now requires {requires:?}", info.name());
+					}
+					tracker.set_requires(system, requires, world);
+				}
+				GraphEdit::Provides(system, provides) => {
+					if let Some(info) = tracker.providers().get(&system) {
+						info!("`{}` now provides {provides:?}", info.name());
+					}
+					tracker.set_provides(system, provides, world);
+				}
+			}
+		}
+	});
+}
+
+/// Returns the providers whose name, or the label of any key they require or provide, contains
+/// `search`, ignoring case.
+fn search_matches<K: SetupKey + Debug>(
+	tracker: &SetupTracker<K>,
+	search: &str,
+) -> HashSet<bevy_ecs::system::SystemId> {
+	if search.is_empty() {
+		return HashSet::default();
+	}
+	let search = search.to_lowercase();
+	tracker
+		.providers()
+		.iter()
+		.filter(|(_, info)| {
+			info.name().to_lowercase().contains(&search)
+				|| info
+					.requires()
+					.iter()
+					.chain(info.provides())
+					.any(|key| tracker.key_label(key).to_lowercase().contains(&search))
+		})
+		.map(|(id, _)| *id)
+		.collect()
 }
 
 /// Draws the setup graph visualization within the provided UI context.
@@ -324,11 +974,25 @@ pub fn sync_snarl<K: SetupKey>(
 ///
 /// Returns the response from the snarl widget, which can be used to detect
 /// interactions with the graph.
+///
+/// If the graph exceeds its [`GraphLimits`](crate::GraphLimits), a summary is shown instead of the
+/// graph itself.
 pub fn draw_setup_graph<K: SetupKey + Debug>(
 	ui: &mut bevy_egui::egui::Ui,
 	graph: &SetupTracker<K>,
 	state: &mut SetupGraphVisState<K>,
 ) {
+	if let Err(too_large) = graph.check_limits() {
+		ui.label(format!(
+			"{} providers, {} keys, {} edges",
+			graph.providers().len(),
+			too_large.keys,
+			too_large.edges,
+		));
+		ui.label(too_large.to_string());
+		return;
+	}
+
 	let style = SnarlStyle {
 		node_layout: Some(NodeLayout::sandwich()),
 		pin_fill: Some(Color32::WHITE),
@@ -342,14 +1006,350 @@ pub fn draw_setup_graph<K: SetupKey + Debug>(
 		..Default::default()
 	};
 
-	state.snarl.show(
-		&mut SetupGraphViewer(graph),
-		&style,
-		std::any::type_name::<SetupTracker<K>>(),
+	bevy_egui::egui::TopBottomPanel::top(std::any::type_name::<GraphErrors<K>>()).show_inside(
+		ui,
+		|ui| {
+			ui.horizontal(|ui| {
+				ui.selectable_value(&mut state.view, GraphView::Graph, "Graph");
+				ui.selectable_value(&mut state.view, GraphView::Timeline, "Timeline");
+			});
+			draw_progress_sparkline(ui, graph);
+			if state.view == GraphView::Timeline {
+				return;
+			}
+			ui.horizontal(|ui| {
+				ui.checkbox(&mut state.edit_mode, "Edit").on_hover_text(
+					"Drag wires to change what providers require and provide, until the app exits",
+				);
+				ui.checkbox(&mut state.show_critical_path, "Critical path");
+				ui.add_enabled_ui(state.show_critical_path, |ui| {
+					ui.radio_value(&mut state.path_weight, PathWeight::Estimated, "Estimated");
+					ui.radio_value(&mut state.path_weight, PathWeight::Measured, "Measured");
+				});
+			});
+			ui.horizontal(|ui| {
+				ui.label("Search");
+				ui.text_edit_singleline(&mut state.search);
+				ui.checkbox(&mut state.only_matches, "Only matches");
+				ui.checkbox(&mut state.hide_finished, "Hide finished");
+				ui.menu_button("Stages", |ui| {
+					for stage in 0..state.stage_count {
+						let mut collapsed = state.collapsed_stages.contains(&stage);
+						if ui
+							.checkbox(&mut collapsed, format!("Collapse stage {stage}"))
+							.changed()
+						{
+							if collapsed {
+								state.collapsed_stages.insert(stage);
+							} else {
+								state.collapsed_stages.remove(&stage);
+							}
+						}
+					}
+				});
+			});
+			for error in &state.errors.errors {
+				ui.colored_label(Color32::RED, error.to_string());
+			}
+		},
+	);
+	if state.view == GraphView::Timeline {
+		draw_setup_timeline(ui, graph);
+		return;
+	}
+	let critical_path = if state.show_critical_path {
+		graph.critical_path(state.path_weight)
+	} else {
+		Vec::new()
+	};
+
+	let widget = SnarlWidget::new()
+		.id_salt(std::any::type_name::<SetupTracker<K>>())
+		.style(style);
+	// Selection is stored by the widget, so this is the selection as of the previous frame
+	let selected = widget
+		.get_selected_nodes(ui)
+		.into_iter()
+		.filter_map(|node| state.snarl.get_node(node).copied())
+		.collect::<Vec<_>>();
+	if !selected.is_empty() {
+		bevy_egui::egui::SidePanel::right(std::any::type_name::<SetupGraphVisState<K>>())
+			.resizable(true)
+			.show_inside(ui, |ui| {
+				bevy_egui::egui::ScrollArea::vertical().show(ui, |ui| {
+					for system in selected {
+						draw_provider_inspector(ui, graph, &state.errors, system);
+						ui.separator();
+					}
+				});
+			});
+	}
+
+	widget.show(
+		&mut state.snarl,
+		&mut SetupGraphViewer {
+			tracker: graph,
+			errors: &state.errors,
+			critical_path: &critical_path,
+			matches: &state.matches,
+			edits: state.edit_mode.then_some(&mut state.edits),
+		},
 		ui,
 	);
 }
 
+/// Draws a Gantt chart of the tracker's [measured timeline](SetupTracker::measured_timeline), with
+/// one bar per provider from when it was dispatched to when its keys finished, grouped by stage.
+///
+/// Unfinished providers extend to the current time, and hovering over a bar shows its exact times.
+/// This shows how much providers actually run in parallel, and where setup is waiting.
+pub fn draw_setup_timeline<K: SetupKey>(ui: &mut Ui, graph: &SetupTracker<K>) {
+	const LABEL_WIDTH: f32 = 160.0;
+	const ROW_HEIGHT: f32 = 20.0;
+
+	let spans = graph.measured_timeline();
+	let Some(first) = graph.started.values().min() else {
+		ui.label("No providers have been dispatched yet");
+		return;
+	};
+	let now = bevy_platform::time::Instant::now().saturating_duration_since(*first);
+	let end_of = |span: &MeasuredSpan| span.end.unwrap_or(now);
+	let total = spans
+		.iter()
+		.map(end_of)
+		.max()
+		.unwrap_or_default()
+		.as_secs_f32()
+		.max(f32::EPSILON);
+
+	ScrollArea::both().show(ui, |ui| {
+		let width = ui.available_width().max(LABEL_WIDTH + 200.0);
+		let (rect, response) =
+			ui.allocate_exact_size(vec2(width, ROW_HEIGHT * spans.len() as f32), Sense::hover());
+		let painter = ui.painter_at(rect);
+		let scale = (width - LABEL_WIDTH) / total;
+		let text_color = ui.visuals().text_color();
+		let mut hovered = None;
+		for (row, span) in spans.iter().enumerate() {
+			let top = rect.top() + row as f32 * ROW_HEIGHT;
+			if row > 0 && spans[row - 1].stage != span.stage {
+				painter.hline(
+					rect.x_range(),
+					top,
+					Stroke::new(1.0, Color32::from_gray(96)),
+				);
+			}
+			painter.text(
+				pos2(rect.left(), top + ROW_HEIGHT / 2.0),
+				Align2::LEFT_CENTER,
+				&span.name,
+				FontId::proportional(12.0),
+				text_color,
+			);
+			let x =
+				|time: std::time::Duration| rect.left() + LABEL_WIDTH + time.as_secs_f32() * scale;
+			let bar = Rect::from_min_max(
+				pos2(x(span.start), top + 3.0),
+				pos2(
+					x(end_of(span)).max(x(span.start) + 2.0),
+					top + ROW_HEIGHT - 3.0,
+				),
+			);
+			let color = match (span.end, span.stage % 2) {
+				(None, _) => Color32::YELLOW,
+				(Some(_), 0) => Color32::from_rgb(80, 160, 80),
+				(Some(_), _) => Color32::from_rgb(60, 120, 180),
+			};
+			painter.rect_filled(bar, 2.0, color);
+			if response.hover_pos().is_some_and(|pos| bar.contains(pos)) {
+				hovered = Some(span);
+			}
+		}
+		if let Some(span) = hovered {
+			let end = span.end.map_or_else(
+				|| "running".into(),
+				|end| format!("{:.3}s", end.as_secs_f32()),
+			);
+			response.on_hover_text_at_pointer(format!(
+				"{} (stage {})\n{:.3}s - {end}",
+				span.name,
+				span.stage,
+				span.start.as_secs_f32()
+			));
+		}
+	});
+}
+
+/// Draws the details of a single provider: its name, requirements, provisions with their last
+/// known progress, and [run statistics](SetupTracker::run_stats).
+fn draw_provider_inspector<K: SetupKey + Debug>(
+	ui: &mut Ui,
+	graph: &SetupTracker<K>,
+	errors: &GraphErrors<K>,
+	system: bevy_ecs::system::SystemId,
+) {
+	let Some(info) = graph.providers().get(&system) else {
+		return;
+	};
+	ui.heading(info.name());
+	if let Some(description) = info.description() {
+		ui.label(description);
+	}
+	let stats = graph.run_stats(system);
+	Grid::new(system).num_columns(2).show(ui, |ui| {
+		ui.strong("Runs");
+		ui.label(stats.map_or(0, |stats| stats.runs()).to_string());
+		ui.end_row();
+		ui.strong("Last run");
+		ui.label(
+			stats
+				.map(|stats| format!("{:.3}ms", stats.last_duration().as_secs_f64() * 1000.0))
+				.unwrap_or_default(),
+		);
+		ui.end_row();
+		if let Some(error) = stats.and_then(ProviderRunStats::last_error) {
+			ui.strong("Error");
+			ui.colored_label(Color32::RED, error);
+			ui.end_row();
+		}
+	});
+
+	for (heading, keys) in [("Requires", info.requires()), ("Provides", info.provides())] {
+		if keys.is_empty() {
+			continue;
+		}
+		ui.strong(heading);
+		for key in keys {
+			let progress = graph
+				.last_key_progress(key)
+				.map(|progress| format!("{:?}", *progress))
+				.unwrap_or_else(|| "-".into());
+			ui.colored_label(
+				SetupGraphViewer {
+					tracker: graph,
+					errors,
+					critical_path: &[],
+					matches: &HashSet::default(),
+				}
+				.key_status_color(key),
+				format!("{}: {progress}", graph.key_label(key)),
+			);
+		}
+	}
+}
+
+/// Draws a panel listing every setup key with its progress bar, weight, and elapsed time.
+///
+/// This complements [`draw_setup_graph`] for apps that want a simple loading overlay rather than
+/// a node graph. Per-key progress is read from the keys' [`SetupKeyState`] entities, as of the last
+/// time [`advance_setup`](crate::advance_setup) ran. See [`draw_setup_progress_overlay`] for a
+/// ready-made window.
+pub fn draw_setup_progress_panel<K: SetupKey + Debug>(
+	ui: &mut Ui,
+	tracker: &SetupTracker<K>,
+	keys: &Query<&SetupKeyState<K>>,
+) {
+	let progress = tracker.last_progress();
+	ui.add(ProgressBar::new(finite_or_zero(progress)).text(format!("{progress:.0}")));
+	draw_progress_sparkline(ui, tracker);
+	ui.separator();
+	Grid::new(std::any::type_name::<SetupKeyState<K>>())
+		.num_columns(4)
+		.striped(true)
+		.show(ui, |ui| {
+			ui.strong("Key");
+			ui.strong("Progress");
+			ui.strong("Weight");
+			ui.strong("Elapsed");
+			ui.end_row();
+
+			for key in tracker.entries().keys() {
+				let Some(state) = tracker
+					.key_entity(key)
+					.and_then(|entity| keys.get(entity).ok())
+				else {
+					continue;
+				};
+				ui.label(tracker.key_label(key));
+				let bar = ProgressBar::new(finite_or_zero(state.progress))
+					.desired_width(160.0)
+					.text(format!("{:.0}", state.progress));
+				ui.add(if state.failed {
+					bar.fill(Color32::RED)
+				} else {
+					bar
+				});
+				ui.label(format!("{}", tracker.key_weight(key)));
+				ui.label(
+					tracker
+						.key_elapsed(key)
+						.map(|elapsed| format!("{:.2}s", elapsed.as_secs_f32()))
+						.unwrap_or_default(),
+				);
+				ui.end_row();
+			}
+		});
+}
+
+/// Draws a sparkline of the tracker's [progress history](SetupTracker::progress_history), which
+/// makes stalls (flat stretches) and regressions (dips) visible at a glance.
+pub fn draw_progress_sparkline<K: SetupKey>(ui: &mut Ui, tracker: &SetupTracker<K>) {
+	let (rect, _) = ui.allocate_exact_size(vec2(ui.available_width(), 32.0), Sense::hover());
+	let painter = ui.painter_at(rect);
+	painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+	let mut history = tracker.progress_history().peekable();
+	let Some(&(first, _)) = history.peek() else {
+		return;
+	};
+	let samples = history
+		.map(|(time, progress)| (time.duration_since(first).as_secs_f32(), progress))
+		.collect::<Vec<_>>();
+	let span = samples
+		.last()
+		.map_or(0.0, |(time, _)| *time)
+		.max(f32::EPSILON);
+	let points = samples
+		.into_iter()
+		.map(|(time, progress)| {
+			pos2(
+				rect.left() + time / span * rect.width(),
+				rect.bottom() - finite_or_zero(progress) * rect.height(),
+			)
+		})
+		.collect::<Vec<_>>();
+	painter.add(Shape::line(
+		points,
+		Stroke::new(1.5, ui.visuals().selection.bg_fill),
+	));
+}
+
+/// Returns the progress as a fraction for egui, treating non-finite values as zero.
+fn finite_or_zero(progress: Progress) -> f32 {
+	if progress.is_finite() { *progress } else { 0.0 }
+}
+
+/// System that draws [`draw_setup_progress_panel`] in a window while the tracker exists.
+///
+/// Add it to the `EguiPrimaryContextPass` schedule to show a per-key loading overlay.
+pub fn draw_setup_progress_overlay<K: SetupKey + Debug>(
+	tracker: Option<Res<SetupTracker<K>>>,
+	keys: Query<&SetupKeyState<K>>,
+	mut contexts: EguiContexts,
+) {
+	let Some(tracker) = tracker else {
+		return;
+	};
+	let Ok(ctx) = contexts.ctx_mut() else {
+		error!("No egui context");
+		return;
+	};
+	bevy_egui::egui::Window::new(format!("{} Progress", disqualified::ShortName::of::<K>()))
+		.default_width(400.0)
+		.show(ctx, |ui| {
+			draw_setup_progress_panel(ui, &tracker, &keys);
+		});
+}
+
 /// Opens the setup graph visualization window.
 ///
 /// This function programmatically opens the dedicated visualization window.
@@ -394,7 +1394,12 @@ pub fn draw_setup_graph_window<K: SetupKey + Debug>(
 	graph: Res<SetupTracker<K>>,
 	mut contexts: EguiContexts,
 	mut state: Option<ResMut<SetupGraphVisState<K>>>,
+	tabs: Option<Res<SetupGraphTabs>>,
 ) {
+	if tabs.is_some_and(|tabs| tabs.is_open()) {
+		// Shown as a tab in the shared window instead
+		return;
+	}
 	let Ok(ctx) = contexts.ctx_mut() else {
 		error!("No egui context");
 		return;
@@ -422,3 +1427,188 @@ pub fn draw_setup_graph_window<K: SetupKey + Debug>(
 		commands.init_resource::<SetupGraphVisState<K>>();
 	}
 }
+
+/// System that draws a window with buttons to pause, resume, and [step](crate::SetupStepper::step)
+/// through setup, along with the provider run by the last step.
+///
+/// Added by [`SetupGraphVisualizationPlugin`] with the `debug` feature, and only shown while a
+/// [`SetupStepper`](crate::SetupStepper) exists.
+#[cfg(feature = "debug")]
+pub fn draw_setup_stepper_window<K: SetupKey + Debug>(
+	mut contexts: EguiContexts,
+	mut stepper: ResMut<crate::SetupStepper<K>>,
+	tracker: Option<Res<SetupTracker<K>>>,
+) {
+	let Ok(ctx) = contexts.ctx_mut() else {
+		error!("No egui context");
+		return;
+	};
+	bevy_egui::egui::Window::new(format!(
+		"SetupTracker<{}> Stepper",
+		disqualified::ShortName::of::<K>()
+	))
+	.show(ctx, |ui| {
+		ui.horizontal(|ui| {
+			if stepper.is_paused() {
+				if ui.button("Resume").clicked() {
+					stepper.resume();
+				}
+				if ui.button("Step").clicked() {
+					stepper.step();
+				}
+			} else if ui.button("Pause").clicked() {
+				stepper.pause();
+			}
+		});
+		if let Some(step) = stepper.last_step() {
+			ui.label(format!("Last step: {}", step.name));
+			for key in &step.finished {
+				let label = match &tracker {
+					Some(tracker) => tracker.key_label(key),
+					None => format!("{key:?}").into(),
+				};
+				ui.colored_label(Color32::GREEN, format!("✔ {label}"));
+			}
+		}
+	});
+}
+
+/// Plugin that adds a single window showing every tracker registered by a
+/// [`SetupGraphVisualizationPlugin`], with one tab per key type.
+///
+/// While the window is open, it replaces the per-key-type windows drawn by
+/// [`draw_setup_graph_window`]. Open and close it with [`toggle_setup_graph_tabs`] or
+/// [`SetupGraphTabs::set_open`].
+#[derive(Default)]
+pub struct SetupGraphTabsPlugin;
+
+impl Plugin for SetupGraphTabsPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<SetupGraphTabs>()
+			.add_systems(EguiPrimaryContextPass, draw_setup_graph_tabs);
+	}
+}
+
+/// Type-erased registry of the setup trackers that can be shown in the [`SetupGraphTabsPlugin`]
+/// window.
+///
+/// Each [`SetupGraphVisualizationPlugin`] registers its key type here.
+#[derive(Resource, Debug, Default)]
+pub struct SetupGraphTabs {
+	tabs: Vec<TrackerTab>,
+	selected: usize,
+	open: bool,
+}
+
+/// Functions to draw and close the graph of one `SetupTracker<K>`, without knowing `K`.
+#[derive(Debug)]
+struct TrackerTab {
+	name: String,
+	key_type: TypeId,
+	draw: fn(&mut Ui, &mut World),
+	close: fn(&mut World),
+}
+
+impl SetupGraphTabs {
+	/// Adds a tab for `SetupTracker<K>`, unless there already is one.
+	pub fn register<K: SetupKey + Debug>(&mut self) {
+		if self
+			.tabs
+			.iter()
+			.any(|tab| tab.key_type == TypeId::of::<K>())
+		{
+			return;
+		}
+		self.tabs.push(TrackerTab {
+			name: disqualified::ShortName::of::<K>().to_string(),
+			key_type: TypeId::of::<K>(),
+			draw: draw_tab::<K>,
+			close: |world| {
+				world.remove_resource::<SetupGraphVisState<K>>();
+			},
+		});
+	}
+
+	/// Returns the names of the registered key types, in tab order.
+	pub fn names(&self) -> impl Iterator<Item = &str> {
+		self.tabs.iter().map(|tab| tab.name.as_str())
+	}
+
+	/// Returns `true` if the window is open.
+	pub fn is_open(&self) -> bool {
+		self.open
+	}
+
+	/// Opens or closes the window.
+	pub fn set_open(&mut self, open: bool) {
+		self.open = open;
+	}
+}
+
+/// Draws the graph of `SetupTracker<K>`, creating its [`SetupGraphVisState`] if needed.
+fn draw_tab<K: SetupKey + Debug>(ui: &mut Ui, world: &mut World) {
+	if !world.contains_resource::<SetupTracker<K>>() {
+		ui.label("Setup has finished");
+		return;
+	}
+	if !world.contains_resource::<SetupGraphVisState<K>>() {
+		// Filled in by `sync_snarl` next frame
+		world.init_resource::<SetupGraphVisState<K>>();
+	}
+	world.resource_scope::<SetupGraphVisState<K>, _>(|world, mut state| {
+		draw_setup_graph(ui, world.resource::<SetupTracker<K>>(), &mut state);
+	});
+}
+
+/// Toggles the [`SetupGraphTabsPlugin`] window.
+pub fn toggle_setup_graph_tabs(mut tabs: ResMut<SetupGraphTabs>) {
+	tabs.open = !tabs.open;
+	info!(
+		"{} setup graph tabs window",
+		if tabs.open { "Opening" } else { "Closing" }
+	);
+}
+
+/// System that renders the [`SetupGraphTabsPlugin`] window.
+///
+/// The per-key-type [`SetupGraphVisState`]s are removed when the window closes, so their
+/// own windows don't reappear.
+pub fn draw_setup_graph_tabs(world: &mut World) {
+	if !world.resource::<SetupGraphTabs>().open {
+		return;
+	}
+	let Ok(mut context) = world
+		.query_filtered::<&mut EguiContext, With<PrimaryEguiContext>>()
+		.single_mut(world)
+	else {
+		error!("No egui context");
+		return;
+	};
+	let ctx = context.get_mut().clone();
+
+	world.resource_scope::<SetupGraphTabs, _>(|world, mut tabs| {
+		let mut open = true;
+		bevy_egui::egui::Window::new("Setup Graphs")
+			.open(&mut open)
+			.default_width(1200.0)
+			.default_height(800.0)
+			.show(&ctx, |ui| {
+				ui.horizontal(|ui| {
+					for i in 0..tabs.tabs.len() {
+						let name = tabs.tabs[i].name.clone();
+						ui.selectable_value(&mut tabs.selected, i, name);
+					}
+				});
+				ui.separator();
+				if let Some(tab) = tabs.tabs.get(tabs.selected) {
+					(tab.draw)(ui, world);
+				}
+			});
+		if !open {
+			tabs.open = false;
+			for tab in &tabs.tabs {
+				(tab.close)(world);
+			}
+		}
+	});
+}